@@ -1,29 +1,226 @@
+use crate::modules::{category, MODULE_IDS};
 use crate::pipeline::Pipeline;
 use eframe::egui;
 
+/// How many recently added modules the sidebar lists
+const RECENT_LIMIT: usize = 8;
+
 pub struct YuryCipherApp {
     pipeline: Pipeline,
     show_settings: bool,
     current_lang: String,
+    cyberchef_recipe: String,
+    /// Result of the last CyberChef import, shown under the import box
+    import_messages: Vec<String>,
+    /// File the recipe is saved to and loaded from
+    recipe_path: String,
+    /// Result of the last recipe save or load, shown in the top panel
+    recipe_message: String,
+    /// Ctrl+Shift+V runs the pipeline on the clipboard, Ctrl+Shift+C copies
+    /// the final output
+    clipboard_hotkeys: bool,
+    /// Module ids starred in the sidebar
+    favorites: Vec<String>,
+    /// Module ids in the order they were last added, most recent first
+    recents: Vec<String>,
+}
+
+/// Sidebar groups and the modules listed under each, in `MODULE_IDS` order.
+fn sidebar_groups() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut groups: Vec<(&'static str, Vec<&'static str>)> = Vec::new();
+    for &id in MODULE_IDS {
+        let group = category(id);
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, ids)) => ids.push(id),
+            None => groups.push((group, vec![id])),
+        }
+    }
+    groups
+}
+
+/// Read a list of module ids saved by `save`, dropping ones that no longer exist
+fn load_ids(storage: Option<&dyn eframe::Storage>, key: &str) -> Vec<String> {
+    storage
+        .and_then(|storage| storage.get_string(key))
+        .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|id| MODULE_IDS.contains(&id.as_str()))
+        .collect()
 }
 
 impl YuryCipherApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         Self {
             pipeline: Pipeline::default(),
             show_settings: false,
             current_lang: "en".to_string(),
+            cyberchef_recipe: String::new(),
+            import_messages: Vec::new(),
+            recipe_path: "recipe.json".to_string(),
+            recipe_message: String::new(),
+            clipboard_hotkeys: true,
+            favorites: load_ids(cc.storage, "favorites"),
+            recents: load_ids(cc.storage, "recents"),
+        }
+    }
+
+    fn add_module(&mut self, id: &str) {
+        self.pipeline.add_module(id);
+        self.recents.retain(|recent| recent != id);
+        self.recents.insert(0, id.to_string());
+        self.recents.truncate(RECENT_LIMIT);
+    }
+
+    /// A sidebar entry: a star to toggle the favorite and a button that adds
+    /// the module to the pipeline.
+    fn module_button(&mut self, ui: &mut egui::Ui, id: &str) {
+        let name_key = format!("modules.{}", id);
+        let tooltip_key = format!("tooltips.{}", id);
+        ui.horizontal(|ui| {
+            let favorite = self.favorites.iter().any(|f| f == id);
+            if ui
+                .selectable_label(favorite, if favorite { "★" } else { "☆" })
+                .on_hover_text(if favorite {
+                    "Remove from favorites"
+                } else {
+                    "Add to favorites"
+                })
+                .clicked()
+            {
+                if favorite {
+                    self.favorites.retain(|f| f != id);
+                } else {
+                    self.favorites.push(id.to_string());
+                }
+            }
+            if ui
+                .button(rust_i18n::t!(&name_key))
+                .on_hover_text(rust_i18n::t!(&tooltip_key))
+                .clicked()
+            {
+                self.add_module(id);
+            }
+        });
+    }
+
+    /// Take the Shift variants of paste and copy before a focused text box
+    /// sees them, so they work wherever the focus is.
+    fn handle_clipboard_hotkeys(&mut self, ctx: &egui::Context) {
+        let mut pasted = None;
+        let mut copy = false;
+        ctx.input_mut(|i| {
+            if !(i.modifiers.command && i.modifiers.shift) {
+                return;
+            }
+            i.events.retain(|event| match event {
+                egui::Event::Paste(text) => {
+                    pasted = Some(text.clone());
+                    false
+                }
+                egui::Event::Copy => {
+                    copy = true;
+                    false
+                }
+                _ => true,
+            });
+        });
+        if let Some(text) = pasted {
+            self.pipeline.set_input(text);
+        }
+        if copy {
+            if let Some(output) = self.pipeline.final_output() {
+                ctx.copy_text(output.to_string());
+            }
         }
     }
 }
 
 impl eframe::App for YuryCipherApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        for (key, ids) in [("favorites", &self.favorites), ("recents", &self.recents)] {
+            storage.set_string(key, serde_json::to_string(ids).unwrap_or_default());
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.clipboard_hotkeys {
+            self.handle_clipboard_hotkeys(ctx);
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("Reset Pipeline").clicked() {
                     self.pipeline.clear();
                 }
+                if ui
+                    .button("Export recipe")
+                    .on_hover_text("Copy the pipeline as a numbered list of steps")
+                    .clicked()
+                {
+                    let recipe = self.pipeline.recipe_text();
+                    ui.output_mut(|o| o.copied_text = recipe);
+                }
+                ui.menu_button("Inverse", |ui| {
+                    let steps = self.pipeline.inverse_preview();
+                    if steps.is_empty() {
+                        ui.label("No enabled stages");
+                        return;
+                    }
+                    ui.strong("Chain that undoes this one");
+                    for (idx, step) in steps.iter().enumerate() {
+                        match step {
+                            Ok(step) => ui.label(format!("{}. {}", idx + 1, step)),
+                            Err(e) => ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                format!("{}. ✗ {}", idx + 1, e),
+                            ),
+                        };
+                    }
+                    if steps.iter().any(|step| step.is_err()) {
+                        ui.small("Stages marked ✗ lose information or have no opposite direction");
+                    }
+                })
+                .response
+                .on_hover_text("Preview the stages, last to first and each with the settings that undo it, that turn the output back into the input");
+                ui.separator();
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.recipe_path)
+                        .hint_text("recipe.json")
+                        .desired_width(140.0),
+                );
+                if ui
+                    .button("Save recipe")
+                    .on_hover_text("Save the modules and their settings to this JSON file")
+                    .clicked()
+                {
+                    let recipe =
+                        serde_json::to_string_pretty(&self.pipeline.to_json()).unwrap_or_default();
+                    self.recipe_message = match std::fs::write(&self.recipe_path, recipe) {
+                        Ok(()) => format!("Saved to {}", self.recipe_path),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                }
+                if ui
+                    .button("Load recipe")
+                    .on_hover_text("Replace the pipeline with the recipe in this JSON file")
+                    .clicked()
+                {
+                    self.recipe_message = match std::fs::read_to_string(&self.recipe_path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|recipe| self.pipeline.from_json(&recipe))
+                    {
+                        Ok(warnings) if warnings.is_empty() => {
+                            format!("Loaded {}", self.recipe_path)
+                        }
+                        Ok(warnings) => warnings.join("; "),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                }
+                if !self.recipe_message.is_empty() {
+                    ui.small(&self.recipe_message);
+                }
+                ui.separator();
                 if ui.button("Settings").clicked() {
                     self.show_settings = true;
                 }
@@ -35,280 +232,33 @@ impl eframe::App for YuryCipherApp {
             ui.separator();
 
             egui::ScrollArea::vertical().show(ui, |ui| {
-                egui::CollapsingHeader::new("Transform")
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.replace"))
-                            .on_hover_text(rust_i18n::t!("tooltips.replace"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("replace");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.reverse"))
-                            .on_hover_text(rust_i18n::t!("tooltips.reverse"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("reverse");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.case_transform"))
-                            .on_hover_text(rust_i18n::t!("tooltips.case_transform"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("case_transform");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.numeral"))
-                            .on_hover_text(rust_i18n::t!("tooltips.numeral"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("numeral");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.bitwise"))
-                            .on_hover_text(rust_i18n::t!("tooltips.bitwise"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("bitwise");
-                        }
-                    });
-
-                egui::CollapsingHeader::new("Alphabets")
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.morse"))
-                            .on_hover_text(rust_i18n::t!("tooltips.morse"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("morse");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.spelling"))
-                            .on_hover_text(rust_i18n::t!("tooltips.spelling"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("spelling");
-                        }
-                    });
-
-                egui::CollapsingHeader::new("Ciphers")
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.enigma"))
-                            .on_hover_text(rust_i18n::t!("tooltips.enigma"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("enigma");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.caesar"))
-                            .on_hover_text(rust_i18n::t!("tooltips.caesar"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("caesar");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.affine"))
-                            .on_hover_text(rust_i18n::t!("tooltips.affine"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("affine");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.rot13"))
-                            .on_hover_text(rust_i18n::t!("tooltips.rot13"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("rot13");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.a1z26"))
-                            .on_hover_text(rust_i18n::t!("tooltips.a1z26"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("a1z26");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.vigenere"))
-                            .on_hover_text(rust_i18n::t!("tooltips.vigenere"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("vigenere");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.bacon"))
-                            .on_hover_text(rust_i18n::t!("tooltips.bacon"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("bacon");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.substitution"))
-                            .on_hover_text(rust_i18n::t!("tooltips.substitution"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("substitution");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.rail_fence"))
-                            .on_hover_text(rust_i18n::t!("tooltips.rail_fence"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("rail_fence");
-                        }
-                    });
-
-                egui::CollapsingHeader::new("Polybius Square Ciphers")
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.polybius"))
-                            .on_hover_text(rust_i18n::t!("tooltips.polybius"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("polybius");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.tap_code"))
-                            .on_hover_text(rust_i18n::t!("tooltips.tap_code"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("tap_code");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.adfgx"))
-                            .on_hover_text(rust_i18n::t!("tooltips.adfgx"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("adfgx");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.bifid"))
-                            .on_hover_text(rust_i18n::t!("tooltips.bifid"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("bifid");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.nihilist"))
-                            .on_hover_text(rust_i18n::t!("tooltips.nihilist"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("nihilist");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.trifid"))
-                            .on_hover_text(rust_i18n::t!("tooltips.trifid"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("trifid");
-                        }
-                    });
-
-                egui::CollapsingHeader::new("Encoding")
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.base32"))
-                            .on_hover_text(rust_i18n::t!("tooltips.base32"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("base32");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.base64"))
-                            .on_hover_text(rust_i18n::t!("tooltips.base64"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("base64");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.ascii85"))
-                            .on_hover_text(rust_i18n::t!("tooltips.ascii85"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("ascii85");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.baudot"))
-                            .on_hover_text(rust_i18n::t!("tooltips.baudot"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("baudot");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.unicode"))
-                            .on_hover_text(rust_i18n::t!("tooltips.unicode"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("unicode");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.url"))
-                            .on_hover_text(rust_i18n::t!("tooltips.url"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("url");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.punycode"))
-                            .on_hover_text(rust_i18n::t!("tooltips.punycode"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("punycode");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.bootstring"))
-                            .on_hover_text(rust_i18n::t!("tooltips.bootstring"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("bootstring");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.integer"))
-                            .on_hover_text(rust_i18n::t!("tooltips.integer"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("integer");
-                        }
-                    });
-
-                egui::CollapsingHeader::new("Modern Cryptography")
-                    .default_open(false)
-                    .show(ui, |ui| {
-                        if ui
-                            .button(rust_i18n::t!("modules.block_cipher"))
-                            .on_hover_text(rust_i18n::t!("tooltips.block_cipher"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("block_cipher");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.rc4"))
-                            .on_hover_text(rust_i18n::t!("tooltips.rc4"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("rc4");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.hash"))
-                            .on_hover_text(rust_i18n::t!("tooltips.hash"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("hash");
-                        }
-                        if ui
-                            .button(rust_i18n::t!("modules.hmac"))
-                            .on_hover_text(rust_i18n::t!("tooltips.hmac"))
-                            .clicked()
-                        {
-                            self.pipeline.add_module("hmac");
-                        }
-                    });
+                if !self.favorites.is_empty() {
+                    egui::CollapsingHeader::new("Favorites")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for id in self.favorites.clone() {
+                                self.module_button(ui, &id);
+                            }
+                        });
+                }
+                if !self.recents.is_empty() {
+                    egui::CollapsingHeader::new("Recently used")
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for id in self.recents.clone() {
+                                self.module_button(ui, &id);
+                            }
+                        });
+                }
+                for (group, ids) in sidebar_groups() {
+                    egui::CollapsingHeader::new(group)
+                        .default_open(group == "Transform")
+                        .show(ui, |ui| {
+                            for id in ids {
+                                self.module_button(ui, id);
+                            }
+                        });
+                }
             });
         });
 
@@ -351,7 +301,56 @@ impl eframe::App for YuryCipherApp {
                                 rust_i18n::set_locale("zh-CN");
                             }
                         });
+
+                    ui.separator();
+                    ui.heading("Processing");
+                    self.pipeline.settings_ui(ui);
+
+                    ui.separator();
+                    ui.heading("Hotkeys");
+                    ui.checkbox(&mut self.clipboard_hotkeys, "Clipboard hotkeys")
+                        .on_hover_text("Use the app as a quick converter for the current pipeline");
+                    ui.small("Ctrl+Shift+V: replace the input with the clipboard and run");
+                    ui.small("Ctrl+Shift+C: copy the final output");
+
+                    ui.separator();
+                    ui.heading("Import CyberChef recipe");
+                    ui.label("Paste a recipe saved from CyberChef as JSON. It replaces the current pipeline.");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut self.cyberchef_recipe)
+                            .hint_text(r#"[{"op":"From Base64","args":["A-Za-z0-9+/=",true]}]"#)
+                            .desired_rows(4)
+                            .desired_width(f32::INFINITY),
+                    );
+                    if ui.button("Import").clicked() {
+                        self.import_messages =
+                            match self.pipeline.import_cyberchef(&self.cyberchef_recipe) {
+                                Ok(warnings) if warnings.is_empty() => {
+                                    vec!["Recipe imported".to_string()]
+                                }
+                                Ok(warnings) => warnings,
+                                Err(e) => vec![e],
+                            };
+                    }
+                    for message in &self.import_messages {
+                        ui.small(message);
+                    }
                 });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::create_module;
+
+    #[test]
+    fn test_every_module_id_round_trips() {
+        for &id in MODULE_IDS {
+            let module = create_module(id).unwrap_or_else(|| panic!("no module for {id}"));
+            assert_eq!(module.id(), id);
+        }
+        assert!(create_module("no_such_module").is_none());
+    }
+}
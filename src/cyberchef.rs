@@ -0,0 +1,378 @@
+//! Best-effort import of CyberChef recipes (the JSON form from "Save recipe").
+//!
+//! Supported operations:
+//! - To/From Base64, To/From Base32, To/From Base85, To/From Binary
+//! - ROT13 (other amounts become Caesar), ROT47 (any amount), Atbash, XOR with a single-byte key
+//! - Reverse, To Upper case, To Lower case, Find / Replace
+//! - Vigenère, Affine, A1Z26, Rail Fence and Bacon encode/decode
+//! - To/From Morse Code, To/From Punycode, URL Encode/Decode
+//! - Gzip/Gunzip, Zlib Deflate/Inflate, Raw Deflate/Inflate
+//! - MD5, SHA2 (256-bit only), CRC-32 Checksum, Adler-32 Checksum
+//!
+//! Anything else is skipped with a warning. Because the compression modules
+//! carry their binary side as hex or Base64, a "From Base64"/"From Hex" right
+//! before a decompression (or "To Base64"/"To Hex" right after a compression)
+//! is folded into that stage.
+
+use serde_json::{json, Value};
+
+/// A module id from `modules::create_module` plus the settings to import into it.
+pub struct ImportedStage {
+    pub id: &'static str,
+    pub config: Value,
+}
+
+/// Translate a CyberChef recipe into stages, returning warnings for
+/// operations that were skipped or only partially translated.
+pub fn parse_recipe(recipe: &str) -> Result<(Vec<ImportedStage>, Vec<String>), String> {
+    let ops: Vec<Value> = serde_json::from_str(recipe.trim())
+        .map_err(|e| format!("Not a CyberChef recipe: {}", e))?;
+
+    let mut stages = Vec::new();
+    let mut warnings = Vec::new();
+    let mut idx = 0;
+
+    while idx < ops.len() {
+        let op = ops[idx]["op"].as_str().unwrap_or_default();
+        let args = ops[idx]["args"].as_array().cloned().unwrap_or_default();
+        let next_op = ops.get(idx + 1).and_then(|o| o["op"].as_str());
+        idx += 1;
+
+        if ops[idx - 1]["disabled"].as_bool() == Some(true) {
+            warnings.push(format!("Skipped disabled operation \"{}\"", op));
+            continue;
+        }
+
+        // Fold the text encoding of compressed data into the compression stage
+        if let Some(encoding) = binary_encoding(op, "From") {
+            if let Some(id) = next_op.and_then(decompression_id) {
+                stages.push(ImportedStage {
+                    id,
+                    config: json!({ "mode": "Decompress", "encoding": encoding }),
+                });
+                idx += 1;
+                continue;
+            }
+        }
+        if let Some(id) = compression_id(op) {
+            if let Some(encoding) = next_op.and_then(|next| binary_encoding(next, "To")) {
+                stages.push(ImportedStage {
+                    id,
+                    config: json!({ "mode": "Compress", "encoding": encoding }),
+                });
+                idx += 1;
+                continue;
+            }
+        }
+
+        match translate(op, &args) {
+            Ok((stage, warning)) => {
+                stages.push(stage);
+                warnings.extend(warning);
+            }
+            Err(warning) => warnings.push(warning),
+        }
+    }
+
+    Ok((stages, warnings))
+}
+
+fn stage(id: &'static str, config: Value) -> ImportedStage {
+    ImportedStage { id, config }
+}
+
+/// Map one operation; the optional warning notes arguments that were ignored.
+fn translate(op: &str, args: &[Value]) -> Result<(ImportedStage, Option<String>), String> {
+    let arg_str = |i: usize| args.get(i).and_then(Value::as_str).unwrap_or_default();
+    let arg_int = |i: usize| {
+        args.get(i)
+            .and_then(|v| v.as_i64().or_else(|| v.as_str()?.parse().ok()))
+    };
+    let direction = |encode: bool| if encode { "Encode" } else { "Decode" };
+
+    let imported = match op {
+        "To Base64" | "From Base64" => {
+            stage("base64", json!({ "mode": direction(op.starts_with("To")) }))
+        }
+        "To Base32" | "From Base32" => {
+            stage("base32", json!({ "mode": direction(op.starts_with("To")) }))
+        }
+        "To Base85" | "From Base85" => stage(
+            "ascii85",
+            json!({ "mode": direction(op.starts_with("To")) }),
+        ),
+        "ROT13" => match arg_int(3).unwrap_or(13) {
+            13 => stage("rot13", Value::Null),
+            amount => stage("caesar", json!({ "shift": amount, "mode": "Encode" })),
+        },
+        "To Binary" | "From Binary" => {
+            let separator = match arg_str(0) {
+                "None" => "",
+                "Comma" => ",",
+                "Semi-colon" => ";",
+                "Colon" => ":",
+                "Line feed" => "\n",
+                "CRLF" => "\r\n",
+                _ => " ",
+            };
+            stage(
+                "binary_text",
+                json!({
+                    "mode": direction(op.starts_with("To")),
+                    "separator": separator,
+                    "width": arg_int(1).unwrap_or(8),
+                }),
+            )
+        }
+        "ROT47" => match arg_int(0).unwrap_or(47) {
+            47 => stage("rot47", Value::Null),
+            amount => stage(
+                "rotate",
+                json!({ "amount": amount, "set": "Printable", "mode": "Encode" }),
+            ),
+        },
+        "Atbash Cipher" => stage("atbash", Value::Null),
+        "XOR" => {
+            let key = &args.first().cloned().unwrap_or_default();
+            let byte = single_byte_key(
+                key["option"].as_str().unwrap_or("Hex"),
+                key["string"].as_str().unwrap_or_default(),
+            )
+            .ok_or_else(|| "Skipped \"XOR\": only single-byte keys are supported".to_string())?;
+            stage(
+                "bitwise",
+                json!({ "op": "XOR", "operand": byte.to_string() }),
+            )
+        }
+        "Reverse" => {
+            let warning = (!matches!(arg_str(0), "" | "Character")).then(|| {
+                format!(
+                    "\"Reverse\" by {} is imported as reverse by character",
+                    arg_str(0)
+                )
+            });
+            return Ok((stage("reverse", Value::Null), warning));
+        }
+        "To Upper case" => stage("case_transform", json!({ "mode": "UpperCase" })),
+        "To Lower case" => stage("case_transform", json!({ "mode": "LowerCase" })),
+        "Find / Replace" => {
+            let find = &args.first().cloned().unwrap_or_default();
+            if find["option"].as_str() == Some("Regex") {
+                return Err(
+                    "Skipped \"Find / Replace\": regex patterns are not supported".to_string(),
+                );
+            }
+            stage(
+                "replace",
+                json!({ "find": find["string"].as_str().unwrap_or_default(), "replace": arg_str(1) }),
+            )
+        }
+        "Vigenère Encode" | "Vigenère Decode" => stage(
+            "vigenere",
+            json!({ "key": arg_str(0), "mode": direction(op.ends_with("Encode")) }),
+        ),
+        "Affine Cipher Encode" | "Affine Cipher Decode" => stage(
+            "affine",
+            json!({
+                "a": arg_int(0).unwrap_or(1),
+                "b": arg_int(1).unwrap_or(0),
+                "mode": direction(op.ends_with("Encode")),
+            }),
+        ),
+        "A1Z26 Cipher Encode" | "A1Z26 Cipher Decode" => stage(
+            "a1z26",
+            json!({ "mode": direction(op.ends_with("Encode")) }),
+        ),
+        "Rail Fence Cipher Encode" | "Rail Fence Cipher Decode" => {
+            let warning = (arg_int(1).unwrap_or(0) != 0).then(|| {
+                "\"Rail Fence Cipher\" offset is not supported and was ignored".to_string()
+            });
+            return Ok((
+                stage(
+                    "rail_fence",
+                    json!({ "rails": arg_int(0).unwrap_or(2), "mode": direction(op.ends_with("Encode")) }),
+                ),
+                warning,
+            ));
+        }
+        "Bacon Cipher Encode" | "Bacon Cipher Decode" => stage(
+            "bacon",
+            json!({ "mode": direction(op.ends_with("Encode")) }),
+        ),
+        "To Morse Code" | "From Morse Code" => stage(
+            "morse",
+            json!({ "direction": direction(op.starts_with("To")) }),
+        ),
+        "To Punycode" | "From Punycode" => stage(
+            "punycode",
+            json!({ "mode": direction(op.starts_with("To")) }),
+        ),
+        "URL Encode" | "URL Decode" => {
+            stage("url", json!({ "mode": direction(op.ends_with("Encode")) }))
+        }
+        "MD5" => stage("hash", json!({ "algorithm": "MD5" })),
+        "SHA2" if matches!(arg_str(0), "" | "256") => {
+            stage("hash", json!({ "algorithm": "SHA256" }))
+        }
+        "CRC-32 Checksum" => stage("hash", json!({ "algorithm": "CRC32" })),
+        "CRC Checksum" if arg_str(0) == "CRC-32" => stage("hash", json!({ "algorithm": "CRC32" })),
+        "Adler-32 Checksum" => stage("hash", json!({ "algorithm": "Adler32" })),
+        _ => {
+            if let Some(id) = compression_id(op) {
+                stage(id, json!({ "mode": "Compress", "encoding": "Hex" }))
+            } else if let Some(id) = decompression_id(op) {
+                stage(id, json!({ "mode": "Decompress", "encoding": "Hex" }))
+            } else {
+                return Err(format!("Skipped unsupported operation \"{}\"", op));
+            }
+        }
+    };
+    Ok((imported, None))
+}
+
+fn compression_id(op: &str) -> Option<&'static str> {
+    match op {
+        "Gzip" => Some("gzip"),
+        "Zlib Deflate" => Some("zlib"),
+        "Raw Deflate" => Some("deflate"),
+        _ => None,
+    }
+}
+
+fn decompression_id(op: &str) -> Option<&'static str> {
+    match op {
+        "Gunzip" => Some("gzip"),
+        "Zlib Inflate" => Some("zlib"),
+        "Raw Inflate" => Some("deflate"),
+        _ => None,
+    }
+}
+
+/// "From Base64"/"From Hex" (or "To ...") as a compression module encoding
+fn binary_encoding(op: &str, prefix: &str) -> Option<&'static str> {
+    match op.strip_prefix(prefix)?.trim() {
+        "Base64" => Some("Base64"),
+        "Hex" => Some("Hex"),
+        _ => None,
+    }
+}
+
+/// Interpret a CyberChef key argument, accepting it only if it is one byte long.
+fn single_byte_key(format: &str, key: &str) -> Option<u8> {
+    let bytes = match format {
+        "Hex" => hex::decode(key.split_whitespace().collect::<String>()).ok()?,
+        "Decimal" => key
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().ok())
+            .collect::<Option<Vec<u8>>>()?,
+        "Base64" => {
+            use base64::prelude::*;
+            BASE64_STANDARD.decode(key).ok()?
+        }
+        _ => key.as_bytes().to_vec(),
+    };
+    match bytes[..] {
+        [byte] => Some(byte),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules;
+
+    fn parse(recipe: &str) -> (Vec<(&'static str, Value)>, Vec<String>) {
+        let (stages, warnings) = parse_recipe(recipe).unwrap();
+        let stages = stages
+            .into_iter()
+            .map(|stage| (stage.id, stage.config))
+            .collect();
+        (stages, warnings)
+    }
+
+    #[test]
+    fn test_folds_text_encoding_into_decompression() {
+        let (stages, warnings) = parse(
+            r#"[{"op": "From Base64", "args": ["A-Za-z0-9+/=", true, false]},
+                {"op": "Gunzip", "args": []},
+                {"op": "Raw Deflate", "args": ["Dynamic Huffman Coding"]},
+                {"op": "To Hex", "args": ["Space", 0]}]"#,
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(
+            stages,
+            [
+                (
+                    "gzip",
+                    json!({ "mode": "Decompress", "encoding": "Base64" })
+                ),
+                ("deflate", json!({ "mode": "Compress", "encoding": "Hex" })),
+            ]
+        );
+
+        // The folded stage reads CyberChef's Base64 directly
+        let mut gzip = modules::create_module("gzip").unwrap();
+        gzip.import_config(json!({ "mode": "Compress", "encoding": "Base64" }))
+            .unwrap();
+        let compressed = gzip.process("hello").unwrap();
+        gzip.import_config(stages[0].1.clone()).unwrap();
+        assert_eq!(gzip.process(&compressed).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_unsupported_operation_is_skipped_with_warning() {
+        let (stages, warnings) = parse(
+            r#"[{"op": "ROT13", "args": [true, true, false, 13]},
+                {"op": "Magic", "args": [3, false, false, ""]},
+                {"op": "Reverse", "args": ["Character"]}]"#,
+        );
+        let ids: Vec<_> = stages.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, ["rot13", "reverse"]);
+        assert_eq!(warnings, ["Skipped unsupported operation \"Magic\""]);
+
+        assert!(parse_recipe("not json").is_err());
+    }
+
+    #[test]
+    fn test_other_rotation_amounts_fall_back() {
+        let (stages, _) = parse(
+            r#"[{"op": "ROT13", "args": [true, true, false, 3]},
+                {"op": "ROT47", "args": [5]}]"#,
+        );
+        assert_eq!(
+            stages,
+            [
+                ("caesar", json!({ "shift": 3, "mode": "Encode" })),
+                (
+                    "rotate",
+                    json!({ "amount": 5, "set": "Printable", "mode": "Encode" })
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xor_needs_a_single_byte_key() {
+        assert_eq!(single_byte_key("Hex", "2a"), Some(42));
+        assert_eq!(single_byte_key("Decimal", "42"), Some(42));
+        assert_eq!(single_byte_key("Base64", "Kg=="), Some(42));
+        assert_eq!(single_byte_key("UTF8", "*"), Some(42));
+        assert_eq!(single_byte_key("Hex", "2a 2b"), None);
+        assert_eq!(single_byte_key("UTF8", ""), None);
+
+        let (stages, warnings) = parse(
+            r#"[{"op": "XOR", "args": [{"option": "Hex", "string": "2a"}, "Standard", false]},
+                {"op": "XOR", "args": [{"option": "UTF8", "string": "key"}, "Standard", false]}]"#,
+        );
+        assert_eq!(
+            stages,
+            [("bitwise", json!({ "op": "XOR", "operand": "42" }))]
+        );
+        assert_eq!(
+            warnings,
+            ["Skipped \"XOR\": only single-byte keys are supported"]
+        );
+    }
+}
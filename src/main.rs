@@ -1,6 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+pub mod cyberchef;
 pub mod module;
 pub mod modules;
 pub mod pipeline;
@@ -13,7 +14,10 @@ fn main() -> eframe::Result {
     env_logger::init();
 
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([1280.0, 720.0]),
+        // The app id also names the folder favorites and recents are saved in
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1280.0, 720.0])
+            .with_app_id("yuricypher"),
         ..Default::default()
     };
 
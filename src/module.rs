@@ -1,9 +1,264 @@
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 
-pub trait Module {
+/// Which way a reversible module works, shared by every module with an
+/// encode/decode (or encrypt/decrypt) switch. Keyed ciphers label the two
+/// ways Encrypt/Decrypt in their UI, and configs may use those names too.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    #[serde(alias = "Encrypt")]
+    Encode,
+    #[serde(alias = "Decrypt")]
+    Decode,
+}
+
+impl Direction {
+    /// The opposite direction, which undoes this one
+    pub fn inverted(self) -> Self {
+        match self {
+            Direction::Encode => Direction::Decode,
+            Direction::Decode => Direction::Encode,
+        }
+    }
+}
+
+/// Why a module could not produce an output. The pipeline stops at the
+/// failing stage instead of passing the message on as data.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProcessError {
+    /// The input is not in the form the module expects
+    InvalidInput(String),
+    /// The module's settings cannot be used
+    BadConfig(String),
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            ProcessError::BadConfig(message) => write!(f, "Invalid settings: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Access to a module's settings as JSON, implemented for every
+/// serializable module.
+pub trait ModuleConfig {
+    fn export_config(&self) -> serde_json::Value;
+    /// Apply settings from JSON. Keys that are missing keep their current value.
+    fn import_config(&mut self, config: serde_json::Value) -> Result<(), String>;
+}
+
+impl<T: serde::Serialize + serde::de::DeserializeOwned> ModuleConfig for T {
+    fn export_config(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn import_config(&mut self, config: serde_json::Value) -> Result<(), String> {
+        let mut merged = self.export_config();
+        match (&mut merged, config) {
+            (serde_json::Value::Object(current), serde_json::Value::Object(changes)) => {
+                current.extend(changes)
+            }
+            (_, serde_json::Value::Null) => {}
+            (_, other) => merged = other,
+        }
+        *self = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// How a module's output turns back into its input, for undoing a chain
+#[derive(Debug, PartialEq)]
+pub enum Inverse {
+    /// The module passes its input through, so there is nothing to undo
+    PassThrough,
+    /// Running the module again with the same settings
+    Itself,
+    /// Running the same module with these settings
+    Settings(serde_json::Value),
+}
+
+impl Inverse {
+    /// The settings of `module` with the fields in `changes` replaced,
+    /// usually its direction flipped
+    pub fn changed<T: ModuleConfig + ?Sized>(module: &T, changes: serde_json::Value) -> Self {
+        let mut config = module.export_config();
+        if let (Some(config), serde_json::Value::Object(changes)) =
+            (config.as_object_mut(), changes)
+        {
+            config.extend(changes);
+        }
+        Inverse::Settings(config)
+    }
+}
+
+pub trait Module: ModuleConfig {
     fn name(&self) -> &str;
-    fn process(&self, input: &str) -> String;
+    /// The key the module is created from, see `modules::create_module`
+    fn id(&self) -> &str;
+    /// The sidebar group the module is listed under
+    fn category(&self) -> &str {
+        crate::modules::category(self.id())
+    }
+    /// Empty input yields empty output, unless the empty string has a
+    /// meaningful result of its own (a digest, a compressed stream, a padded
+    /// cipher block, an affix).
+    fn process(&self, input: &str) -> Result<String, ProcessError>;
+    /// Whether the output can currently be bytes that are not valid UTF-8,
+    /// which `process` can only show lossily (with U+FFFD in their place).
+    fn binary_output(&self) -> bool {
+        false
+    }
+    /// The bytes behind `process`'s output, intact even when they are not
+    /// UTF-8. Only differs from `process` for modules with `binary_output`.
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
+        self.process(input).map(String::into_bytes)
+    }
+    /// How to undo this module with its current settings, or `None` when
+    /// its output does not determine its input (a digest, a case change)
+    fn inverse(&self) -> Option<Inverse> {
+        None
+    }
     fn ui(&mut self, ui: &mut egui::Ui);
+    /// Extra UI drawn after processing, for modules that report on the data
+    /// flowing through them. Receives this stage's input and output.
+    fn inspect_ui(&mut self, _ui: &mut egui::Ui, _input: &str, _output: &str) {}
+    /// A hash of state outside the settings that the output depends on, such
+    /// as a randomly drawn IV, so cached outputs are recomputed when it changes.
+    fn state_hash(&self) -> u64 {
+        0
+    }
     fn as_any(&self) -> &dyn std::any::Any;
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::create_module;
+    use serde_json::json;
+
+    /// Encode `input` with `id`, then decode it with the direction set to
+    /// `decode` under `key`, as a config saved before `Direction` was shared
+    /// would have it.
+    fn round_trip(id: &str, key: &str, encode: &str, decode: &str, input: &str) -> String {
+        let mut module = create_module(id).unwrap();
+        module.import_config(json!({ key: encode })).unwrap();
+        assert_eq!(module.export_config()[key], "Encode", "{id}");
+        let encoded = module.process(input).unwrap();
+        assert_ne!(encoded, input, "{id}");
+        module.import_config(json!({ key: decode })).unwrap();
+        assert_eq!(module.export_config()[key], "Decode", "{id}");
+        module.process(&encoded).unwrap()
+    }
+
+    #[test]
+    fn test_direction_replaces_each_mode_enum() {
+        // One module for each enum that `Direction` replaced
+        assert_eq!(
+            round_trip("caesar", "mode", "Encode", "Decode", "Hello"),
+            "Hello"
+        );
+        assert_eq!(
+            round_trip("a1z26", "mode", "Encode", "Decode", "abc"),
+            "abc"
+        );
+        assert_eq!(
+            round_trip("base64", "mode", "Encode", "Decode", "Hello"),
+            "Hello"
+        );
+        assert_eq!(
+            round_trip("unicode", "mode", "Encode", "Decode", "Hé"),
+            "Hé"
+        );
+        assert_eq!(
+            round_trip("url", "mode", "Encode", "Decode", "a b&c"),
+            "a b&c"
+        );
+        assert_eq!(
+            round_trip("polybius", "mode", "Encode", "Decode", "HELLO"),
+            "HELLO"
+        );
+        assert_eq!(
+            round_trip("morse", "direction", "Encode", "Decode", "SOS"),
+            "SOS"
+        );
+        assert_eq!(
+            round_trip("block_cipher", "mode", "Encrypt", "Decrypt", "secret"),
+            "secret"
+        );
+        assert_eq!(
+            round_trip("rc4", "mode", "Encrypt", "Decrypt", "secret"),
+            "secret"
+        );
+    }
+
+    #[test]
+    fn test_direction_inverted() {
+        assert_eq!(Direction::Encode.inverted(), Direction::Decode);
+        assert_eq!(Direction::Decode.inverted(), Direction::Encode);
+    }
+
+    /// Run the inverse `module` reports on its `output`
+    fn undo(module: &dyn Module, output: &str) -> String {
+        match module.inverse().unwrap() {
+            Inverse::PassThrough | Inverse::Itself => module.process(output),
+            Inverse::Settings(config) => {
+                let mut inverse = create_module(module.id()).unwrap();
+                inverse.import_config(config).unwrap();
+                inverse.process(output)
+            }
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_inverse_undoes_module() {
+        for (id, config, input) in [
+            ("base64", json!(null), "Hello"),
+            ("affix", json!(null), "body"),
+            ("enigma", json!(null), "HELLOWORLD"),
+            ("condition", json!(null), "anything"),
+            ("numeral", json!({ "to": "Hexadecimal" }), "255 16"),
+            ("bitwise", json!({ "op": "ROL", "operand": "3" }), "Hi"),
+            (
+                "per_word",
+                json!({ "op": "Rotate", "amount": 2 }),
+                "hello world",
+            ),
+            ("byteorder", json!({ "output": "Raw" }), "48656c6c"),
+            ("gray", json!(null), "Gray"),
+            ("chain_xor", json!({ "iv": "42" }), "chain"),
+            ("parity", json!(null), "Hi"),
+            ("gzip", json!(null), "zip me"),
+            ("block_cipher", json!({ "random_iv": true }), "secret"),
+            ("split_apply", json!({ "inner": { "id": "base32" } }), "a,b"),
+        ] {
+            let mut module = create_module(id).unwrap();
+            module.import_config(config).unwrap();
+            let output = module.process(input).unwrap();
+            assert_eq!(undo(module.as_ref(), &output), input, "{id}");
+        }
+    }
+
+    #[test]
+    fn test_inverse_none_when_information_is_lost() {
+        for (id, config) in [
+            ("hash", json!(null)),
+            ("case_transform", json!(null)),
+            ("caesar", json!({ "brute_force": true })),
+            ("bitwise", json!({ "op": "AND", "operand": "15" })),
+            ("bitwise", json!({ "op": "XOR", "output": "Hex" })),
+            ("byteorder", json!({ "remainder": "Pad" })),
+            ("integer", json!({ "mode": "ToHex" })),
+            ("split_apply", json!(null)),
+        ] {
+            let mut module = create_module(id).unwrap();
+            module.import_config(config).unwrap();
+            assert_eq!(module.inverse(), None, "{id}");
+        }
+    }
+}
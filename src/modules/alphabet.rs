@@ -1,6 +1,7 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
 use eframe::egui;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 lazy_static! {
@@ -78,42 +79,200 @@ lazy_static! {
     };
 }
 
-#[derive(PartialEq, Clone, Copy)]
-pub enum Direction {
-    Encode,
-    Decode,
-}
+/// Procedural signals, sent as one character with no inter-letter gap
+const PROSIGNS: [(&str, &str); 8] = [
+    ("AR", ".-.-."),      // End of message
+    ("SK", "...-.-"),     // End of contact
+    ("BT", "-...-"),      // Break / new paragraph
+    ("KN", "-.--."),      // Go ahead, named station only
+    ("AS", ".-..."),      // Wait
+    ("KA", "-.-.-"),      // Start of message
+    ("SN", "...-."),      // Understood
+    ("SOS", "...---..."), // Distress
+];
+
+/// Common Q-codes and their meaning as a statement
+const Q_CODES: [(&str, &str); 16] = [
+    ("QRL", "this frequency is busy"),
+    ("QRM", "interference"),
+    ("QRN", "static noise"),
+    ("QRO", "increase power"),
+    ("QRP", "reduce power"),
+    ("QRQ", "send faster"),
+    ("QRS", "send slower"),
+    ("QRT", "stop sending"),
+    ("QRV", "I am ready"),
+    ("QRX", "wait"),
+    ("QRZ", "who is calling me?"),
+    ("QSB", "your signal is fading"),
+    ("QSL", "I acknowledge receipt"),
+    ("QSO", "a contact"),
+    ("QSY", "change frequency"),
+    ("QTH", "my location is"),
+];
 
+#[derive(Serialize, Deserialize)]
 pub struct MorseCodeModule {
     direction: Direction,
+    /// Encode `<AR>`-style prosigns and show them when decoding
+    prosigns: bool,
+    /// Follow decoded Q-codes with their meaning
+    expand_q_codes: bool,
+    /// Decode letters as lowercase (Morse itself has no case)
+    lowercase_output: bool,
 }
 
 impl Default for MorseCodeModule {
     fn default() -> Self {
         Self {
             direction: Direction::Encode,
+            prosigns: true,
+            expand_q_codes: false,
+            lowercase_output: false,
+        }
+    }
+}
+
+impl MorseCodeModule {
+    fn encode(&self, input: &str) -> String {
+        let input = input.to_uppercase();
+        let mut codes: Vec<&str> = Vec::new();
+        let mut rest = input.as_str();
+        while let Some(c) = rest.chars().next() {
+            if self.prosigns && c == '<' {
+                let prosign = rest[1..].split_once('>').and_then(|(name, after)| {
+                    let (_, code) = PROSIGNS.iter().find(|(n, _)| *n == name)?;
+                    Some((*code, after))
+                });
+                if let Some((code, after)) = prosign {
+                    codes.push(code);
+                    rest = after;
+                    continue;
+                }
+            }
+            codes.push(MORSE_CODE.get(&c).cloned().unwrap_or(" "));
+            rest = &rest[c.len_utf8()..];
+        }
+        codes.join(" ")
+    }
+
+    fn decode(&self, input: &str) -> String {
+        let words = morse_words(input)
+            .iter()
+            .map(|word| {
+                word.iter()
+                    .map(|code| self.decode_code(code))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        if !self.expand_q_codes {
+            return words.join(" ");
+        }
+        words
+            .iter()
+            .map(
+                |word| match Q_CODES.iter().find(|(q, _)| q.eq_ignore_ascii_case(word)) {
+                    Some((_, meaning)) => format!("{} ({})", word, meaning),
+                    None => word.clone(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Unknown codes are kept in brackets: usually letters run together.
+    fn decode_code(&self, code: &str) -> String {
+        if let Some(c) = REVERSE_MORSE_CODE.get(code) {
+            return if self.lowercase_output {
+                c.to_ascii_lowercase().to_string()
+            } else {
+                c.to_string()
+            };
         }
+        if self.prosigns {
+            if let Some((name, _)) = PROSIGNS.iter().find(|(_, c)| *c == code) {
+                return format!("<{}>", name);
+            }
+            return format!("[{}]", code);
+        }
+        " ".to_string()
+    }
+}
+
+/// Split Morse text into words of codes. Words are separated by `/` or by
+/// two or more spaces.
+fn morse_words(input: &str) -> Vec<Vec<String>> {
+    input
+        .replace('/', "   ")
+        .split("  ")
+        .filter(|word| !word.trim().is_empty())
+        .map(|word| word.split_whitespace().map(String::from).collect())
+        .collect()
+}
+
+/// What a single Morse code stands for, for the explanation tooltips.
+fn explain_code(code: &str) -> String {
+    if let Some(c) = REVERSE_MORSE_CODE.get(code) {
+        return c.to_string();
+    }
+    match PROSIGNS.iter().find(|(_, c)| *c == code) {
+        Some((name, _)) => format!("Prosign <{}>", name),
+        None => "Unknown code".to_string(),
     }
 }
 
+/// Codes shown in the explanation, to keep huge inputs responsive
+const MAX_EXPLAINED_CODES: usize = 500;
+
+/// Height of the keying plot
+const SIGNAL_HEIGHT: f32 = 24.0;
+
+/// On/off keying of Morse words as (key down, length in dit units) runs:
+/// dits are 1 unit, dahs 3, with gaps of 1 within a code, 3 between codes
+/// and 7 between words.
+fn keying(words: &[Vec<String>]) -> Vec<(bool, u32)> {
+    let mut runs = Vec::new();
+    for (w, word) in words.iter().enumerate() {
+        if w > 0 {
+            runs.push((false, 7));
+        }
+        for (c, code) in word.iter().enumerate() {
+            if c > 0 {
+                runs.push((false, 3));
+            }
+            for (e, element) in code.chars().enumerate() {
+                if e > 0 {
+                    runs.push((false, 1));
+                }
+                runs.push((true, if element == '-' { 3 } else { 1 }));
+            }
+        }
+    }
+    runs
+}
+
 impl Module for MorseCodeModule {
     fn name(&self) -> &str {
         "Morse Code"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.direction {
-            Direction::Encode => input
-                .to_uppercase()
-                .chars()
-                .map(|c| MORSE_CODE.get(&c).cloned().unwrap_or(" "))
-                .collect::<Vec<_>>()
-                .join(" "),
-            Direction::Decode => input
-                .split_whitespace()
-                .map(|s| REVERSE_MORSE_CODE.get(s).cloned().unwrap_or(' '))
-                .collect::<String>(),
-        }
+    fn id(&self) -> &str {
+        "morse"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.direction {
+            Direction::Encode => self.encode(input),
+            Direction::Decode => self.decode(input),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "direction": self.direction.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -121,6 +280,80 @@ impl Module for MorseCodeModule {
             ui.radio_value(&mut self.direction, Direction::Encode, "Encode");
             ui.radio_value(&mut self.direction, Direction::Decode, "Decode");
         });
+        ui.checkbox(&mut self.prosigns, "Prosigns (<AR>, <SK>, <BT>, <KN>, ...)")
+            .on_hover_text(
+                "Encode <AR> as one run-together character. When decoding, unknown \
+                 sequences are shown in [brackets] since they are often letters sent without a gap.",
+            );
+        if self.direction == Direction::Decode {
+            ui.checkbox(&mut self.expand_q_codes, "Explain Q-codes (QTH, QSL, ...)");
+            ui.checkbox(&mut self.lowercase_output, "Lowercase output");
+        }
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, output: &str) {
+        let morse = match self.direction {
+            Direction::Encode => output,
+            Direction::Decode => input,
+        };
+        let words = morse_words(morse);
+        if words.is_empty() {
+            return;
+        }
+        egui::CollapsingHeader::new("Explain codes")
+            .id_salt("morse_explain")
+            .show(ui, |ui| {
+                ui.small("Hover a code to see what it stands for");
+                ui.horizontal_wrapped(|ui| {
+                    let mut shown = 0;
+                    for (idx, word) in words.iter().enumerate() {
+                        if idx > 0 {
+                            ui.monospace("/");
+                        }
+                        for code in word {
+                            if shown == MAX_EXPLAINED_CODES {
+                                ui.small("…");
+                                return;
+                            }
+                            ui.monospace(code).on_hover_text(explain_code(code));
+                            shown += 1;
+                        }
+                    }
+                });
+            });
+        egui::CollapsingHeader::new("Signal")
+            .id_salt("morse_signal")
+            .show(ui, |ui| {
+                let runs = keying(&words);
+                let units: u32 = runs.iter().map(|(_, len)| len).sum();
+                // PARIS timing: a word is 50 units, so a unit lasts 1.2 / WPM seconds
+                ui.small(format!(
+                    "{} units, {:.1} s at 20 WPM",
+                    units,
+                    units as f32 * 1.2 / 20.0
+                ));
+                let unit = (ui.available_width() / units.max(1) as f32).clamp(0.5, 6.0);
+                let size = egui::vec2(units as f32 * unit, SIGNAL_HEIGHT);
+                egui::ScrollArea::horizontal()
+                    .id_salt("morse_signal_scroll")
+                    .show(ui, |ui| {
+                        let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+                        let rect = response.rect;
+                        let mut x = rect.left();
+                        let mut points = vec![egui::pos2(x, rect.bottom())];
+                        for &(on, len) in &runs {
+                            let y = if on { rect.top() } else { rect.bottom() };
+                            points.push(egui::pos2(x, y));
+                            x += len as f32 * unit;
+                            points.push(egui::pos2(x, y));
+                        }
+                        points.push(egui::pos2(x, rect.bottom()));
+                        painter.add(egui::Shape::line(
+                            points,
+                            egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 140, 0)),
+                        ));
+                    });
+            });
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -132,6 +365,7 @@ impl Module for MorseCodeModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct SpellingAlphabetModule;
 
 impl Default for SpellingAlphabetModule {
@@ -145,13 +379,17 @@ impl Module for SpellingAlphabetModule {
         "Spelling Alphabet"
     }
 
-    fn process(&self, input: &str) -> String {
-        input
+    fn id(&self) -> &str {
+        "spelling"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(input
             .to_uppercase()
             .chars()
             .map(|c| NATO_ALPHABET.get(&c).cloned().unwrap_or(" "))
             .collect::<Vec<_>>()
-            .join(" ")
+            .join(" "))
     }
 
     fn ui(&mut self, _ui: &mut egui::Ui) {
@@ -166,3 +404,31 @@ impl Module for SpellingAlphabetModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_morse_sk_prosign() {
+        let mut module = MorseCodeModule::default();
+        assert_eq!(module.process("<SK>").unwrap(), "...-.-");
+        assert_eq!(module.process("TU <sk>").unwrap(), "- ..-   ...-.-");
+        module.direction = Direction::Decode;
+        assert_eq!(module.process("...-.-").unwrap(), "<SK>");
+        assert_eq!(module.process("- ..- / ...-.-").unwrap(), "TU <SK>");
+        // Without prosigns the run-together code is not a character
+        module.prosigns = false;
+        assert_eq!(module.process("...-.-").unwrap(), " ");
+    }
+
+    #[test]
+    fn test_morse_lowercase_output() {
+        let mut module = MorseCodeModule::default();
+        let encoded = module.process("Hi There").unwrap();
+        module.direction = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "HI THERE");
+        module.lowercase_output = true;
+        assert_eq!(module.process(&encoded).unwrap(), "hi there");
+    }
+}
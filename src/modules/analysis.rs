@@ -0,0 +1,650 @@
+use crate::module::{Inverse, Module, ProcessError};
+use crate::modules::cipher::map_alphabetic;
+use crate::modules::transform::ByteFormat;
+use base64::prelude::*;
+use eframe::egui;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ConditionKind {
+    Contains,
+    Regex,
+    IsHex,
+    IsBase64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ConditionModule {
+    kind: ConditionKind,
+    pattern: String,
+}
+
+impl Default for ConditionModule {
+    fn default() -> Self {
+        Self {
+            kind: ConditionKind::Contains,
+            pattern: String::new(),
+        }
+    }
+}
+
+impl ConditionModule {
+    /// Check the input against the configured condition.
+    /// Returns an error for an invalid regular expression.
+    pub fn evaluate(&self, input: &str) -> Result<bool, String> {
+        match self.kind {
+            ConditionKind::Contains => Ok(input.contains(&self.pattern)),
+            ConditionKind::Regex => regex::Regex::new(&self.pattern)
+                .map(|re| re.is_match(input))
+                .map_err(|e| e.to_string()),
+            ConditionKind::IsHex => {
+                let hex: String = input.split_whitespace().collect();
+                Ok(!hex.is_empty() && hex::decode(&hex).is_ok())
+            }
+            ConditionKind::IsBase64 => {
+                let b64: String = input.split_whitespace().collect();
+                Ok(!b64.is_empty() && BASE64_STANDARD.decode(&b64).is_ok())
+            }
+        }
+    }
+}
+
+impl Module for ConditionModule {
+    fn name(&self) -> &str {
+        "Condition"
+    }
+
+    fn id(&self) -> &str {
+        "condition"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Pass-through: the result is only shown in the UI
+        Ok(input.to_string())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::PassThrough)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Condition")
+                .selected_text(match self.kind {
+                    ConditionKind::Contains => "Contains",
+                    ConditionKind::Regex => "Matches regex",
+                    ConditionKind::IsHex => "Is valid hex",
+                    ConditionKind::IsBase64 => "Is valid Base64",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.kind, ConditionKind::Contains, "Contains");
+                    ui.selectable_value(&mut self.kind, ConditionKind::Regex, "Matches regex");
+                    ui.selectable_value(&mut self.kind, ConditionKind::IsHex, "Is valid hex");
+                    ui.selectable_value(&mut self.kind, ConditionKind::IsBase64, "Is valid Base64");
+                });
+
+            if matches!(self.kind, ConditionKind::Contains | ConditionKind::Regex) {
+                ui.label("Pattern:");
+                ui.text_edit_singleline(&mut self.pattern);
+            }
+        });
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        match self.evaluate(input) {
+            Ok(true) => ui.colored_label(egui::Color32::GREEN, "● Match"),
+            Ok(false) => ui.colored_label(egui::Color32::RED, "● No match"),
+            Err(e) => ui.colored_label(egui::Color32::YELLOW, format!("Invalid regex: {}", e)),
+        };
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Known file signatures: (description, offset, magic bytes)
+const FILE_SIGNATURES: &[(&str, usize, &[u8])] = &[
+    ("PNG image", 0, b"\x89PNG\r\n\x1a\n"),
+    ("JPEG image", 0, b"\xff\xd8\xff"),
+    ("GIF image", 0, b"GIF87a"),
+    ("GIF image", 0, b"GIF89a"),
+    ("WebP image", 8, b"WEBP"),
+    ("BMP image", 0, b"BM"),
+    ("TIFF image (little-endian)", 0, b"II*\x00"),
+    ("TIFF image (big-endian)", 0, b"MM\x00*"),
+    ("PDF document", 0, b"%PDF-"),
+    ("ZIP archive (also DOCX/XLSX/JAR/APK)", 0, b"PK\x03\x04"),
+    ("ZIP archive (empty)", 0, b"PK\x05\x06"),
+    ("gzip compressed data", 0, b"\x1f\x8b"),
+    ("bzip2 compressed data", 0, b"BZh"),
+    ("xz compressed data", 0, b"\xfd7zXZ\x00"),
+    ("7-Zip archive", 0, b"7z\xbc\xaf\x27\x1c"),
+    ("RAR archive", 0, b"Rar!\x1a\x07"),
+    ("tar archive", 257, b"ustar"),
+    ("zlib compressed data", 0, b"\x78\x9c"),
+    ("zlib compressed data", 0, b"\x78\x01"),
+    ("zlib compressed data", 0, b"\x78\xda"),
+    ("ELF executable", 0, b"\x7fELF"),
+    ("Windows PE executable", 0, b"MZ"),
+    ("Java class file", 0, b"\xca\xfe\xba\xbe"),
+    ("WebAssembly module", 0, b"\x00asm"),
+    ("SQLite database", 0, b"SQLite format 3\x00"),
+    ("Ogg media", 0, b"OggS"),
+    ("MP3 audio (ID3 tag)", 0, b"ID3"),
+    ("FLAC audio", 0, b"fLaC"),
+    ("RIFF container (WAV/AVI)", 0, b"RIFF"),
+    ("MIDI audio", 0, b"MThd"),
+    ("PostScript document", 0, b"%!PS"),
+];
+
+/// Return the first signature matching the data as (description, offset, magic)
+pub fn detect_file_type(data: &[u8]) -> Option<(&'static str, usize, &'static [u8])> {
+    FILE_SIGNATURES
+        .iter()
+        .find(|(_, offset, magic)| data.get(*offset..offset + magic.len()) == Some(*magic))
+        .copied()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FileTypeModule {
+    input: ByteFormat,
+}
+
+impl Default for FileTypeModule {
+    fn default() -> Self {
+        Self {
+            input: ByteFormat::Hex,
+        }
+    }
+}
+
+impl Module for FileTypeModule {
+    fn name(&self) -> &str {
+        "File Type"
+    }
+
+    fn id(&self) -> &str {
+        "filetype"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Pass-through: the detection is only shown in the UI
+        Ok(input.to_string())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::PassThrough)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        self.input.ui(ui, "Input:");
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        let bytes = match self.input.parse(input) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                ui.colored_label(egui::Color32::YELLOW, e);
+                return;
+            }
+        };
+        match detect_file_type(&bytes) {
+            Some((description, offset, magic)) => {
+                let signature = magic
+                    .iter()
+                    .map(|b| format!("{:02X}", b))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                ui.colored_label(
+                    egui::Color32::GREEN,
+                    format!(
+                        "Detected: {} ({} at offset {})",
+                        description, signature, offset
+                    ),
+                );
+            }
+            None => {
+                ui.label("No known file signature found");
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Count how often each byte value occurs.
+pub fn byte_histogram(bytes: &[u8]) -> [usize; 256] {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    counts
+}
+
+/// Shannon entropy of the byte distribution, in bits per byte (0.0 to 8.0).
+pub fn byte_entropy(counts: &[usize; 256]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ByteHeatmapModule {
+    input: ByteFormat,
+    /// Scale colours by log(count) so rare bytes stay visible next to common ones
+    log_scale: bool,
+}
+
+impl Default for ByteHeatmapModule {
+    fn default() -> Self {
+        Self {
+            input: ByteFormat::Raw,
+            log_scale: true,
+        }
+    }
+}
+
+const HEATMAP_CELL: f32 = 16.0;
+
+impl Module for ByteHeatmapModule {
+    fn name(&self) -> &str {
+        "Byte Heatmap"
+    }
+
+    fn id(&self) -> &str {
+        "byte_heatmap"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Pass-through: the heatmap is only shown in the UI
+        Ok(input.to_string())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::PassThrough)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            self.input.ui(ui, "Input:");
+            ui.checkbox(&mut self.log_scale, "Log scale");
+        });
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        let bytes = match self.input.parse(input) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                ui.colored_label(egui::Color32::YELLOW, e);
+                return;
+            }
+        };
+        let counts = byte_histogram(&bytes);
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let distinct = counts.iter().filter(|&&count| count > 0).count();
+        ui.label(format!(
+            "{} bytes, {} distinct values, entropy {:.2} bits/byte",
+            bytes.len(),
+            distinct,
+            byte_entropy(&counts)
+        ));
+        ui.small("Rows are the high nibble, columns the low nibble. Encrypted or compressed data looks flat; text clusters in 0x20-0x7E.");
+
+        // One label row/column plus the 16×16 grid
+        let size = egui::vec2(17.0 * HEATMAP_CELL, 17.0 * HEATMAP_CELL);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+        let origin = response.rect.min + egui::vec2(HEATMAP_CELL, HEATMAP_CELL);
+        let font = egui::FontId::monospace(10.0);
+        let text_color = ui.visuals().text_color();
+        for i in 0..16 {
+            let offset = (i as f32 + 0.5) * HEATMAP_CELL;
+            painter.text(
+                origin + egui::vec2(offset, -HEATMAP_CELL / 2.0),
+                egui::Align2::CENTER_CENTER,
+                format!("{:X}", i),
+                font.clone(),
+                text_color,
+            );
+            painter.text(
+                origin + egui::vec2(-HEATMAP_CELL / 2.0, offset),
+                egui::Align2::CENTER_CENTER,
+                format!("{:X}", i),
+                font.clone(),
+                text_color,
+            );
+        }
+
+        let intensity = |count: usize| -> f32 {
+            if count == 0 || max == 0 {
+                0.0
+            } else if self.log_scale {
+                ((count as f32).ln_1p() / (max as f32).ln_1p()).max(0.05)
+            } else {
+                (count as f32 / max as f32).max(0.05)
+            }
+        };
+        let empty = ui.visuals().extreme_bg_color;
+        let hot = egui::Color32::from_rgb(255, 140, 0);
+        for (value, &count) in counts.iter().enumerate() {
+            let min = origin
+                + egui::vec2(
+                    (value % 16) as f32 * HEATMAP_CELL,
+                    (value / 16) as f32 * HEATMAP_CELL,
+                );
+            let rect =
+                egui::Rect::from_min_size(min, egui::vec2(HEATMAP_CELL, HEATMAP_CELL)).shrink(0.5);
+            painter.rect_filled(rect, 1.0, empty.lerp_to_gamma(hot, intensity(count)));
+        }
+
+        if let Some(pos) = response.hover_pos() {
+            let cell = (pos - origin) / HEATMAP_CELL;
+            if (0.0..16.0).contains(&cell.x) && (0.0..16.0).contains(&cell.y) {
+                let value = cell.y as usize * 16 + cell.x as usize;
+                let shown = match value as u8 {
+                    b @ 0x20..=0x7E => format!(" '{}'", b as char),
+                    _ => String::new(),
+                };
+                let share = if bytes.is_empty() {
+                    0.0
+                } else {
+                    counts[value] as f64 * 100.0 / bytes.len() as f64
+                };
+                response.on_hover_text_at_pointer(format!(
+                    "0x{:02X}{}: {} ({:.1}%)",
+                    value, shown, counts[value], share
+                ));
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+lazy_static! {
+    /// A few thousand common English words, lowercase, one per line
+    static ref WORDLIST: HashSet<&'static str> = include_str!("wordlist.txt").lines().collect();
+}
+
+const LONGEST_WORD: usize = 20;
+
+/// How much of `text` reads as English words, from 0.0 to 1.0.
+///
+/// Only letters are considered, so the text may have its spaces removed
+/// (as transposition ciphers often do). The letters are split into the
+/// segmentation covering the most of them with known words; one- and
+/// two-letter words count half, since they turn up in random text too.
+pub fn word_score(text: &str) -> f32 {
+    let letters: Vec<u8> = text
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_lowercase())
+        .collect();
+    if letters.is_empty() {
+        return 0.0;
+    }
+
+    // best[i] = highest coverage achievable for the first i letters
+    let mut best = vec![0.0f32; letters.len() + 1];
+    for end in 1..=letters.len() {
+        best[end] = best[end - 1];
+        for start in end.saturating_sub(LONGEST_WORD)..end {
+            let word = std::str::from_utf8(&letters[start..end]).unwrap_or_default();
+            if WORDLIST.contains(word) {
+                let len = (end - start) as f32;
+                let weight = if end - start <= 2 { 0.5 } else { 1.0 };
+                best[end] = best[end].max(best[start] + len * weight);
+            }
+        }
+    }
+    best[letters.len()] / letters.len() as f32
+}
+
+/// Relative frequency of A-Z in typical English text
+pub const ENGLISH_FREQUENCIES: [f32; 26] = [
+    0.0817, 0.0149, 0.0278, 0.0425, 0.1270, 0.0223, 0.0202, 0.0609, 0.0697, 0.0015, 0.0077, 0.0403,
+    0.0241, 0.0675, 0.0751, 0.0193, 0.0010, 0.0599, 0.0633, 0.0906, 0.0276, 0.0098, 0.0236, 0.0015,
+    0.0197, 0.0007,
+];
+
+/// Chi-squared distance of letter counts (index 0 = A) from English; lower
+/// is more English-like. Returns infinity when there are no letters.
+pub fn english_chi_squared(counts: &[usize; 26]) -> f32 {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return f32::INFINITY;
+    }
+    counts
+        .iter()
+        .zip(ENGLISH_FREQUENCIES)
+        .map(|(&count, freq)| {
+            let expected = freq * total as f32;
+            (count as f32 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Decodes text to bytes, or returns `None` when it does not look like its
+/// encoding.
+type PeelDecoder = fn(&str) -> Option<Vec<u8>>;
+
+/// Decoders `peel` tries, in order. Binary and hex come before Base32/Base64
+/// since their digits are valid in those alphabets too.
+const PEEL_DECODERS: [(&str, PeelDecoder); 5] = [
+    ("Binary", |text| ByteFormat::Binary.parse(text).ok()),
+    ("Hex", |text| {
+        let hex: String = text.split_whitespace().collect();
+        (hex.len() >= 2).then_some(())?;
+        hex::decode(hex).ok()
+    }),
+    ("Base32", |text| {
+        let b32: String = text.lines().collect();
+        data_encoding::BASE32.decode(b32.as_bytes()).ok()
+    }),
+    ("Base64", |text| {
+        let b64: String = text.lines().collect();
+        (b64.len() >= 4).then_some(())?;
+        BASE64_STANDARD.decode(b64).ok()
+    }),
+    ("URL", percent_decode),
+];
+
+/// Percent-decode `text` to bytes, or `None` if it has no escapes.
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut escaped = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                escaped = true;
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    escaped.then_some(decoded)
+}
+
+/// The bytes as text, if they are UTF-8 without control characters other
+/// than line breaks and tabs.
+fn readable_text(bytes: Vec<u8>) -> Option<String> {
+    let text = String::from_utf8(bytes).ok()?;
+    let readable = !text.trim().is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'));
+    readable.then_some(text)
+}
+
+fn letter_counts(text: &str) -> [usize; 26] {
+    let mut counts = [0usize; 26];
+    for b in text.bytes().filter(u8::is_ascii_alphabetic) {
+        counts[(b.to_ascii_uppercase() - b'A') as usize] += 1;
+    }
+    counts
+}
+
+/// Text with its letters ROT13-rotated, if that makes it clearly more
+/// English-like.
+fn peel_rot13(text: &str) -> Option<String> {
+    let counts = letter_counts(text);
+    if counts.iter().sum::<usize>() < 8 {
+        return None;
+    }
+    let mut rotated = [0usize; 26];
+    for (i, &count) in counts.iter().enumerate() {
+        rotated[(i + 13) % 26] = count;
+    }
+    (english_chi_squared(&rotated) * 2.0 < english_chi_squared(&counts))
+        .then(|| map_alphabetic(text, |x| Some(x + 13)))
+}
+
+/// Repeatedly apply the first decoder that turns `input` into readable
+/// text, up to `max_depth` times. Returns the names of the applied decoders
+/// and the final text.
+pub fn peel(input: &str, max_depth: usize) -> (Vec<&'static str>, String) {
+    let mut steps = Vec::new();
+    let mut text = input.trim().to_string();
+    while steps.len() < max_depth {
+        let decoded = PEEL_DECODERS
+            .iter()
+            .find_map(|(name, decode)| Some((*name, readable_text(decode(&text)?)?)))
+            .or_else(|| Some(("ROT13", peel_rot13(&text)?)));
+        let Some((name, decoded)) = decoded else {
+            break;
+        };
+        steps.push(name);
+        text = decoded.trim().to_string();
+    }
+    (steps, text)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AutoPeelModule {
+    max_depth: usize,
+}
+
+impl Default for AutoPeelModule {
+    fn default() -> Self {
+        Self { max_depth: 10 }
+    }
+}
+
+impl Module for AutoPeelModule {
+    fn name(&self) -> &str {
+        "Auto Peel"
+    }
+
+    fn id(&self) -> &str {
+        "auto_peel"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(peel(input, self.max_depth).1)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Max depth:");
+            ui.add(egui::DragValue::new(&mut self.max_depth).range(1..=50));
+        });
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        let (steps, _) = peel(input, self.max_depth);
+        if steps.is_empty() {
+            ui.small("No known encoding recognised");
+        } else {
+            ui.small(format!("Applied: {}", steps.join(" → ")));
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_png_header() {
+        let header = FileTypeModule::default()
+            .input
+            .parse("89504e470d0a1a0a 0000000d49484452")
+            .unwrap();
+        let (description, offset, magic) = detect_file_type(&header).unwrap();
+        assert_eq!(description, "PNG image");
+        assert_eq!(offset, 0);
+        assert_eq!(magic, b"\x89PNG\r\n\x1a\n");
+        assert_eq!(detect_file_type(&header[..7]), None);
+        assert_eq!(detect_file_type(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_english_outscores_shuffled_text() {
+        let english = "the enemy will attack the northern bridge at dawn";
+        // The same 41 letters in a fixed scrambled order; 7 is coprime to 41,
+        // so stepping by 7 visits each letter once
+        let letters: Vec<char> = english.chars().filter(|c| c.is_alphabetic()).collect();
+        let shuffled: String = (0..letters.len())
+            .map(|i| letters[i * 7 % letters.len()])
+            .collect();
+        assert!(word_score(english) > 0.9);
+        assert!(word_score(&english.replace(' ', "")) > 0.9);
+        assert!(word_score(&shuffled) < 0.5);
+        assert_eq!(word_score(""), 0.0);
+    }
+
+    #[test]
+    fn test_peel_double_base64() {
+        let (steps, text) = peel("Wm14aFozdHVaWE4wWldSZmJHRjVaWEp6ZlE9PQ==", 10);
+        assert_eq!(steps, ["Base64", "Base64"]);
+        assert_eq!(text, "flag{nested_layers}");
+        let (steps, text) = peel("Wm14aFozdHVaWE4wWldSZmJHRjVaWEp6ZlE9PQ==", 1);
+        assert_eq!(steps, ["Base64"]);
+        assert_eq!(text, "ZmxhZ3tuZXN0ZWRfbGF5ZXJzfQ==");
+    }
+}
@@ -1,60 +1,459 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
+use crate::modules::analysis::{english_chi_squared, ENGLISH_FREQUENCIES};
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Map every ASCII letter through `f`, which receives and returns its 0-25
+/// alphabet index, keeping the letter's case. Returning `None` keeps the
+/// letter as is. Everything else (digits, punctuation, whitespace and
+/// non-ASCII text) is copied through unchanged.
+pub fn map_alphabetic(input: &str, mut f: impl FnMut(u8) -> Option<u8>) -> String {
+    input
+        .chars()
+        .map(|c| {
+            if !c.is_ascii_alphabetic() {
+                return c;
+            }
+            let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+            match f(c as u8 - base) {
+                Some(index) => (base + index % 26) as char,
+                None => c,
+            }
+        })
+        .collect()
+}
+
+/// Like `map_alphabetic`, but over the 52 letters A-Z then a-z, so `f`
+/// receives 0-25 for uppercase and 26-51 for lowercase letters and may move
+/// a letter into the other case.
+pub fn map_cased(input: &str, mut f: impl FnMut(u8) -> Option<u8>) -> String {
+    input
+        .chars()
+        .map(|c| {
+            let index = match c {
+                'A'..='Z' => c as u8 - b'A',
+                'a'..='z' => c as u8 - b'a' + 26,
+                _ => return c,
+            };
+            match f(index).map(|index| index % 52) {
+                Some(index @ 0..=25) => (b'A' + index) as char,
+                Some(index) => (b'a' + index - 26) as char,
+                None => c,
+            }
+        })
+        .collect()
+}
+
+/// Map every letter of `alphabet` (given in uppercase) through `f`, which
+/// receives and returns its index, keeping the letter's case. Other
+/// characters are copied through unchanged.
+pub fn map_letters(input: &str, alphabet: &[char], mut f: impl FnMut(usize) -> usize) -> String {
+    let lowercase: Vec<char> = alphabet
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    input
+        .chars()
+        .map(|c| {
+            if let Some(i) = alphabet.iter().position(|&a| a == c) {
+                alphabet[f(i) % alphabet.len()]
+            } else if let Some(i) = lowercase.iter().position(|&a| a == c) {
+                lowercase[f(i) % alphabet.len()]
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Calculate modular multiplicative inverse using Extended Euclidean Algorithm
+pub fn mod_inverse(a: i32, m: i32) -> Option<i32> {
+    let (mut t, mut new_t) = (0, 1);
+    let (mut r, mut new_r) = (m, a);
+
+    while new_r != 0 {
+        let quotient = r / new_r;
+        (t, new_t) = (new_t, t - quotient * new_t);
+        (r, new_r) = (new_r, r - quotient * new_r);
+    }
+
+    if r > 1 {
+        return None; // a is not invertible
+    }
+    if t < 0 {
+        t += m;
+    }
+    Some(t)
+}
+
+/// Scripts whose alphabets the shift ciphers can work in
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+}
+
+impl Script {
+    pub const ALL: [Script; 3] = [Script::Latin, Script::Cyrillic, Script::Greek];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Script::Latin => "Latin (26)",
+            Script::Cyrillic => "Cyrillic (33)",
+            Script::Greek => "Greek (24)",
+        }
+    }
+
+    /// The uppercase alphabet in order
+    pub fn letters(&self) -> Vec<char> {
+        match self {
+            Script::Latin => ('A'..='Z').collect(),
+            Script::Cyrillic => "АБВГДЕЁЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯ".chars().collect(),
+            Script::Greek => "ΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩ".chars().collect(),
+        }
+    }
+
+    /// Spelling variants folded into a regular letter before shifting: the
+    /// Greek final sigma is an ordinary sigma.
+    pub fn normalize(&self, input: &str) -> String {
+        match self {
+            Script::Greek => input.replace('ς', "σ"),
+            _ => input.to_string(),
+        }
+    }
+}
+
+/// The Latin alphabet in order
+pub const LATIN_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Order the unused letters follow the keyword in a keyed alphabet
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum KeyFill {
+    /// The rest of the alphabet from the start
+    Forward,
+    /// The rest of the alphabet backwards from the end
+    Reverse,
+}
+
+/// Expand a keyword into a full mixed alphabet: the keyword's characters in
+/// order of first appearance, then the unused characters of `alphabet`.
+/// Keyword characters match the alphabet ignoring case and are dropped when
+/// not in it. With `merge_j`, a J in the keyword stands for I, for 25-letter
+/// alphabets without J.
+pub fn keyed_alphabet(keyword: &str, alphabet: &str, fill: KeyFill, merge_j: bool) -> String {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    let find = |c: char| {
+        let c = if merge_j && c.eq_ignore_ascii_case(&'j') {
+            'I'
+        } else {
+            c
+        };
+        alphabet
+            .iter()
+            .copied()
+            .find(|a| a.to_uppercase().eq(c.to_uppercase()))
+    };
+    let rest: Vec<char> = match fill {
+        KeyFill::Forward => alphabet.clone(),
+        KeyFill::Reverse => alphabet.iter().rev().copied().collect(),
+    };
+    let mut keyed: Vec<char> = Vec::with_capacity(alphabet.len());
+    for c in keyword.chars().filter_map(find).chain(rest) {
+        if !keyed.contains(&c) {
+            keyed.push(c);
+        }
+    }
+    keyed.into_iter().collect()
+}
 
-#[derive(PartialEq, Clone, Copy)]
-pub enum CipherMode {
-    Encode,
-    Decode,
+/// How the keyword ciphers treat letter case
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LetterCase {
+    /// Match letters ignoring case and give each output letter the case of
+    /// its input letter
+    Keep,
+    /// Uppercase the input first
+    Upper,
+    /// Lowercase the input first
+    Lower,
+    /// Uppercase and lowercase letters are different symbols
+    Distinct,
 }
 
+impl LetterCase {
+    pub const ALL: [LetterCase; 4] = [
+        LetterCase::Keep,
+        LetterCase::Upper,
+        LetterCase::Lower,
+        LetterCase::Distinct,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LetterCase::Keep => "Keep",
+            LetterCase::Upper => "Uppercase",
+            LetterCase::Lower => "Lowercase",
+            LetterCase::Distinct => "Distinct",
+        }
+    }
+
+    /// The input with its case folded, if this option folds it
+    pub fn fold<'a>(&self, input: &'a str) -> std::borrow::Cow<'a, str> {
+        match self {
+            LetterCase::Upper => input.to_uppercase().into(),
+            LetterCase::Lower => input.to_lowercase().into(),
+            LetterCase::Keep | LetterCase::Distinct => input.into(),
+        }
+    }
+
+    /// A "Case:" row; `distinct` explains what Distinct does in this module.
+    pub fn ui(&mut self, ui: &mut egui::Ui, distinct: &str) {
+        ui.horizontal(|ui| {
+            ui.label("Case:");
+            egui::ComboBox::from_id_salt(ui.id().with("letter_case"))
+                .width(90.0)
+                .selected_text(self.label())
+                .show_ui(ui, |ui| {
+                    for case in LetterCase::ALL {
+                        ui.selectable_value(self, case, case.label());
+                    }
+                });
+        })
+        .response
+        .on_hover_text(format!(
+            "Keep: letters match either case and keep the input's case\n\
+             Uppercase / Lowercase: fold the input's case first\n\
+             Distinct: {}",
+            distinct
+        ));
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct CaesarCipherModule {
     shift: i32,
-    mode: CipherMode,
+    mode: Direction,
+    /// Output the decryption for every shift, one line each, instead of
+    /// applying `shift`
+    brute_force: bool,
+    /// Alphabet the shift moves along; its length is the modulus
+    script: Script,
+    /// Shift within a keyword-mixed alphabet instead of A-Z (Latin only)
+    keyed: bool,
+    keyword: String,
+    /// Also shift digits, mod 10, by their own `digit_shift`
+    shift_digits: bool,
+    digit_shift: i32,
+    /// Known plaintext used by "Find shift"
+    #[serde(skip)]
+    crib: String,
+    /// Outcome of the last "Find shift" search
+    #[serde(skip)]
+    crib_result: Option<String>,
 }
 
 impl Default for CaesarCipherModule {
     fn default() -> Self {
         Self {
             shift: 1,
-            mode: CipherMode::Encode,
+            mode: Direction::Encode,
+            brute_force: false,
+            script: Script::Latin,
+            keyed: false,
+            keyword: String::new(),
+            shift_digits: false,
+            digit_shift: 1,
+            crib: String::new(),
+            crib_result: None,
         }
     }
 }
 
-impl Module for CaesarCipherModule {
-    fn name(&self) -> &str {
-        "Caesar Cipher"
+impl CaesarCipherModule {
+    /// The uppercase alphabet the shift moves along
+    fn alphabet(&self) -> Vec<char> {
+        match self.script {
+            Script::Latin if self.keyed => {
+                keyed_alphabet(&self.keyword, LATIN_ALPHABET, KeyFill::Forward, false)
+                    .chars()
+                    .collect()
+            }
+            script => script.letters(),
+        }
     }
 
-    fn process(&self, input: &str) -> String {
+    /// Shift the digits of `text` by `digit_shift` in the current mode,
+    /// when enabled.
+    fn apply_digit_shift(&self, text: String) -> String {
+        if !self.shift_digits {
+            return text;
+        }
         let shift = match self.mode {
-            CipherMode::Encode => self.shift.rem_euclid(26) as u8,
-            CipherMode::Decode => (26 - self.shift.rem_euclid(26)) as u8,
-        };
-        input
-            .chars()
-            .map(|c| {
-                if c.is_ascii_alphabetic() {
-                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                    let offset = c as u8 - base;
-                    let new_offset = (offset + shift) % 26;
-                    (base + new_offset) as char
-                } else {
-                    c
+            Direction::Encode => self.digit_shift.rem_euclid(10),
+            Direction::Decode => (-self.digit_shift).rem_euclid(10),
+        } as u32;
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) if c.is_ascii_digit() => {
+                    char::from_digit((d + shift) % 10, 10).unwrap_or(c)
                 }
+                _ => c,
             })
             .collect()
     }
 
+    /// All shifts (0 to the alphabet length) for which processing `input` in
+    /// the current mode yields text containing `crib`, ignoring case.
+    pub fn find_shifts(&self, input: &str, crib: &str) -> Vec<i32> {
+        let crib = crib.to_lowercase();
+        if crib.trim().is_empty() {
+            return Vec::new();
+        }
+        (0..self.alphabet().len() as i32)
+            .filter(|&shift| {
+                let candidate = Self {
+                    shift,
+                    mode: self.mode,
+                    brute_force: false,
+                    script: self.script,
+                    keyed: self.keyed,
+                    keyword: self.keyword.clone(),
+                    shift_digits: self.shift_digits,
+                    digit_shift: self.digit_shift,
+                    crib: String::new(),
+                    crib_result: None,
+                };
+                candidate
+                    .process(input)
+                    .is_ok_and(|output| output.to_lowercase().contains(&crib))
+            })
+            .collect()
+    }
+}
+
+impl Module for CaesarCipherModule {
+    fn name(&self) -> &str {
+        "Caesar Cipher"
+    }
+
+    fn id(&self) -> &str {
+        "caesar"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let alphabet = self.alphabet();
+        let input = self.script.normalize(input);
+        if self.brute_force && !input.is_empty() {
+            let len = alphabet.len();
+            return Ok((0..len)
+                .map(|shift| {
+                    let candidate = map_letters(&input, &alphabet, |i| i + len - shift);
+                    format!("{:>2}: {}", shift, candidate)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"));
+        }
+        let len = alphabet.len() as i32;
+        let shift = match self.mode {
+            Direction::Encode => self.shift.rem_euclid(len),
+            Direction::Decode => len - self.shift.rem_euclid(len),
+        } as usize;
+        Ok(self.apply_digit_shift(map_letters(&input, &alphabet, |i| i + shift)))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        // "All shifts" lists every candidate rather than one decryption
+        if self.brute_force {
+            return None;
+        }
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, CipherMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, CipherMode::Decode, "Decode");
+            for (mode, brute_force, label) in [
+                (Direction::Encode, false, "Encode"),
+                (Direction::Decode, false, "Decode"),
+                (Direction::Decode, true, "All shifts"),
+            ] {
+                let selected = self.mode == mode && self.brute_force == brute_force;
+                if ui.radio(selected, label).clicked() {
+                    self.mode = mode;
+                    self.brute_force = brute_force;
+                }
+            }
         });
+        if !self.brute_force {
+            ui.horizontal(|ui| {
+                ui.label("Shift:");
+                ui.add(egui::DragValue::new(&mut self.shift));
+                ui.checkbox(&mut self.shift_digits, "Shift digits by")
+                    .on_hover_text("Also shift 0-9, mod 10, by a separate amount");
+                if self.shift_digits {
+                    ui.add(egui::DragValue::new(&mut self.digit_shift));
+                }
+            });
+        }
+        egui::ComboBox::from_label("Alphabet")
+            .selected_text(self.script.label())
+            .show_ui(ui, |ui| {
+                for script in Script::ALL {
+                    ui.selectable_value(&mut self.script, script, script.label());
+                }
+            });
+        if self.script == Script::Latin {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.keyed, "Keyed")
+                    .on_hover_text("Shift within an alphabet that starts with the keyword");
+                if self.keyed {
+                    ui.text_edit_singleline(&mut self.keyword);
+                }
+            });
+        }
+
+        // Preview of the substitution the current settings make
+        let alphabet = self.alphabet();
+        let len = alphabet.len() as i32;
+        let row = |shift: i32| -> String {
+            (0..len)
+                .map(|i| alphabet[(i + shift).rem_euclid(len) as usize])
+                .collect()
+        };
+        ui.monospace(format!("Plain:  {}", row(0)));
+        ui.monospace(format!("Cipher: {}", row(self.shift)));
+        if self.shift_digits {
+            let digits: String = (0..10)
+                .map(|i| char::from_digit((i + self.digit_shift).rem_euclid(10) as u32, 10))
+                .map(|c| c.unwrap_or('?'))
+                .collect();
+            ui.monospace(format!("Digits: 0123456789 → {}", digits));
+        }
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
         ui.horizontal(|ui| {
-            ui.label("Shift:");
-            ui.add(egui::DragValue::new(&mut self.shift));
+            ui.label("Crib:");
+            ui.text_edit_singleline(&mut self.crib)
+                .on_hover_text("A word you expect in the plaintext");
+            if ui.button("Find shift").clicked() {
+                let shifts = self.find_shifts(input, &self.crib);
+                self.crib_result = Some(match shifts[..] {
+                    [] => "No shift produces the crib".to_string(),
+                    [shift] => {
+                        self.shift = shift;
+                        format!("Shift set to {}", shift)
+                    }
+                    _ => format!("Several shifts match: {:?}", shifts),
+                });
+            }
         });
+        if let Some(result) = &self.crib_result {
+            ui.small(result);
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -66,7 +465,7 @@ impl Module for CaesarCipherModule {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct ROT13Module;
 
 impl Module for ROT13Module {
@@ -74,21 +473,58 @@ impl Module for ROT13Module {
         "ROT13"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "rot13"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         // ROT13 is just Caesar with shift 13
-        input
+        Ok(map_alphabetic(input, |x| Some(x + 13)))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::Itself)
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui) {
+        // No config
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Rotates every printable ASCII character from '!' to '~' by half of
+/// its 94-character range, so applying it twice gives the input back.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ROT47Module;
+
+impl Module for ROT47Module {
+    fn name(&self) -> &str {
+        "ROT47"
+    }
+
+    fn id(&self) -> &str {
+        "rot47"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(input
             .chars()
-            .map(|c| {
-                if c.is_ascii_alphabetic() {
-                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                    let offset = c as u8 - base;
-                    let new_offset = (offset + 13) % 26;
-                    (base + new_offset) as char
-                } else {
-                    c
-                }
+            .map(|c| match c {
+                '!'..='~' => (33 + (c as u8 - 33 + 47) % 94) as char,
+                _ => c,
             })
-            .collect()
+            .collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::Itself)
     }
 
     fn ui(&mut self, _ui: &mut egui::Ui) {
@@ -104,20 +540,153 @@ impl Module for ROT13Module {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-pub enum A1Z26Mode {
-    Encode,
-    Decode,
+/// Characters a rotation moves
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RotationSet {
+    /// A-Z and a-z, each case rotating within itself
+    Letters,
+    /// Everything from '!' to '~'
+    Printable,
+}
+
+/// ROT-N over letters or over all printable ASCII, covering ROT13, ROT47
+/// and every other amount in one stage.
+#[derive(Serialize, Deserialize)]
+pub struct RotationModule {
+    amount: i32,
+    set: RotationSet,
+    mode: Direction,
+}
+
+impl Default for RotationModule {
+    fn default() -> Self {
+        Self {
+            amount: 13,
+            set: RotationSet::Letters,
+            mode: Direction::Encode,
+        }
+    }
+}
+
+impl Module for RotationModule {
+    fn name(&self) -> &str {
+        "Rotate"
+    }
+
+    fn id(&self) -> &str {
+        "rotate"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let amount = match self.mode {
+            Direction::Encode => self.amount as i64,
+            Direction::Decode => -(self.amount as i64),
+        };
+        Ok(match self.set {
+            RotationSet::Letters => {
+                let shift = amount.rem_euclid(26) as u8;
+                map_alphabetic(input, |x| Some(x + shift))
+            }
+            RotationSet::Printable => {
+                let shift = amount.rem_euclid(94) as u8;
+                input
+                    .chars()
+                    .map(|c| match c {
+                        '!'..='~' => (33 + (c as u8 - 33 + shift) % 94) as char,
+                        _ => c,
+                    })
+                    .collect()
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Rotate:");
+            ui.radio_value(&mut self.set, RotationSet::Letters, "Letters only");
+            ui.radio_value(&mut self.set, RotationSet::Printable, "All printable ASCII");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Amount:");
+            ui.add(egui::DragValue::new(&mut self.amount));
+            if ui.button("ROT13").clicked() {
+                (self.amount, self.set) = (13, RotationSet::Letters);
+            }
+            if ui.button("ROT47").clicked() {
+                (self.amount, self.set) = (47, RotationSet::Printable);
+            }
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct AtbashModule;
+
+impl Module for AtbashModule {
+    fn name(&self) -> &str {
+        "Atbash"
+    }
+
+    fn id(&self) -> &str {
+        "atbash"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Mirror the alphabet: A <-> Z, B <-> Y, ...
+        Ok(map_alphabetic(input, |x| Some(25 - x)))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::Itself)
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui) {
+        // No config
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
 }
 
+/// Largest number A can be given
+const A1Z26_MAX_OFFSET: u32 = 100;
+
+#[derive(Serialize, Deserialize)]
 pub struct A1Z26Module {
-    mode: A1Z26Mode,
+    mode: Direction,
+    /// Number given to A: 1 for A1Z26, 0 for A0Z25
+    offset: u32,
 }
 
 impl Default for A1Z26Module {
     fn default() -> Self {
         Self {
-            mode: A1Z26Mode::Encode,
+            mode: Direction::Encode,
+            offset: 1,
         }
     }
 }
@@ -127,14 +696,24 @@ impl Module for A1Z26Module {
         "A1Z26"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            A1Z26Mode::Encode => input
+    fn id(&self) -> &str {
+        "a1z26"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        if self.offset > A1Z26_MAX_OFFSET {
+            return Err(ProcessError::BadConfig(format!(
+                "A must be 0 to {}, got {}",
+                A1Z26_MAX_OFFSET, self.offset
+            )));
+        }
+        Ok(match self.mode {
+            Direction::Encode => input
                 .chars()
                 .filter_map(|c| {
                     if c.is_ascii_alphabetic() {
                         let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                        Some(format!("{}", c as u8 - base + 1))
+                        Some(format!("{}", (c as u8 - base) as u32 + self.offset))
                     } else if c.is_whitespace() {
                         Some(" ".to_string())
                     } else {
@@ -143,31 +722,42 @@ impl Module for A1Z26Module {
                 })
                 .collect::<Vec<_>>()
                 .join("-"),
-            A1Z26Mode::Decode => {
+            Direction::Decode => {
                 // Split by non-digit characters
                 input
                     .split(|c: char| !c.is_ascii_digit())
                     .filter(|s| !s.is_empty())
                     .map(|s| {
-                        if let Ok(n) = s.parse::<u8>() {
-                            if (1..=26).contains(&n) {
-                                (b'a' + n - 1) as char
-                            } else {
-                                '?'
-                            }
-                        } else {
-                            '?'
+                        match s
+                            .parse::<u32>()
+                            .ok()
+                            .and_then(|n| n.checked_sub(self.offset))
+                        {
+                            Some(index) if index < 26 => (b'a' + index as u8) as char,
+                            _ => '?',
                         }
                     })
                     .collect()
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, A1Z26Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, A1Z26Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("A =");
+            ui.add(egui::DragValue::new(&mut self.offset).range(0..=A1Z26_MAX_OFFSET));
+            ui.label(format!("Z = {}", self.offset.saturating_add(25)));
         });
     }
 
@@ -180,10 +770,11 @@ impl Module for A1Z26Module {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct AffineCipherModule {
     a: i32,
     b: i32,
-    mode: CipherMode,
+    mode: Direction,
 }
 
 impl Default for AffineCipherModule {
@@ -191,79 +782,170 @@ impl Default for AffineCipherModule {
         Self {
             a: 5,
             b: 8,
-            mode: CipherMode::Encode,
+            mode: Direction::Encode,
         }
     }
 }
 
-impl AffineCipherModule {
-    /// Calculate modular multiplicative inverse using Extended Euclidean Algorithm
-    fn mod_inverse(a: i32, m: i32) -> Option<i32> {
-        let (mut t, mut new_t) = (0, 1);
-        let (mut r, mut new_r) = (m, a);
+impl Module for AffineCipherModule {
+    fn name(&self) -> &str {
+        "Affine Cipher"
+    }
 
-        while new_r != 0 {
-            let quotient = r / new_r;
-            (t, new_t) = (new_t, t - quotient * new_t);
-            (r, new_r) = (new_r, r - quotient * new_r);
-        }
+    fn id(&self) -> &str {
+        "affine"
+    }
 
-        if r > 1 {
-            return None; // a is not invertible
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let a = self.a.rem_euclid(26);
+        let b = self.b.rem_euclid(26);
+
+        if a % 2 == 0 || a == 13 {
+            return Err(ProcessError::BadConfig(format!(
+                "'a' ({}) must be coprime to 26",
+                a
+            )));
         }
-        if t < 0 {
-            t += m;
+
+        // D(y) = a^(-1) * (y - b) mod 26
+        let a_inv = mod_inverse(a, 26).unwrap_or(1);
+        Ok(map_alphabetic(input, |x| {
+            let x = x as i32;
+            let new_x = match self.mode {
+                Direction::Encode => (a * x + b).rem_euclid(26),
+                Direction::Decode => (a_inv * (x - b)).rem_euclid(26),
+            };
+            Some(new_x as u8)
+        }))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("a (Slope):");
+            ui.add(egui::DragValue::new(&mut self.a));
+            ui.label("b (Intercept):");
+            ui.add(egui::DragValue::new(&mut self.b));
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HillCipherModule {
+    /// 2x2 key matrix, by rows
+    key: [[i32; 2]; 2],
+    mode: Direction,
+}
+
+impl Default for HillCipherModule {
+    fn default() -> Self {
+        Self {
+            key: [[3, 3], [2, 5]],
+            mode: Direction::Encode,
         }
-        Some(t)
     }
 }
 
-impl Module for AffineCipherModule {
+impl HillCipherModule {
+    /// The matrix that undoes the key mod 26
+    fn inverse(&self) -> Result<[[i32; 2]; 2], String> {
+        let [[a, b], [c, d]] = self.key;
+        let det = (a * d - b * c).rem_euclid(26);
+        let det_inv = mod_inverse(det, 26).ok_or_else(|| {
+            format!(
+                "The key's determinant ({} mod 26) must be coprime to 26 for the cipher to be reversible",
+                det
+            )
+        })?;
+        Ok([[d, -b], [-c, a]].map(|row| row.map(|x| (det_inv * x).rem_euclid(26))))
+    }
+}
+
+impl Module for HillCipherModule {
     fn name(&self) -> &str {
-        "Affine Cipher"
+        "Hill Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        let a = self.a.rem_euclid(26);
-        let b = self.b.rem_euclid(26);
+    fn id(&self) -> &str {
+        "hill"
+    }
 
-        if a % 2 == 0 || a == 13 {
-            return format!("Error: 'a' ({}) must be coprime to 26.", a);
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Encoding needs the inverse too, so a key that cannot be undone is refused
+        let inverse = self.inverse().map_err(ProcessError::BadConfig)?;
+        let matrix = match self.mode {
+            Direction::Encode => self.key.map(|row| row.map(|x| x.rem_euclid(26))),
+            Direction::Decode => inverse,
+        };
+
+        let mut letters: Vec<i32> = input
+            .bytes()
+            .filter(u8::is_ascii_alphabetic)
+            .map(|b| (b.to_ascii_uppercase() - b'A') as i32)
+            .collect();
+        let padded = letters.len() % 2 == 1;
+        if padded {
+            if self.mode == Direction::Decode {
+                return Err(ProcessError::InvalidInput(
+                    "Hill ciphertext must have an even number of letters".to_string(),
+                ));
+            }
+            letters.push((b'X' - b'A') as i32);
         }
 
-        input
-            .chars()
-            .map(|c| {
-                if c.is_ascii_alphabetic() {
-                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                    let x = (c as u8 - base) as i32;
-                    let new_x = match self.mode {
-                        CipherMode::Encode => (a * x + b).rem_euclid(26),
-                        CipherMode::Decode => {
-                            // D(y) = a^(-1) * (y - b) mod 26
-                            let a_inv = Self::mod_inverse(a, 26).unwrap_or(1);
-                            (a_inv * (x - b)).rem_euclid(26)
-                        }
-                    } as u8;
-                    (base + new_x) as char
-                } else {
-                    c
-                }
-            })
-            .collect()
+        // Each pair of letters is a column vector multiplied by the matrix
+        let mut mapped = letters.chunks(2).flat_map(|pair| {
+            matrix.map(|row| ((row[0] * pair[0] + row[1] * pair[1]).rem_euclid(26)) as u8)
+        });
+        let mut output = map_alphabetic(input, |_| mapped.next());
+        if padded {
+            output.extend(mapped.map(|x| (b'A' + x) as char));
+        }
+        Ok(output)
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, CipherMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, CipherMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
-        ui.horizontal(|ui| {
-            ui.label("a (Slope):");
-            ui.add(egui::DragValue::new(&mut self.a));
-            ui.label("b (Intercept):");
-            ui.add(egui::DragValue::new(&mut self.b));
+        ui.label("Key matrix:");
+        egui::Grid::new("hill_key").show(ui, |ui| {
+            for row in &mut self.key {
+                for x in row {
+                    ui.add(egui::DragValue::new(x).range(0..=25));
+                }
+                ui.end_row();
+            }
         });
+        if let Err(e) = self.inverse() {
+            ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -275,17 +957,104 @@ impl Module for AffineCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct VigenereCipherModule {
     key: String,
-    mode: A1Z26Mode,
+    mode: Direction,
+    /// Read the key as comma/space-separated shifts (any integer, mod 26)
+    /// instead of letters
+    numeric_key: bool,
+    /// Follow the key with the plaintext itself instead of repeating it
+    autokey: bool,
+    /// Assumed key length for "Recover key"
+    period: usize,
+    /// Outcome of the last "Recover key"
+    #[serde(skip)]
+    recovered: Option<String>,
+    case: LetterCase,
 }
 
 impl Default for VigenereCipherModule {
     fn default() -> Self {
         Self {
             key: String::from("KEY"),
-            mode: A1Z26Mode::Encode,
+            mode: Direction::Encode,
+            numeric_key: false,
+            autokey: false,
+            period: 3,
+            recovered: None,
+            case: LetterCase::Keep,
+        }
+    }
+}
+
+impl VigenereCipherModule {
+    /// Letters the shifts run over: A-Z, or A-Z then a-z when case is
+    /// distinct.
+    fn alphabet_len(&self) -> u8 {
+        if self.case == LetterCase::Distinct {
+            52
+        } else {
+            26
+        }
+    }
+
+    /// The key as shifts in 0..alphabet_len.
+    fn shifts(&self) -> Result<Vec<u8>, String> {
+        let n = self.alphabet_len();
+        if !self.numeric_key {
+            return Ok(self
+                .key
+                .chars()
+                .filter(|c| c.is_ascii_alphabetic())
+                .map(|c| match c {
+                    'a'..='z' if n == 52 => c as u8 - b'a' + 26,
+                    _ => c.to_ascii_uppercase() as u8 - b'A',
+                })
+                .collect());
         }
+        self.key
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<i64>()
+                    .map(|shift| shift.rem_euclid(n as i64) as u8)
+                    .map_err(|_| format!("Invalid shift '{}' in numeric key", token))
+            })
+            .collect()
+    }
+
+    /// Most likely key of the given length for Vigenère ciphertext, found by
+    /// matching each column's letter frequencies against English.
+    pub fn recover_key(ciphertext: &str, period: usize) -> String {
+        let letters: Vec<u8> = ciphertext
+            .bytes()
+            .filter(u8::is_ascii_alphabetic)
+            .map(|b| b.to_ascii_uppercase() - b'A')
+            .collect();
+        (0..period)
+            .map(|column| {
+                let mut counts = [0usize; 26];
+                for &letter in letters.iter().skip(column).step_by(period) {
+                    counts[letter as usize] += 1;
+                }
+                // Decrypting with shift k turns letter (x + k) back into x
+                let shift = (0..26)
+                    .min_by(|&a, &b| {
+                        let score = |k: usize| {
+                            let mut shifted = [0usize; 26];
+                            for (x, slot) in shifted.iter_mut().enumerate() {
+                                *slot = counts[(x + k) % 26];
+                            }
+                            english_chi_squared(&shifted)
+                        };
+                        score(a).total_cmp(&score(b))
+                    })
+                    .unwrap_or(0);
+                char::from(b'A' + shift as u8)
+            })
+            .collect()
     }
 }
 
@@ -294,49 +1063,111 @@ impl Module for VigenereCipherModule {
         "Vigenere Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        let key_clean: Vec<u8> = self
-            .key
-            .chars()
-            .filter(|c| c.is_ascii_alphabetic())
-            .map(|c| c.to_ascii_uppercase() as u8 - b'A')
-            .collect();
+    fn id(&self) -> &str {
+        "vigenere"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let key_clean = self.shifts().map_err(ProcessError::BadConfig)?;
+        let input = self.case.fold(input);
 
         if key_clean.is_empty() {
-            return input.to_string();
+            return Ok(input.into_owned());
         }
 
-        let mut key_idx = 0;
-        input
-            .chars()
-            .map(|c| {
-                if c.is_ascii_alphabetic() {
-                    let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                    let x = c as u8 - base;
-                    let k = key_clean[key_idx % key_clean.len()];
-                    key_idx += 1;
-
-                    let new_x = match self.mode {
-                        A1Z26Mode::Encode => (x + k) % 26,
-                        A1Z26Mode::Decode => (x + 26 - k) % 26,
-                    };
-                    (base + new_x) as char
-                } else {
-                    c
-                }
+        let n = self.alphabet_len();
+        let map = |f: &mut dyn FnMut(u8) -> Option<u8>| {
+            if n == 52 {
+                map_cased(&input, f)
+            } else {
+                map_alphabetic(&input, f)
+            }
+        };
+
+        if self.autokey {
+            // Each plaintext letter joins the end of the key as it is
+            // known, which when decoding is only after decrypting it
+            let mut keystream: std::collections::VecDeque<u8> = key_clean.into();
+            return Ok(map(&mut |x| {
+                let k = keystream.pop_front()?;
+                let (output, plain) = match self.mode {
+                    Direction::Encode => (x + k, x),
+                    Direction::Decode => {
+                        let plain = (x + n - k) % n;
+                        (plain, plain)
+                    }
+                };
+                keystream.push_back(plain);
+                Some(output)
+            }));
+        }
+
+        // The key only advances on letters
+        let mut keystream = key_clean.iter().cycle();
+        Ok(map(&mut |x| {
+            let k = *keystream.next()?;
+            Some(match self.mode {
+                Direction::Encode => x + k,
+                Direction::Decode => x + n - k,
             })
-            .collect()
+        }))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, A1Z26Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, A1Z26Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Key:");
             ui.text_edit_singleline(&mut self.key);
         });
+        ui.checkbox(&mut self.numeric_key, "Numeric key")
+            .on_hover_text("Key is a list of shifts like 3,-1,14,7 instead of letters");
+        ui.checkbox(&mut self.autokey, "Autokey").on_hover_text(
+            "After the key runs out, continue it with the plaintext instead of repeating it",
+        );
+        self.case.ui(
+            ui,
+            "shift over the 52 letters A-Z then a-z, with key letters counted the same way",
+        );
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        // Frequency analysis only finds repeating keys over A-Z
+        if self.mode != Direction::Decode || self.autokey || self.case == LetterCase::Distinct {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Key length:");
+            ui.add(egui::DragValue::new(&mut self.period).range(1..=50));
+            if ui
+                .button("Recover key")
+                .on_hover_text("Guess each key letter from English letter frequencies")
+                .clicked()
+            {
+                let mut key = Self::recover_key(input, self.period);
+                if self.numeric_key {
+                    key = key
+                        .bytes()
+                        .map(|b| (b - b'A').to_string())
+                        .collect::<Vec<_>>()
+                        .join(",");
+                }
+                self.recovered = Some(format!("Key set to {}", key));
+                self.key = key;
+            }
+        });
+        if let Some(result) = &self.recovered {
+            ui.small(result);
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -348,16 +1179,17 @@ impl Module for VigenereCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct RailFenceCipherModule {
     rails: i32,
-    mode: A1Z26Mode,
+    mode: Direction,
 }
 
 impl Default for RailFenceCipherModule {
     fn default() -> Self {
         Self {
             rails: 3,
-            mode: A1Z26Mode::Encode,
+            mode: Direction::Encode,
         }
     }
 }
@@ -367,16 +1199,20 @@ impl Module for RailFenceCipherModule {
         "Rail Fence Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "rail_fence"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         let rails = self.rails.max(2) as usize;
         let chars: Vec<char> = input.chars().collect();
         let len = chars.len();
         if len == 0 {
-            return String::new();
+            return Ok(String::new());
         }
 
-        match self.mode {
-            A1Z26Mode::Encode => {
+        Ok(match self.mode {
+            Direction::Encode => {
                 let mut fence = vec![vec![]; rails];
                 let mut rail = 0;
                 let mut direction = 1;
@@ -397,13 +1233,14 @@ impl Module for RailFenceCipherModule {
                 }
                 fence.into_iter().flatten().collect()
             }
-            A1Z26Mode::Decode => {
-                let mut fence = vec![vec![0; len]; rails];
+            Direction::Decode => {
+                // Record which rail each position of the zigzag lands on
+                let mut rail_of = vec![0; len];
                 let mut rail = 0;
                 let mut direction = 1;
 
-                for i in 0..len {
-                    fence[rail][i] = 1;
+                for slot in rail_of.iter_mut() {
+                    *slot = rail;
                     if rail == 0 {
                         direction = 1;
                     } else if rail == rails - 1 {
@@ -418,41 +1255,36 @@ impl Module for RailFenceCipherModule {
 
                 let mut char_iter = chars.into_iter();
                 let mut filled_fence = vec![vec!['\0'; len]; rails];
-                for r in 0..rails {
-                    for c in 0..len {
-                        if fence[r][c] == 1 {
+                for (r, row) in filled_fence.iter_mut().enumerate() {
+                    for (c, &slot) in rail_of.iter().enumerate() {
+                        if slot == r {
                             if let Some(ch) = char_iter.next() {
-                                filled_fence[r][c] = ch;
+                                row[c] = ch;
                             }
                         }
                     }
                 }
 
                 let mut result = String::new();
-                rail = 0;
-                direction = 1;
-                for c in 0..len {
-                    result.push(filled_fence[rail][c]);
-                    if rail == 0 {
-                        direction = 1;
-                    } else if rail == rails - 1 {
-                        direction = -1;
-                    }
-                    if direction == 1 {
-                        rail += 1;
-                    } else {
-                        rail -= 1;
-                    }
+                for (c, &r) in rail_of.iter().enumerate() {
+                    result.push(filled_fence[r][c]);
                 }
                 result
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, A1Z26Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, A1Z26Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Rails:");
@@ -469,14 +1301,237 @@ impl Module for RailFenceCipherModule {
     }
 }
 
+/// Scytale: the text is wound around a rod, one row per turn, so it is
+/// written row-wise into a grid `columns` wide and read off column-wise.
+#[derive(Serialize, Deserialize)]
+pub struct ScytaleModule {
+    columns: usize,
+    mode: Direction,
+}
+
+impl Default for ScytaleModule {
+    fn default() -> Self {
+        Self {
+            columns: 4,
+            mode: Direction::Encode,
+        }
+    }
+}
+
+impl Module for ScytaleModule {
+    fn name(&self) -> &str {
+        "Scytale Cipher"
+    }
+
+    fn id(&self) -> &str {
+        "scytale"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let chars: Vec<char> = input.chars().collect();
+        // More columns than characters reads the text back unchanged
+        let columns = self.columns.clamp(1, chars.len().max(1));
+
+        Ok(match self.mode {
+            Direction::Encode => (0..columns)
+                .flat_map(|c| chars.iter().skip(c).step_by(columns))
+                .collect(),
+            Direction::Decode => {
+                // The last row may be short, so only the first columns
+                // hold a character from it
+                let rows = chars.len().div_ceil(columns);
+                let long_columns = match chars.len() % columns {
+                    0 => columns,
+                    n => n,
+                };
+                let mut grid = Vec::with_capacity(columns);
+                let mut rest = chars.as_slice();
+                for c in 0..columns {
+                    let height = if c < long_columns { rows } else { rows - 1 };
+                    let (column, tail) = rest.split_at(height.min(rest.len()));
+                    grid.push(column);
+                    rest = tail;
+                }
+                (0..rows)
+                    .flat_map(|r| grid.iter().filter_map(move |column| column.get(r)))
+                    .collect()
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Columns:");
+            ui.add(egui::DragValue::new(&mut self.columns).range(1..=100))
+                .on_hover_text("Letters per turn around the rod");
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Columnar transposition: write `text` into rows under `key`, then read
+/// the columns in alphabetical key order (ties left to right). Returns the
+/// columns in reading order. Non-letters in the key are ignored; an empty
+/// key leaves the text as a single column.
+pub fn columnar_columns(text: &[char], key: &str) -> Vec<String> {
+    let order = columnar_order(key);
+    if order.is_empty() {
+        return vec![text.iter().collect()];
+    }
+    order
+        .iter()
+        .map(|&col| text.iter().skip(col).step_by(order.len()).collect())
+        .collect()
+}
+
+/// Undo [`columnar_columns`] for the concatenated columns.
+pub fn columnar_decrypt(text: &[char], key: &str) -> String {
+    let order = columnar_order(key);
+    if order.is_empty() {
+        return text.iter().collect();
+    }
+
+    // The first `len % cols` columns (in key position) hold one extra row
+    let cols = order.len();
+    let rows = text.len() / cols;
+    let long_cols = text.len() % cols;
+    let mut grid = vec![vec![]; cols];
+    let mut rest = text;
+    for &col in &order {
+        let len = rows + usize::from(col < long_cols);
+        let (column, tail) = rest.split_at(len);
+        grid[col] = column.to_vec();
+        rest = tail;
+    }
+
+    (0..text.len()).map(|i| grid[i % cols][i / cols]).collect()
+}
+
+fn columnar_order(key: &str) -> Vec<usize> {
+    let key: Vec<char> = key
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let mut order: Vec<usize> = (0..key.len()).collect();
+    order.sort_by_key(|&i| key[i]);
+    order
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ColumnarTranspositionModule {
+    key: String,
+    /// Apply a second transposition with `second_key` (double transposition)
+    double: bool,
+    second_key: String,
+    mode: Direction,
+}
+
+impl Default for ColumnarTranspositionModule {
+    fn default() -> Self {
+        Self {
+            key: "ZEBRAS".to_string(),
+            double: false,
+            second_key: String::new(),
+            mode: Direction::Encode,
+        }
+    }
+}
+
+impl Module for ColumnarTranspositionModule {
+    fn name(&self) -> &str {
+        "Columnar Transposition"
+    }
+
+    fn id(&self) -> &str {
+        "columnar"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Whitespace is dropped so the grid only holds message characters
+        let mut text: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut keys = vec![self.key.as_str()];
+        if self.double {
+            keys.push(self.second_key.as_str());
+        }
+
+        match self.mode {
+            Direction::Encode => {
+                for key in keys {
+                    text = columnar_columns(&text, key).concat().chars().collect();
+                }
+            }
+            Direction::Decode => {
+                for key in keys.into_iter().rev() {
+                    text = columnar_decrypt(&text, key).chars().collect();
+                }
+            }
+        }
+        Ok(text.into_iter().collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Key:");
+            ui.text_edit_singleline(&mut self.key);
+        });
+        ui.checkbox(&mut self.double, "Double transposition");
+        if self.double {
+            ui.horizontal(|ui| {
+                ui.label("Second key:");
+                ui.text_edit_singleline(&mut self.second_key);
+            });
+        }
+        ui.label("Columns are read in alphabetical key order; repeated letters go left to right");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct BaconCipherModule {
-    mode: A1Z26Mode,
+    mode: Direction,
 }
 
 impl Default for BaconCipherModule {
     fn default() -> Self {
         Self {
-            mode: A1Z26Mode::Encode,
+            mode: Direction::Encode,
         }
     }
 }
@@ -486,9 +1541,13 @@ impl Module for BaconCipherModule {
         "Bacon Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            A1Z26Mode::Encode => input
+    fn id(&self) -> &str {
+        "bacon"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => input
                 .to_uppercase()
                 .chars()
                 .map(|c| {
@@ -508,7 +1567,7 @@ impl Module for BaconCipherModule {
                     }
                 })
                 .collect(),
-            A1Z26Mode::Decode => {
+            Direction::Decode => {
                 let clean: String = input
                     .chars()
                     .filter(|c| *c == 'a' || *c == 'b' || *c == 'A' || *c == 'B')
@@ -537,13 +1596,20 @@ impl Module for BaconCipherModule {
                     })
                     .collect()
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, A1Z26Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, A1Z26Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
     }
 
@@ -556,10 +1622,19 @@ impl Module for BaconCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct AlphabeticalSubstitutionModule {
     plaintext: String,
     ciphertext: String,
-    mode: CipherMode,
+    mode: Direction,
+    /// Keyword the ciphertext alphabet is generated from
+    keyword: String,
+    fill: KeyFill,
+    /// Also map digits paired with digits in the two alphabets
+    map_digits: bool,
+    /// Letters left as they are, whatever the alphabets say
+    pass_through: String,
+    case: LetterCase,
 }
 
 impl Default for AlphabeticalSubstitutionModule {
@@ -567,48 +1642,106 @@ impl Default for AlphabeticalSubstitutionModule {
         Self {
             plaintext: "abcdefghijklmnopqrstuvwxyz".to_string(),
             ciphertext: "zyxwvutsrqponmlkjihgfedcba".to_string(),
-            mode: CipherMode::Encode,
+            mode: Direction::Encode,
+            keyword: String::new(),
+            fill: KeyFill::Forward,
+            map_digits: false,
+            pass_through: String::new(),
+            case: LetterCase::Keep,
         }
     }
 }
 
+/// Relative frequency of each letter A-Z in `input`, ignoring case and
+/// non-letters, most frequent first. Letters that do not occur are left out.
+pub fn frequency_table(input: &str) -> Vec<(char, f32)> {
+    let mut counts = [0usize; 26];
+    for c in input.chars().filter(char::is_ascii_alphabetic) {
+        counts[(c.to_ascii_uppercase() as u8 - b'A') as usize] += 1;
+    }
+    let total: usize = counts.iter().sum();
+    let mut table: Vec<(char, f32)> = (b'A'..=b'Z')
+        .zip(counts)
+        .filter(|&(_, count)| count > 0)
+        .map(|(letter, count)| (letter as char, count as f32 / total as f32))
+        .collect();
+    // Stable, so tied letters stay in alphabetical order
+    table.sort_by(|a, b| b.1.total_cmp(&a.1));
+    table
+}
+
 impl Module for AlphabeticalSubstitutionModule {
     fn name(&self) -> &str {
         "Alphabetical Substitution"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "substitution"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         let plain_chars: Vec<char> = self.plaintext.chars().collect();
         let cipher_chars: Vec<char> = self.ciphertext.chars().collect();
 
         if plain_chars.len() != cipher_chars.len() {
-            return "Error: Plaintext and Ciphertext alphabets must have the same length."
-                .to_string();
+            return Err(ProcessError::BadConfig(
+                "Plaintext and Ciphertext alphabets must have the same length".to_string(),
+            ));
         }
 
-        let mut map = std::collections::HashMap::new();
         // In encode mode: plaintext -> ciphertext
         // In decode mode: ciphertext -> plaintext (swap the mapping)
         let (from_chars, to_chars) = match self.mode {
-            CipherMode::Encode => (&plain_chars, &cipher_chars),
-            CipherMode::Decode => (&cipher_chars, &plain_chars),
+            Direction::Encode => (&plain_chars, &cipher_chars),
+            Direction::Decode => (&cipher_chars, &plain_chars),
         };
 
-        for (i, &f) in from_chars.iter().enumerate() {
-            map.insert(f, to_chars[i]);
-            map.insert(f.to_ascii_uppercase(), to_chars[i].to_ascii_uppercase());
+        // Letters map case-insensitively and keep the input's case, unless
+        // case is distinct, when each letter maps exactly as written
+        let distinct = self.case == LetterCase::Distinct;
+        let index = |c: char| match c {
+            'a'..='z' if distinct => (c as u8 - b'a' + 26) as usize,
+            _ => (c.to_ascii_uppercase() as u8 - b'A') as usize,
+        };
+        let mut table = [None; 52];
+        let mut digits = [None; 10];
+        for (&f, &t) in from_chars.iter().zip(to_chars) {
+            if f.is_ascii_alphabetic() && t.is_ascii_alphabetic() {
+                table[index(f)] = Some(index(t) as u8);
+            } else if self.map_digits && f.is_ascii_digit() && t.is_ascii_digit() {
+                digits[(f as u8 - b'0') as usize] = Some(t);
+            }
+        }
+        for c in self.pass_through.chars().filter(char::is_ascii_alphabetic) {
+            table[index(c)] = None;
         }
 
-        input
+        let input = self.case.fold(input);
+        let letters = if distinct {
+            map_cased(&input, |x| table[x as usize])
+        } else {
+            map_alphabetic(&input, |x| table[x as usize])
+        };
+        Ok(letters
             .chars()
-            .map(|c| map.get(&c).cloned().unwrap_or(c))
-            .collect()
+            .map(|c| match c.to_digit(10) {
+                Some(d) if c.is_ascii_digit() => digits[d as usize].unwrap_or(c),
+                _ => c,
+            })
+            .collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, CipherMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, CipherMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Plaintext:");
@@ -618,6 +1751,75 @@ impl Module for AlphabeticalSubstitutionModule {
             ui.label("Ciphertext:");
             ui.text_edit_singleline(&mut self.ciphertext);
         });
+        ui.horizontal(|ui| {
+            ui.label("Keyword:");
+            ui.text_edit_singleline(&mut self.keyword);
+            ui.radio_value(&mut self.fill, KeyFill::Forward, "A→Z")
+                .on_hover_text("Fill the rest of the alphabet in order");
+            ui.radio_value(&mut self.fill, KeyFill::Reverse, "Z→A")
+                .on_hover_text("Fill the rest of the alphabet backwards");
+            if ui
+                .button("Generate")
+                .on_hover_text("Set the ciphertext alphabet to the keyword followed by the unused plaintext letters")
+                .clicked()
+            {
+                self.ciphertext = keyed_alphabet(&self.keyword, &self.plaintext, self.fill, false);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.map_digits, "Map digits")
+                .on_hover_text(
+                    "Also substitute digits, when both alphabets have digits at the same places",
+                );
+            ui.label("Leave unchanged:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.pass_through)
+                    .hint_text("letters")
+                    .desired_width(80.0),
+            )
+            .on_hover_text("Letters that keep their value even if the alphabets map them");
+        });
+        self.case.ui(
+            ui,
+            "each letter maps exactly as written in the alphabets, others stay as they are",
+        );
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        egui::CollapsingHeader::new("Frequency analysis")
+            .id_salt("substitution_frequencies")
+            .show(ui, |ui| {
+                let table = frequency_table(input);
+                if table.is_empty() {
+                    ui.small("The input has no letters");
+                    return;
+                }
+                // English letters from most to least common: ETAOIN SHRDLU...
+                let mut english: Vec<(char, f32)> = (b'A'..=b'Z')
+                    .map(char::from)
+                    .zip(ENGLISH_FREQUENCIES)
+                    .collect();
+                english.sort_by(|a, b| b.1.total_cmp(&a.1));
+                ui.small("Line up the most common input letters with the most common English ones");
+                egui::Grid::new("substitution_frequency_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Input");
+                        ui.strong("");
+                        ui.strong("English");
+                        ui.strong("");
+                        ui.end_row();
+                        for (&(letter, freq), &(expected, expected_freq)) in
+                            table.iter().zip(&english)
+                        {
+                            ui.monospace(letter.to_string());
+                            ui.monospace(format!("{:5.1}%", freq * 100.0));
+                            ui.monospace(expected.to_string());
+                            ui.monospace(format!("{:5.1}%", expected_freq * 100.0));
+                            ui.end_row();
+                        }
+                    });
+            });
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -628,3 +1830,382 @@ impl Module for AlphabeticalSubstitutionModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::create_module;
+
+    const FORMATTED: &str = "Tab\there,\r\nnew line; (punct!) 123 «é» end.\n";
+
+    #[test]
+    fn test_map_alphabetic_preserves_formatting() {
+        assert_eq!(
+            map_alphabetic(FORMATTED, |x| Some(x + 1)),
+            "Ubc\tifsf,\r\nofx mjof; (qvodu!) 123 «é» foe.\n"
+        );
+        assert_eq!(map_alphabetic(FORMATTED, |_| None), FORMATTED);
+
+        let others = |text: &str| -> Vec<(usize, char)> {
+            text.chars()
+                .enumerate()
+                .filter(|(_, c)| !c.is_ascii_alphabetic())
+                .collect()
+        };
+        for id in ["caesar", "vigenere", "affine", "atbash", "substitution"] {
+            let output = create_module(id).unwrap().process(FORMATTED).unwrap();
+            assert_eq!(output.chars().count(), FORMATTED.chars().count(), "{id}");
+            assert_eq!(others(&output), others(FORMATTED), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_caesar_crib_finds_unique_shift() {
+        let plaintext = "Meet me at the old bridge at dawn";
+        let ciphertext = CaesarCipherModule {
+            shift: 7,
+            ..Default::default()
+        }
+        .process(plaintext)
+        .unwrap();
+        let decoder = CaesarCipherModule {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(decoder.find_shifts(&ciphertext, "BRIDGE"), [7]);
+        assert!(decoder.find_shifts(&ciphertext, "e").len() > 1);
+        assert!(decoder.find_shifts(&ciphertext, "castle").is_empty());
+        assert!(decoder.find_shifts(&ciphertext, " ").is_empty());
+
+        // The crib is a search aid, not a setting, so recipes leave it out
+        let with_crib = CaesarCipherModule {
+            crib: "bridge".to_string(),
+            ..decoder
+        };
+        assert!(serde_json::to_value(&with_crib)
+            .unwrap()
+            .get("crib")
+            .is_none());
+    }
+
+    #[test]
+    fn test_double_columnar_round_trip() {
+        let plaintext = "WEAREDISCOVEREDFLEEATONCE";
+        let single = ColumnarTranspositionModule::default();
+        // The Wikipedia example for key ZEBRAS, without the padding letters
+        assert_eq!(
+            single.process(plaintext).unwrap(),
+            "EVLNACDTESEAROFODEECWIREE"
+        );
+
+        let mut double = ColumnarTranspositionModule {
+            double: true,
+            second_key: "STRIPE".to_string(),
+            ..Default::default()
+        };
+        let encoded = double.process(plaintext).unwrap();
+        assert_ne!(encoded, single.process(plaintext).unwrap());
+        double.mode = Direction::Decode;
+        assert_eq!(double.process(&encoded).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_vigenere_recovers_four_letter_key() {
+        let plaintext = "It was the best of times, it was the worst of times, it was the age of \
+            wisdom, it was the age of foolishness, it was the epoch of belief, it was the \
+            epoch of incredulity, it was the season of Light, it was the season of Darkness, \
+            it was the spring of hope, it was the winter of despair.";
+        let ciphertext = VigenereCipherModule {
+            key: "LIME".to_string(),
+            ..Default::default()
+        }
+        .process(plaintext)
+        .unwrap();
+        assert_eq!(VigenereCipherModule::recover_key(&ciphertext, 4), "LIME");
+    }
+
+    #[test]
+    fn test_keyed_caesar() {
+        // KEYWORD gives the alphabet KEYWORDABCFGHIJLMNPQSTUVXZ, in which
+        // the letters of HELLO WORLD are followed by IYMMR ORDMA
+        let mut module = CaesarCipherModule {
+            keyed: true,
+            keyword: "keyword".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(module.process("Hello, World!").unwrap(), "Iymmr, Ordma!");
+        module.mode = Direction::Decode;
+        assert_eq!(module.process("Iymmr, Ordma!").unwrap(), "Hello, World!");
+    }
+
+    #[test]
+    fn test_vigenere_numeric_key_round_trip() {
+        let mut module = VigenereCipherModule {
+            key: "3,-1, 14 7".to_string(),
+            numeric_key: true,
+            ..Default::default()
+        };
+        // -1 shifts back a letter, the same as 25
+        assert_eq!(module.shifts().unwrap(), [3, 25, 14, 7]);
+        let encoded = module.process("Hello, World!").unwrap();
+        assert!(encoded.starts_with("Kdzs"));
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "Hello, World!");
+
+        module.key = "3,x".to_string();
+        assert!(module.process("Hello").is_err());
+    }
+
+    #[test]
+    fn test_caesar_cyrillic_and_greek() {
+        let shift = |script, mode, input: &str| {
+            CaesarCipherModule {
+                shift: 3,
+                mode,
+                script,
+                ..Default::default()
+            }
+            .process(input)
+            .unwrap()
+        };
+        // Я wraps around to В in the 33-letter alphabet with Ё
+        assert_eq!(
+            shift(Script::Cyrillic, Direction::Encode, "Привет, Яша!"),
+            "Тулезх, Выг!"
+        );
+        assert_eq!(
+            shift(Script::Cyrillic, Direction::Decode, "Тулезх, Выг!"),
+            "Привет, Яша!"
+        );
+        // ω wraps around to γ in the 24-letter alphabet
+        assert_eq!(
+            shift(Script::Greek, Direction::Encode, "Αλφα ωμεγα"),
+            "Δξωδ γοθζδ"
+        );
+        assert_eq!(
+            shift(Script::Greek, Direction::Decode, "Δξωδ γοθζδ"),
+            "Αλφα ωμεγα"
+        );
+    }
+
+    #[test]
+    fn test_keyed_alphabet_with_repeated_letters() {
+        let keyed = |keyword, fill, merge_j| keyed_alphabet(keyword, LATIN_ALPHABET, fill, merge_j);
+        assert_eq!(
+            keyed("BALLOON", KeyFill::Forward, false),
+            "BALONCDEFGHIJKMPQRSTUVWXYZ"
+        );
+        assert_eq!(
+            keyed("Mississippi!", KeyFill::Forward, false),
+            "MISPABCDEFGHJKLNOQRTUVWXYZ"
+        );
+        assert_eq!(
+            keyed("balloon", KeyFill::Reverse, false),
+            "BALONZYXWVUTSRQPMKJIHGFEDC"
+        );
+        assert_eq!(
+            keyed_alphabet(
+                "JINJA",
+                &LATIN_ALPHABET.replace('J', ""),
+                KeyFill::Forward,
+                true
+            ),
+            "INABCDEFGHKLMOPQRSTUVWXYZ"
+        );
+        assert_eq!(keyed("", KeyFill::Forward, false), LATIN_ALPHABET);
+    }
+
+    #[test]
+    fn test_atbash_twice_is_identity() {
+        assert_eq!(AtbashModule.process("Wizard").unwrap(), "Draziw");
+        let text = "The Quick Brown Fox, 42 jumps!";
+        let once = AtbashModule.process(text).unwrap();
+        assert_eq!(AtbashModule.process(&once).unwrap(), text);
+    }
+
+    #[test]
+    fn test_frequency_table() {
+        assert_eq!(
+            frequency_table("Hello, hello! 123"),
+            [('L', 0.4), ('E', 0.2), ('H', 0.2), ('O', 0.2)]
+        );
+        assert!(frequency_table("123 !?").is_empty());
+    }
+
+    #[test]
+    fn test_a1z26_offsets_round_trip() {
+        for (offset, encoded) in [(1, "1-2-26"), (0, "0-1-25")] {
+            let encoder = A1Z26Module {
+                offset,
+                ..Default::default()
+            };
+            assert_eq!(encoder.process("ABZ").unwrap(), encoded);
+            let decoder = A1Z26Module {
+                mode: Direction::Decode,
+                offset,
+            };
+            assert_eq!(decoder.process(encoded).unwrap(), "abz");
+        }
+        let decoder = A1Z26Module {
+            mode: Direction::Decode,
+            offset: 0,
+        };
+        assert_eq!(decoder.process("25-26").unwrap(), "z?");
+        let decoder = A1Z26Module {
+            mode: Direction::Decode,
+            offset: u32::MAX,
+        };
+        assert!(matches!(
+            decoder.process("1"),
+            Err(ProcessError::BadConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_vigenere_autokey_reference() {
+        // Key QUEENLY followed by the plaintext itself
+        let encoder = VigenereCipherModule {
+            key: "QUEENLY".to_string(),
+            autokey: true,
+            ..Default::default()
+        };
+        assert_eq!(encoder.process("ATTACK AT DAWN").unwrap(), "QNXEPV YT WTWP");
+        let decoder = VigenereCipherModule {
+            mode: Direction::Decode,
+            ..encoder
+        };
+        assert_eq!(decoder.process("QNXEPV YT WTWP").unwrap(), "ATTACK AT DAWN");
+    }
+
+    #[test]
+    fn test_substitution_digits_and_pass_through() {
+        let mut module = AlphabeticalSubstitutionModule {
+            plaintext: "abc0123456789".to_string(),
+            ciphertext: "xyz9876543210".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(module.process("Cab 2024").unwrap(), "Zxy 2024");
+        module.map_digits = true;
+        assert_eq!(module.process("Cab 2024").unwrap(), "Zxy 7975");
+
+        // Atbash, except for E and T
+        let module = AlphabeticalSubstitutionModule {
+            pass_through: "eT".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(module.process("Hello, Tom").unwrap(), "Seool, Tln");
+    }
+
+    #[test]
+    fn test_scytale_short_last_row() {
+        // 15 letters over 4 columns leave the last row one short
+        let encoder = ScytaleModule::default();
+        assert_eq!(
+            encoder.process("WEAREDISCOVERED").unwrap(),
+            "WECREDOEAIVDRSE"
+        );
+        let decoder = ScytaleModule {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(
+            decoder.process("WECREDOEAIVDRSE").unwrap(),
+            "WEAREDISCOVERED"
+        );
+        for len in 0..12 {
+            let text: String = ('a'..='z').take(len).collect();
+            for columns in 1..6 {
+                let encoded = ScytaleModule {
+                    columns,
+                    ..Default::default()
+                }
+                .process(&text)
+                .unwrap();
+                let decoder = ScytaleModule {
+                    columns,
+                    mode: Direction::Decode,
+                };
+                assert_eq!(decoder.process(&encoded).unwrap(), text);
+            }
+        }
+        // A column count from an imported recipe may be far beyond any text
+        for mode in [Direction::Encode, Direction::Decode] {
+            let module = ScytaleModule {
+                columns: usize::MAX,
+                mode,
+            };
+            assert_eq!(module.process("").unwrap(), "");
+            assert_eq!(module.process("SCYTALE").unwrap(), "SCYTALE");
+        }
+    }
+
+    #[test]
+    fn test_rot47_twice_is_identity() {
+        assert_eq!(
+            ROT47Module.process("Hello, World!").unwrap(),
+            "w6==@[ (@C=5P"
+        );
+        let text = "The Quick Brown Fox\njumps over 13 lazy dogs! ~{}";
+        let once = ROT47Module.process(text).unwrap();
+        assert_ne!(once, text);
+        assert_eq!(ROT47Module.process(&once).unwrap(), text);
+    }
+
+    #[test]
+    fn test_a1z26_mixed_separators() {
+        let decoder = A1Z26Module {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        for input in ["1 2 3", "1,2,3", "1-2,3", "1\n2\n3", "1, 2; 3"] {
+            assert_eq!(decoder.process(input).unwrap(), "abc", "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_caesar_separate_digit_shift() {
+        let mut module = CaesarCipherModule {
+            shift: 3,
+            shift_digits: true,
+            digit_shift: 5,
+            ..Default::default()
+        };
+        assert_eq!(module.process("AB12").unwrap(), "DE67");
+        assert_eq!(module.process("xyz 789").unwrap(), "abc 234");
+        module.mode = Direction::Decode;
+        assert_eq!(module.process("DE67").unwrap(), "AB12");
+        assert_eq!(module.process("abc 234").unwrap(), "xyz 789");
+
+        // Digits stay put unless enabled
+        module.shift_digits = false;
+        assert_eq!(module.process("DE67").unwrap(), "AB67");
+    }
+
+    #[test]
+    fn test_letter_case_options_on_mixed_case() {
+        let cases = [
+            (LetterCase::Keep, "Rijvs Uyvjn", "Svool Dliow"),
+            (LetterCase::Upper, "RIJVS UYVJN", "SVOOL DLIOW"),
+            (LetterCase::Lower, "rijvs uyvjn", "svool dliow"),
+            // A-Z then a-z for Vigenère; only the lowercase alphabet is given
+            // to the substitution, so capitals pass through
+            (LetterCase::Distinct, "RiJvs uyvJn", "Hvool Wliow"),
+        ];
+        for (case, vigenere, substitution) in cases {
+            let mut module = VigenereCipherModule {
+                case,
+                ..Default::default()
+            };
+            assert_eq!(module.process("Hello World").unwrap(), vigenere);
+            module.mode = Direction::Decode;
+            let decoded = module.process(vigenere).unwrap();
+            assert_eq!(decoded, case.fold("Hello World"));
+
+            let module = AlphabeticalSubstitutionModule {
+                case,
+                ..Default::default()
+            };
+            assert_eq!(module.process("Hello World").unwrap(), substitution);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::module::{Inverse, Module, ProcessError};
+use base64::prelude::*;
+use eframe::egui;
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum CompressionFormat {
+    Gzip,
+    Zlib,
+    Deflate,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum CompressionMode {
+    Compress,
+    Decompress,
+}
+
+/// Text encoding used for the compressed (binary) side
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum BinaryEncoding {
+    Hex,
+    Base64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CompressionModule {
+    format: CompressionFormat,
+    mode: CompressionMode,
+    encoding: BinaryEncoding,
+}
+
+impl CompressionModule {
+    pub fn new(format: CompressionFormat) -> Self {
+        Self {
+            format,
+            mode: CompressionMode::Compress,
+            encoding: BinaryEncoding::Hex,
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self.format {
+            CompressionFormat::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            CompressionFormat::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            CompressionFormat::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self.format {
+            CompressionFormat::Gzip => read_limited(GzDecoder::new(data)),
+            CompressionFormat::Zlib => read_limited(ZlibDecoder::new(data)),
+            CompressionFormat::Deflate => read_limited(DeflateDecoder::new(data)),
+        }
+    }
+}
+
+/// Most bytes decompression produces, so a small compression bomb fails
+/// instead of taking all memory
+const MAX_DECOMPRESSED: u64 = 10_000_000;
+
+/// Everything `reader` yields, or an error once it passes `MAX_DECOMPRESSED`.
+fn read_limited(reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut result = Vec::new();
+    reader.take(MAX_DECOMPRESSED + 1).read_to_end(&mut result)?;
+    if result.len() as u64 > MAX_DECOMPRESSED {
+        return Err(std::io::Error::other("output is larger than 10 MB"));
+    }
+    Ok(result)
+}
+
+impl Module for CompressionModule {
+    fn name(&self) -> &str {
+        match self.format {
+            CompressionFormat::Gzip => "Gzip",
+            CompressionFormat::Zlib => "Zlib",
+            CompressionFormat::Deflate => "Raw Deflate",
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self.format {
+            CompressionFormat::Gzip => "gzip",
+            CompressionFormat::Zlib => "zlib",
+            CompressionFormat::Deflate => "deflate",
+        }
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        match self.mode {
+            CompressionMode::Compress => {
+                let bytes = self.compress(input.as_bytes()).map_err(|e| {
+                    ProcessError::InvalidInput(format!("Compression failed: {}", e))
+                })?;
+                Ok(match self.encoding {
+                    BinaryEncoding::Hex => hex::encode(bytes),
+                    BinaryEncoding::Base64 => BASE64_STANDARD.encode(bytes),
+                })
+            }
+            CompressionMode::Decompress => {
+                let clean: String = input.split_whitespace().collect();
+                if clean.is_empty() {
+                    return Ok(String::new());
+                }
+                let bytes = match self.encoding {
+                    BinaryEncoding::Hex => hex::decode(clean)
+                        .map_err(|_| ProcessError::InvalidInput("Invalid hex input".to_string()))?,
+                    BinaryEncoding::Base64 => BASE64_STANDARD
+                        .decode(clean)
+                        .map_err(|_| ProcessError::InvalidInput("Invalid Base64".to_string()))?,
+                };
+                let data = self.decompress(&bytes).map_err(|e| {
+                    ProcessError::InvalidInput(format!("Decompression failed: {}", e))
+                })?;
+                Ok(String::from_utf8_lossy(&data).to_string())
+            }
+        }
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        let mode = match self.mode {
+            CompressionMode::Compress => CompressionMode::Decompress,
+            CompressionMode::Decompress => CompressionMode::Compress,
+        };
+        Some(Inverse::changed(self, serde_json::json!({ "mode": mode })))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, CompressionMode::Compress, "Compress");
+            ui.radio_value(&mut self.mode, CompressionMode::Decompress, "Decompress");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Compressed data as:");
+            ui.radio_value(&mut self.encoding, BinaryEncoding::Hex, "Hex");
+            ui.radio_value(&mut self.encoding, BinaryEncoding::Base64, "Base64");
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_round_trip() {
+        let input = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+        for format in [
+            CompressionFormat::Gzip,
+            CompressionFormat::Zlib,
+            CompressionFormat::Deflate,
+        ] {
+            for encoding in [BinaryEncoding::Hex, BinaryEncoding::Base64] {
+                let mut module = CompressionModule::new(format);
+                module.encoding = encoding;
+                let compressed = module.process(&input).unwrap();
+                module.mode = CompressionMode::Decompress;
+                assert_eq!(module.process(&compressed).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompression_stops_at_limit() {
+        let module = CompressionModule::new(CompressionFormat::Zlib);
+        let bomb = module
+            .compress(&vec![0; MAX_DECOMPRESSED as usize + 1])
+            .unwrap();
+        assert!(module.decompress(&bomb).is_err());
+        let fits = module
+            .compress(&vec![0; MAX_DECOMPRESSED as usize])
+            .unwrap();
+        assert_eq!(
+            module.decompress(&fits).unwrap().len() as u64,
+            MAX_DECOMPRESSED
+        );
+    }
+}
@@ -1,22 +1,66 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
+use crate::modules::transform::{split_tokens, ByteFormat};
 use base64::prelude::*;
 use data_encoding::BASE32;
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(PartialEq, Clone, Copy)]
-enum Mode {
-    Encode,
-    Decode,
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum LineEnding {
+    Lf,
+    CrLf,
 }
 
+impl LineEnding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Break `text` into lines of at most `width` characters (0 = no wrapping)
+fn wrap_lines(text: &str, width: usize, ending: LineEnding) -> String {
+    if width == 0 {
+        return text.to_string();
+    }
+    text.chars()
+        .collect::<Vec<_>>()
+        .chunks(width)
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(ending.as_str())
+}
+
+/// Text handed to a Base64/Base32 decoder: all whitespace removed, or just trimmed
+fn decode_input(input: &str, ignore_whitespace: bool) -> String {
+    if ignore_whitespace {
+        input.chars().filter(|c| !c.is_whitespace()).collect()
+    } else {
+        input.trim().to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Base64Module {
-    mode: Mode,
+    mode: Direction,
+    /// Wrap encoded output at this many columns (0 = single line)
+    wrap: usize,
+    line_ending: LineEnding,
+    /// Drop all whitespace (line breaks, spaces, tabs) before decoding
+    ignore_whitespace: bool,
 }
 
 impl Default for Base64Module {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+            wrap: 0,
+            line_ending: LineEnding::Lf,
+            ignore_whitespace: true,
+        }
     }
 }
 
@@ -25,21 +69,138 @@ impl Module for Base64Module {
         "Base64"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "base64"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         match self.mode {
-            Mode::Encode => BASE64_STANDARD.encode(input),
-            Mode::Decode => match BASE64_STANDARD.decode(input.trim()) {
-                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-                Err(_) => "Invalid Base64".to_string(),
-            },
+            Direction::Encode => Ok(wrap_lines(
+                &BASE64_STANDARD.encode(input),
+                self.wrap,
+                self.line_ending,
+            )),
+            Direction::Decode => {
+                let bytes = BASE64_STANDARD
+                    .decode(decode_input(input, self.ignore_whitespace))
+                    .map_err(|e| ProcessError::InvalidInput(format!("Invalid Base64: {}", e)))?;
+                Ok(String::from_utf8_lossy(&bytes).to_string())
+            }
+        }
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        if self.mode == Direction::Encode {
+            ui.horizontal(|ui| {
+                ui.label("Wrap at column:");
+                ui.add(egui::DragValue::new(&mut self.wrap).range(0..=1000));
+                if ui.button("MIME (76)").clicked() {
+                    self.wrap = 76;
+                }
+                if ui.button("PEM (64)").clicked() {
+                    self.wrap = 64;
+                }
+            });
+            if self.wrap > 0 {
+                ui.horizontal(|ui| {
+                    ui.label("Line ending:");
+                    ui.radio_value(&mut self.line_ending, LineEnding::Lf, "LF");
+                    ui.radio_value(&mut self.line_ending, LineEnding::CrLf, "CRLF");
+                });
+            }
+        } else {
+            ui.checkbox(&mut self.ignore_whitespace, "Ignore whitespace")
+                .on_hover_text("Accept wrapped or spaced-out input");
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+// PEM Module
+#[derive(Serialize, Deserialize)]
+pub struct PemModule {
+    mode: Direction,
+    label: String,
+    /// How the binary side (the content inside the armour) is read/shown
+    data: ByteFormat,
+}
+
+impl Default for PemModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+            label: String::from("CERTIFICATE"),
+            data: ByteFormat::Raw,
         }
     }
+}
+
+impl Module for PemModule {
+    fn name(&self) -> &str {
+        "PEM"
+    }
+
+    fn id(&self) -> &str {
+        "pem"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        if input.trim().is_empty() {
+            return Ok(String::new());
+        }
+        match self.mode {
+            Direction::Encode => {
+                let bytes = self.data.parse(input).map_err(ProcessError::InvalidInput)?;
+                Ok(pem_wrap(&bytes, self.label.trim()))
+            }
+            Direction::Decode => {
+                let (_, bytes) = pem_unwrap(input).map_err(ProcessError::InvalidInput)?;
+                Ok(self.data.format(&bytes))
+            }
+        }
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Wrap");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Unwrap");
         });
+        if self.mode == Direction::Encode {
+            ui.horizontal(|ui| {
+                ui.label("Label:");
+                ui.text_edit_singleline(&mut self.label);
+            });
+        }
+        let label = match self.mode {
+            Direction::Encode => "Input:",
+            Direction::Decode => "Output:",
+        };
+        self.data.ui(ui, label);
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -51,14 +212,64 @@ impl Module for Base64Module {
     }
 }
 
+fn pem_wrap(data: &[u8], label: &str) -> String {
+    format!(
+        "-----BEGIN {label}-----\n{}\n-----END {label}-----",
+        wrap_lines(&BASE64_STANDARD.encode(data), 64, LineEnding::Lf)
+    )
+}
+
+/// Parse the first PEM block in `input`, returning its label and decoded content.
+fn pem_unwrap(input: &str) -> Result<(String, Vec<u8>), String> {
+    let mut lines = input.lines().map(str::trim);
+    let label = lines
+        .find_map(|line| {
+            line.strip_prefix("-----BEGIN ")
+                .and_then(|rest| rest.strip_suffix("-----"))
+        })
+        .ok_or("missing -----BEGIN ...----- line")?
+        .to_string();
+
+    let mut body = String::new();
+    for line in lines.by_ref() {
+        if let Some(end) = line
+            .strip_prefix("-----END ")
+            .and_then(|rest| rest.strip_suffix("-----"))
+        {
+            if end != label {
+                return Err(format!("BEGIN {} does not match END {}", label, end));
+            }
+            let bytes = BASE64_STANDARD
+                .decode(&body)
+                .map_err(|_| "invalid Base64 inside PEM block".to_string())?;
+            return Ok((label, bytes));
+        }
+        if line.starts_with("-----BEGIN ") {
+            return Err(format!("BEGIN {} has no matching END line", label));
+        }
+        // Skip RFC 1421 style headers such as "Proc-Type: 4,ENCRYPTED"
+        if line.contains(':') {
+            continue;
+        }
+        body.push_str(line);
+    }
+    Err(format!("missing -----END {}----- line", label))
+}
+
 // Base32 Module
+#[derive(Serialize, Deserialize)]
 pub struct Base32Module {
-    mode: Mode,
+    mode: Direction,
+    /// Drop all whitespace before decoding
+    ignore_whitespace: bool,
 }
 
 impl Default for Base32Module {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+            ignore_whitespace: true,
+        }
     }
 }
 
@@ -67,21 +278,38 @@ impl Module for Base32Module {
         "Base32"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "base32"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         match self.mode {
-            Mode::Encode => BASE32.encode(input.as_bytes()),
-            Mode::Decode => match BASE32.decode(input.trim().as_bytes()) {
-                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-                Err(_) => "Invalid Base32".to_string(),
-            },
+            Direction::Encode => Ok(BASE32.encode(input.as_bytes())),
+            Direction::Decode => {
+                let bytes = BASE32
+                    .decode(decode_input(input, self.ignore_whitespace).as_bytes())
+                    .map_err(|e| ProcessError::InvalidInput(format!("Invalid Base32: {}", e)))?;
+                Ok(String::from_utf8_lossy(&bytes).to_string())
+            }
         }
     }
 
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
+        if self.mode == Direction::Decode {
+            ui.checkbox(&mut self.ignore_whitespace, "Ignore whitespace")
+                .on_hover_text("Accept wrapped or spaced-out input");
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -93,14 +321,44 @@ impl Module for Base32Module {
     }
 }
 
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Base85Variant {
+    /// Classic Ascii85 (characters `!` to `u`), see `Ascii85Style`
+    Ascii85,
+    /// RFC 1924 alphabet (0-9, A-Z, a-z, then punctuation)
+    Rfc1924,
+}
+
+/// Tool conventions for classic Ascii85
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Ascii85Style {
+    /// PostScript/PDF: `<~ ~>` framing, `z` for an all-zero group
+    Adobe,
+    /// btoa: no framing, `z` for all zeros and `y` for four spaces
+    Btoa,
+}
+
 // Ascii85 Module
+#[derive(Serialize, Deserialize)]
 pub struct Ascii85Module {
-    mode: Mode,
+    mode: Direction,
+    variant: Base85Variant,
+    style: Ascii85Style,
+    /// Emit `z` for all-zero groups when encoding (decoding always accepts it)
+    zero_shortcut: bool,
+    /// RFC 1924 only: treat the data as an IPv6 address (16 bytes <-> 20 chars)
+    ipv6_address: bool,
 }
 
 impl Default for Ascii85Module {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+            variant: Base85Variant::Ascii85,
+            style: Ascii85Style::Adobe,
+            zero_shortcut: true,
+            ipv6_address: false,
+        }
     }
 }
 
@@ -109,21 +367,78 @@ impl Module for Ascii85Module {
         "Ascii85"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            Mode::Encode => encode_ascii85(input.as_bytes()),
-            Mode::Decode => match decode_ascii85(input.trim()) {
-                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-                Err(_) => "Invalid Ascii85".to_string(),
-            },
+    fn id(&self) -> &str {
+        "ascii85"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        if input.is_empty() {
+            return Ok(String::new());
         }
+        Ok(match (self.variant, self.ipv6_address, self.mode) {
+            (Base85Variant::Ascii85, _, Direction::Encode) => {
+                encode_ascii85(input.as_bytes(), self.style, self.zero_shortcut)
+            }
+            (Base85Variant::Ascii85, _, Direction::Decode) => {
+                let bytes = decode_ascii85(input, self.style)
+                    .map_err(|e| ProcessError::InvalidInput(format!("Invalid Ascii85: {}", e)))?;
+                String::from_utf8_lossy(&bytes).to_string()
+            }
+            (Base85Variant::Rfc1924, false, Direction::Encode) => encode_rfc1924(input.as_bytes()),
+            (Base85Variant::Rfc1924, false, Direction::Decode) => {
+                let bytes = decode_rfc1924(input.trim()).map_err(ProcessError::InvalidInput)?;
+                String::from_utf8_lossy(&bytes).to_string()
+            }
+            (Base85Variant::Rfc1924, true, Direction::Encode) => {
+                let addr = input
+                    .trim()
+                    .parse::<std::net::Ipv6Addr>()
+                    .map_err(|_| ProcessError::InvalidInput("Invalid IPv6 address".to_string()))?;
+                encode_rfc1924_address(addr)
+            }
+            (Base85Variant::Rfc1924, true, Direction::Decode) => {
+                decode_rfc1924_address(input.trim())
+                    .map_err(ProcessError::InvalidInput)?
+                    .to_string()
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Variant:");
+            ui.radio_value(&mut self.variant, Base85Variant::Ascii85, "Ascii85");
+            ui.radio_value(&mut self.variant, Base85Variant::Rfc1924, "RFC 1924");
         });
+        if self.variant == Base85Variant::Ascii85 {
+            ui.horizontal(|ui| {
+                ui.label("Convention:");
+                ui.radio_value(&mut self.style, Ascii85Style::Adobe, "Adobe <~ ~>")
+                    .on_hover_text("Framed with <~ ~>; z = four zero bytes");
+                ui.radio_value(&mut self.style, Ascii85Style::Btoa, "btoa")
+                    .on_hover_text("No framing; z = four zero bytes, y = four spaces");
+            });
+            if self.mode == Direction::Encode {
+                ui.checkbox(&mut self.zero_shortcut, "Use z for all-zero groups");
+            }
+        }
+        if self.variant == Base85Variant::Rfc1924 {
+            ui.checkbox(
+                &mut self.ipv6_address,
+                "IPv6 address (16 bytes <-> 20 characters)",
+            );
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -136,97 +451,189 @@ impl Module for Ascii85Module {
 }
 
 // Ascii85 encoding helper functions
-fn encode_ascii85(data: &[u8]) -> String {
-    let mut result = String::from("<~");
-    let mut i = 0;
-
-    while i < data.len() {
-        let mut value: u32 = 0;
-        let mut count = 0;
+fn encode_ascii85(data: &[u8], style: Ascii85Style, zero_shortcut: bool) -> String {
+    let mut result = String::new();
+    if style == Ascii85Style::Adobe {
+        result.push_str("<~");
+    }
 
-        for j in 0..4 {
-            value = value << 8;
-            if i + j < data.len() {
-                value |= data[i + j] as u32;
-                count += 1;
-            }
-        }
+    for chunk in data.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
 
-        if count == 4 && value == 0 {
+        // Shortcuts only apply to complete groups
+        if chunk.len() == 4 && value == 0 && zero_shortcut {
             result.push('z');
+        } else if chunk.len() == 4 && value == 0x20202020 && style == Ascii85Style::Btoa {
+            result.push('y');
         } else {
             let mut encoded = [0u8; 5];
-            for j in (0..5).rev() {
-                encoded[j] = (value % 85) as u8 + 33;
+            for digit in encoded.iter_mut().rev() {
+                *digit = (value % 85) as u8 + 33;
                 value /= 85;
             }
-
-            for j in 0..=count {
-                result.push(encoded[j] as char);
+            for &b in encoded.iter().take(chunk.len() + 1) {
+                result.push(b as char);
             }
         }
-
-        i += 4;
     }
 
-    result.push_str("~>");
+    if style == Ascii85Style::Adobe {
+        result.push_str("~>");
+    }
     result
 }
 
-fn decode_ascii85(data: &str) -> Result<Vec<u8>, String> {
-    let data = data.trim_start_matches("<~").trim_end_matches("~>");
+fn decode_ascii85(data: &str, style: Ascii85Style) -> Result<Vec<u8>, String> {
+    let data = data.trim();
+    let data = if style == Ascii85Style::Adobe {
+        let data = data.strip_prefix("<~").unwrap_or(data);
+        data.strip_suffix("~>").unwrap_or(data)
+    } else {
+        data
+    };
     let mut result = Vec::new();
-    let mut chars = data.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut group = Vec::with_capacity(5);
+
+    for c in data.chars().filter(|c| !c.is_whitespace()) {
+        let shortcut = match c {
+            'z' => Some([0u8; 4]),
+            'y' if style == Ascii85Style::Btoa => Some([0x20u8; 4]),
+            _ => None,
+        };
+        if let Some(bytes) = shortcut {
+            if !group.is_empty() {
+                return Err(format!("'{}' inside a group", c));
+            }
+            result.extend_from_slice(&bytes);
+            continue;
+        }
+        if !('!'..='u').contains(&c) {
+            return Err(format!("Invalid character '{}'", c));
+        }
+        group.push(c as u32 - 33);
+        if group.len() == 5 {
+            result.extend_from_slice(&ascii85_group(&group)?);
+            group.clear();
+        }
+    }
 
-    while chars.peek().is_some() {
-        let mut value: u32 = 0;
-        let mut count = 0;
-
-        for _ in 0..5 {
-            if let Some(c) = chars.next() {
-                if c == 'z' {
-                    if count == 0 {
-                        result.extend_from_slice(&[0, 0, 0, 0]);
-                        break;
-                    } else {
-                        return Err("Invalid z placement".to_string());
-                    }
-                }
+    match group.len() {
+        0 => {}
+        1 => return Err("Truncated final group".to_string()),
+        n => {
+            // Pad with 'u' and drop the padding bytes again
+            group.resize(5, 84);
+            result.extend_from_slice(&ascii85_group(&group)?[..n - 1]);
+        }
+    }
 
-                if c < '!' || c > 'u' {
-                    return Err("Invalid character".to_string());
-                }
+    Ok(result)
+}
 
-                value = value * 85 + (c as u32 - 33);
-                count += 1;
-            } else {
-                break;
-            }
-        }
+/// Combine five base-85 digits into four bytes, rejecting groups above 2^32 - 1.
+fn ascii85_group(digits: &[u32]) -> Result<[u8; 4], String> {
+    digits
+        .iter()
+        .try_fold(0u32, |acc, &d| acc.checked_mul(85)?.checked_add(d))
+        .map(u32::to_be_bytes)
+        .ok_or_else(|| "Group value out of range".to_string())
+}
 
-        if count > 0 {
-            for _ in count..5 {
-                value = value * 85 + 84;
-            }
+// RFC 1924 Base85 helper functions
+const RFC1924_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
 
-            let bytes = value.to_be_bytes();
-            for i in 0..(count - 1) {
-                result.push(bytes[i]);
-            }
+fn rfc1924_digit(c: char) -> Option<u32> {
+    RFC1924_ALPHABET
+        .iter()
+        .position(|&b| b as char == c)
+        .map(|i| i as u32)
+}
+
+/// Encode bytes in 4-byte groups; a partial final group is zero-padded
+/// and the output trimmed by the same number of characters.
+fn encode_rfc1924(data: &[u8]) -> String {
+    let mut result = String::new();
+    for chunk in data.chunks(4) {
+        let mut group = [0u8; 4];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(group);
+
+        let mut encoded = [0u8; 5];
+        for slot in encoded.iter_mut().rev() {
+            *slot = RFC1924_ALPHABET[(value % 85) as usize];
+            value /= 85;
+        }
+        for &b in encoded.iter().take(chunk.len() + 1) {
+            result.push(b as char);
         }
     }
+    result
+}
 
+fn decode_rfc1924(data: &str) -> Result<Vec<u8>, String> {
+    let chars: Vec<char> = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut result = Vec::new();
+    for chunk in chars.chunks(5) {
+        if chunk.len() == 1 {
+            return Err("Invalid RFC 1924 length".to_string());
+        }
+        let mut value: u32 = 0;
+        for i in 0..5 {
+            // Pad a partial final group with the highest digit
+            let digit = match chunk.get(i) {
+                Some(&c) => rfc1924_digit(c).ok_or("Invalid RFC 1924 character")?,
+                None => 84,
+            };
+            value = value
+                .checked_mul(85)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or("RFC 1924 group out of range")?;
+        }
+        result.extend_from_slice(&value.to_be_bytes()[..chunk.len() - 1]);
+    }
     Ok(result)
 }
 
+/// Encode an IPv6 address as a single 128-bit number in 20 base-85 digits
+fn encode_rfc1924_address(addr: std::net::Ipv6Addr) -> String {
+    let mut value = u128::from(addr);
+    let mut encoded = [0u8; 20];
+    for slot in encoded.iter_mut().rev() {
+        *slot = RFC1924_ALPHABET[(value % 85) as usize];
+        value /= 85;
+    }
+    encoded.iter().map(|&b| b as char).collect()
+}
+
+fn decode_rfc1924_address(data: &str) -> Result<std::net::Ipv6Addr, String> {
+    if data.chars().count() != 20 {
+        return Err("An RFC 1924 address must be exactly 20 characters".to_string());
+    }
+    let mut value: u128 = 0;
+    for c in data.chars() {
+        let digit = rfc1924_digit(c).ok_or("Invalid RFC 1924 character")?;
+        value = value
+            .checked_mul(85)
+            .and_then(|v| v.checked_add(digit as u128))
+            .ok_or("RFC 1924 address out of range")?;
+    }
+    Ok(std::net::Ipv6Addr::from(value))
+}
+
 // Baudot Code Module
+#[derive(Serialize, Deserialize)]
 pub struct BaudotCodeModule {
-    mode: Mode,
+    mode: Direction,
 }
 
 impl Default for BaudotCodeModule {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+        }
     }
 }
 
@@ -235,17 +642,28 @@ impl Module for BaudotCodeModule {
         "Baudot Code"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            Mode::Encode => encode_baudot(input),
-            Mode::Decode => decode_baudot(input),
-        }
+    fn id(&self) -> &str {
+        "baudot"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => encode_baudot(input),
+            Direction::Decode => decode_baudot(input),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
     }
 
@@ -387,20 +805,15 @@ fn decode_baudot(input: &str) -> String {
     result
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum UnicodeMode {
-    Encode,
-    Decode,
-}
-
+#[derive(Serialize, Deserialize)]
 pub struct UnicodeCodePointsModule {
-    mode: UnicodeMode,
+    mode: Direction,
 }
 
 impl Default for UnicodeCodePointsModule {
     fn default() -> Self {
         Self {
-            mode: UnicodeMode::Encode,
+            mode: Direction::Encode,
         }
     }
 }
@@ -410,13 +823,17 @@ impl Module for UnicodeCodePointsModule {
         "Unicode Code Points"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            UnicodeMode::Encode => input
+    fn id(&self) -> &str {
+        "unicode"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => input
                 .chars()
                 .map(|c| format!("U+{:04X} ", c as u32))
                 .collect(),
-            UnicodeMode::Decode => {
+            Direction::Decode => {
                 let mut result = String::new();
                 for part in input.split_whitespace() {
                     let hex_part = part.trim_start_matches("U+").trim_start_matches("u+");
@@ -428,13 +845,20 @@ impl Module for UnicodeCodePointsModule {
                 }
                 result
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, UnicodeMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, UnicodeMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
     }
 
@@ -447,71 +871,262 @@ impl Module for UnicodeCodePointsModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum UrlMode {
-    Encode,
-    Decode,
+/// Text as the bits of its UTF-8 bytes, one fixed-width group per byte.
+#[derive(Serialize, Deserialize)]
+pub struct BinaryTextModule {
+    mode: Direction,
+    separator: String,
+    /// Digits per byte; narrower than 8 only fits ASCII
+    width: usize,
 }
 
+impl Default for BinaryTextModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+            separator: " ".to_string(),
+            width: 8,
+        }
+    }
+}
+
+impl Module for BinaryTextModule {
+    fn name(&self) -> &str {
+        "Binary Text"
+    }
+
+    fn id(&self) -> &str {
+        "binary_text"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let width = self.width.clamp(1, 16);
+        match self.mode {
+            Direction::Encode => {
+                let mut groups = Vec::new();
+                for b in input.bytes() {
+                    if width < 8 && b >> width != 0 {
+                        return Err(ProcessError::InvalidInput(format!(
+                            "Byte 0x{:02X} does not fit in {} bits",
+                            b, width
+                        )));
+                    }
+                    groups.push(format!("{:0width$b}", b, width = width));
+                }
+                Ok(groups.join(&self.separator))
+            }
+            Direction::Decode => {
+                // Groups may be split by the separator or any other, or run
+                // together; anything longer than one group is cut into groups
+                let text = match self.separator.trim() {
+                    "" => input.to_string(),
+                    sep => input.replace(sep, " "),
+                };
+                let mut bytes = Vec::new();
+                for token in split_tokens(&text) {
+                    if token.chars().any(|c| c != '0' && c != '1') {
+                        return Err(ProcessError::InvalidInput(format!(
+                            "\"{}\" is not binary",
+                            token
+                        )));
+                    }
+                    if token.len() > width && !token.len().is_multiple_of(width) {
+                        return Err(ProcessError::InvalidInput(format!(
+                            "\"{}\" is not a whole number of {}-bit groups",
+                            token, width
+                        )));
+                    }
+                    for group in token.as_bytes().chunks(width) {
+                        let value = group
+                            .iter()
+                            .fold(0u32, |acc, &c| (acc << 1) | (c == b'1') as u32);
+                        let byte = u8::try_from(value).map_err(|_| {
+                            ProcessError::InvalidInput(format!("{} is too large for a byte", value))
+                        })?;
+                        bytes.push(byte);
+                    }
+                }
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+        }
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Separator:");
+            ui.add(egui::TextEdit::singleline(&mut self.separator).desired_width(40.0))
+                .on_hover_text("Put between groups; decoding also accepts other separators");
+            ui.label("Bits per byte:");
+            ui.add(egui::DragValue::new(&mut self.width).range(1..=16));
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct UrlEncodingModule {
-    mode: UrlMode,
+    mode: Direction,
+    /// URL and query parameter the encoded output is sent with, for the
+    /// request snippets
+    base_url: String,
+    param: String,
 }
 
 impl Default for UrlEncodingModule {
     fn default() -> Self {
         Self {
-            mode: UrlMode::Encode,
+            mode: Direction::Encode,
+            base_url: "https://example.com/search".to_string(),
+            param: "q".to_string(),
         }
     }
 }
 
+/// Quote `text` as a single shell argument
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+impl UrlEncodingModule {
+    /// `param=encoded`, for a query string or form body
+    fn query_pair(&self, encoded: &str) -> String {
+        format!("{}={}", self.param, encoded)
+    }
+
+    /// The base URL with the encoded value added as a query parameter
+    fn request_url(&self, encoded: &str) -> String {
+        let separator = if self.base_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        format!("{}{}{}", self.base_url, separator, self.query_pair(encoded))
+    }
+
+    fn curl_get(&self, encoded: &str) -> String {
+        format!("curl {}", shell_quote(&self.request_url(encoded)))
+    }
+
+    fn curl_post(&self, encoded: &str) -> String {
+        format!(
+            "curl -d {} {}",
+            shell_quote(&self.query_pair(encoded)),
+            shell_quote(&self.base_url)
+        )
+    }
+}
+
 impl Module for UrlEncodingModule {
     fn name(&self) -> &str {
         "URL Encoding"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            UrlMode::Encode => input
-                .chars()
-                .map(|c| {
-                    if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '~' {
-                        c.to_string()
+    fn id(&self) -> &str {
+        "url"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => input
+                .bytes()
+                .map(|b| {
+                    if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+                        (b as char).to_string()
                     } else {
-                        format!("%{:02X}", c as u8)
+                        format!("%{:02X}", b)
                     }
                 })
                 .collect(),
-            UrlMode::Decode => {
-                let mut result = String::new();
+            Direction::Decode => {
+                // Collect bytes so multi-byte UTF-8 escapes reassemble
+                let mut result = Vec::new();
                 let mut chars = input.chars().peekable();
                 while let Some(c) = chars.next() {
                     if c == '%' {
                         let hex: String = chars.by_ref().take(2).collect();
                         if let Ok(byte) = u8::from_str_radix(&hex, 16) {
-                            result.push(byte as char);
+                            result.push(byte);
                         } else {
-                            result.push('%');
-                            result.push_str(&hex);
+                            result.push(b'%');
+                            result.extend_from_slice(hex.as_bytes());
                         }
                     } else if c == '+' {
-                        result.push(' ');
+                        result.push(b' ');
                     } else {
-                        result.push(c);
+                        let mut buf = [0; 4];
+                        result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
                     }
                 }
-                result
+                String::from_utf8_lossy(&result).to_string()
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, UrlMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, UrlMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
     }
 
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, _input: &str, output: &str) {
+        if self.mode != Direction::Encode || output.is_empty() {
+            return;
+        }
+        egui::CollapsingHeader::new("Use in a request")
+            .id_salt("url_request")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Base URL:");
+                    ui.text_edit_singleline(&mut self.base_url);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Parameter:");
+                    ui.text_edit_singleline(&mut self.param);
+                });
+                let snippets = [
+                    ("Query string", self.query_pair(output)),
+                    ("URL", self.request_url(output)),
+                    ("curl (GET)", self.curl_get(output)),
+                    ("curl (POST form)", self.curl_post(output)),
+                ];
+                for (label, snippet) in snippets {
+                    ui.horizontal(|ui| {
+                        if ui.button("📋").on_hover_text("Copy to clipboard").clicked() {
+                            ui.output_mut(|o| o.copied_text = snippet.clone());
+                        }
+                        ui.label(format!("{}:", label));
+                        ui.monospace(&snippet);
+                    });
+                }
+            });
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -522,13 +1137,16 @@ impl Module for UrlEncodingModule {
 }
 
 // Punycode Module
+#[derive(Serialize, Deserialize)]
 pub struct PunycodeModule {
-    mode: Mode,
+    mode: Direction,
 }
 
 impl Default for PunycodeModule {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+        }
     }
 }
 
@@ -537,23 +1155,32 @@ impl Module for PunycodeModule {
         "Punycode"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "punycode"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         match self.mode {
-            Mode::Encode => match idna::domain_to_ascii(input) {
-                Ok(encoded) => encoded,
-                Err(_) => "Invalid domain".to_string(),
-            },
-            Mode::Decode => match idna::domain_to_unicode(input) {
-                (decoded, Ok(())) => decoded,
-                (_, Err(_)) => "Invalid punycode".to_string(),
+            Direction::Encode => idna::domain_to_ascii(input)
+                .map_err(|_| ProcessError::InvalidInput("Invalid domain".to_string())),
+            Direction::Decode => match idna::domain_to_unicode(input) {
+                (decoded, Ok(())) => Ok(decoded),
+                (_, Err(_)) => Err(ProcessError::InvalidInput("Invalid punycode".to_string())),
             },
         }
     }
 
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
     }
 
@@ -567,13 +1194,16 @@ impl Module for PunycodeModule {
 }
 
 // Bootstring Module (simplified implementation)
+#[derive(Serialize, Deserialize)]
 pub struct BootstringModule {
-    mode: Mode,
+    mode: Direction,
 }
 
 impl Default for BootstringModule {
     fn default() -> Self {
-        Self { mode: Mode::Encode }
+        Self {
+            mode: Direction::Encode,
+        }
     }
 }
 
@@ -582,9 +1212,13 @@ impl Module for BootstringModule {
         "Bootstring"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            Mode::Encode => {
+    fn id(&self) -> &str {
+        "bootstring"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => {
                 // Simplified bootstring: just show which chars are ASCII vs non-ASCII
                 let ascii_part: String = input.chars().filter(|c| c.is_ascii()).collect();
                 let non_ascii: Vec<char> = input.chars().filter(|c| !c.is_ascii()).collect();
@@ -603,7 +1237,7 @@ impl Module for BootstringModule {
                     )
                 }
             }
-            Mode::Decode => {
+            Direction::Decode => {
                 // Simplified decode
                 if let Some(dash_pos) = input.rfind('-') {
                     let ascii_part = &input[..dash_pos];
@@ -622,13 +1256,20 @@ impl Module for BootstringModule {
                     input.to_string()
                 }
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, Mode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, Mode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.label("Note: Simplified Bootstring implementation");
     }
@@ -642,34 +1283,159 @@ impl Module for BootstringModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 enum IntegerMode {
     ToDecimal,
     ToHex,
+    /// Read the bytes as multi-byte integers
+    BytesToWords,
+    /// Write decimal integers as bytes
+    WordsToBytes,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct IntegerModule {
     mode: IntegerMode,
+    /// Integer width in bytes (1, 2, 4 or 8)
+    width: usize,
+    little_endian: bool,
+    signed: bool,
+    /// How the bytes are written, for the multi-byte integer modes
+    bytes: ByteFormat,
 }
 
 impl Default for IntegerModule {
     fn default() -> Self {
         Self {
             mode: IntegerMode::ToDecimal,
+            width: 4,
+            little_endian: false,
+            signed: false,
+            bytes: ByteFormat::Hex,
         }
     }
 }
 
+impl IntegerModule {
+    /// Describes the configured integer type, e.g. "unsigned 32-bit little-endian".
+    fn type_name(&self) -> String {
+        format!(
+            "{} {}-bit{}",
+            if self.signed { "signed" } else { "unsigned" },
+            self.width * 8,
+            match (self.width, self.little_endian) {
+                (1, _) => "",
+                (_, true) => " little-endian",
+                (_, false) => " big-endian",
+            }
+        )
+    }
+
+    /// Decimal values of each whole word of `bytes`; trailing bytes that do
+    /// not fill a word are ignored.
+    fn read_words(&self, bytes: &[u8]) -> Vec<String> {
+        bytes
+            .chunks_exact(self.width)
+            .map(|word| {
+                let mut value: u64 = 0;
+                for i in 0..self.width {
+                    let byte = if self.little_endian {
+                        word[self.width - 1 - i]
+                    } else {
+                        word[i]
+                    };
+                    value = (value << 8) | byte as u64;
+                }
+                if self.signed {
+                    // Sign-extend from the word's top bit
+                    let unused = 64 - self.width * 8;
+                    (((value << unused) as i64) >> unused).to_string()
+                } else {
+                    value.to_string()
+                }
+            })
+            .collect()
+    }
+
+    fn write_words(&self, input: &str) -> Result<Vec<u8>, String> {
+        let bits = self.width as u32 * 8;
+        let (min, max) = if self.signed {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        };
+        let mut bytes = Vec::new();
+        for token in input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            let value: i128 = token
+                .parse()
+                .map_err(|_| format!("'{}' is not an integer", token))?;
+            if value < min || value > max {
+                return Err(format!(
+                    "{} does not fit in a {} integer",
+                    value,
+                    self.type_name()
+                ));
+            }
+            // Two's complement: the low bytes of the value
+            let be = (value as u64).to_be_bytes();
+            let mut word = be[8 - self.width..].to_vec();
+            if self.little_endian {
+                word.reverse();
+            }
+            bytes.extend(word);
+        }
+        Ok(bytes)
+    }
+}
+
 impl Module for IntegerModule {
     fn name(&self) -> &str {
         "Integer"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
+    fn id(&self) -> &str {
+        "integer"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
             IntegerMode::ToDecimal => input.bytes().map(|b| format!("{} ", b)).collect(),
             IntegerMode::ToHex => input.bytes().map(|b| format!("{:02X} ", b)).collect(),
-        }
+            IntegerMode::BytesToWords | IntegerMode::WordsToBytes
+                if !(1..=8).contains(&self.width) =>
+            {
+                return Err(ProcessError::BadConfig(format!(
+                    "Width must be 1 to 8 bytes, got {}",
+                    self.width
+                )));
+            }
+            IntegerMode::BytesToWords => {
+                let bytes = self
+                    .bytes
+                    .parse(input)
+                    .map_err(ProcessError::InvalidInput)?;
+                self.read_words(&bytes).join(" ")
+            }
+            IntegerMode::WordsToBytes => {
+                let bytes = self
+                    .write_words(input)
+                    .map_err(ProcessError::InvalidInput)?;
+                self.bytes.format(&bytes)
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        let mode = match self.mode {
+            IntegerMode::BytesToWords => IntegerMode::WordsToBytes,
+            IntegerMode::WordsToBytes => IntegerMode::BytesToWords,
+            // Nothing reads the byte listings back
+            IntegerMode::ToDecimal | IntegerMode::ToHex => return None,
+        };
+        Some(Inverse::changed(self, serde_json::json!({ "mode": mode })))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -677,6 +1443,58 @@ impl Module for IntegerModule {
             ui.radio_value(&mut self.mode, IntegerMode::ToDecimal, "To Decimal");
             ui.radio_value(&mut self.mode, IntegerMode::ToHex, "To Hex");
         });
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut self.mode,
+                IntegerMode::BytesToWords,
+                "Bytes → integers",
+            );
+            ui.radio_value(
+                &mut self.mode,
+                IntegerMode::WordsToBytes,
+                "Integers → bytes",
+            );
+        });
+        if matches!(
+            self.mode,
+            IntegerMode::BytesToWords | IntegerMode::WordsToBytes
+        ) {
+            ui.horizontal(|ui| {
+                ui.label("Width:");
+                for width in [1, 2, 4, 8] {
+                    ui.radio_value(&mut self.width, width, format!("{}-bit", width * 8));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.little_endian, false, "Big-endian");
+                ui.radio_value(&mut self.little_endian, true, "Little-endian");
+                ui.checkbox(&mut self.signed, "Signed");
+            });
+            let label = if self.mode == IntegerMode::BytesToWords {
+                "Input:"
+            } else {
+                "Output:"
+            };
+            self.bytes.ui(ui, label);
+        }
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        if self.mode != IntegerMode::BytesToWords {
+            return;
+        }
+        if let Ok(bytes) = self.bytes.parse(input) {
+            let trailing = bytes.len() % self.width;
+            if trailing > 0 {
+                ui.colored_label(
+                    egui::Color32::from_rgb(220, 160, 0),
+                    format!(
+                        "⚠ The last {} byte(s) do not fill a {}-byte word and were ignored",
+                        trailing, self.width
+                    ),
+                );
+            }
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -687,3 +1505,672 @@ impl Module for IntegerModule {
         self
     }
 }
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum RleFormat {
+    /// Hex-encoded (count, byte) pairs; runs longer than 255 are split
+    Bytes,
+    /// Decimal count followed by the character, e.g. "3A2B"
+    Text,
+}
+
+// Run-Length Encoding Module
+#[derive(Serialize, Deserialize)]
+pub struct RleModule {
+    mode: Direction,
+    format: RleFormat,
+}
+
+impl Default for RleModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+            format: RleFormat::Text,
+        }
+    }
+}
+
+impl Module for RleModule {
+    fn name(&self) -> &str {
+        "Run-Length Encoding"
+    }
+
+    fn id(&self) -> &str {
+        "rle"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match (self.mode, self.format) {
+            (Direction::Encode, RleFormat::Bytes) => {
+                hex::encode(rle_encode_bytes(input.as_bytes()))
+            }
+            (Direction::Decode, RleFormat::Bytes) => {
+                let clean: String = input.split_whitespace().collect();
+                let bytes = hex::decode(clean)
+                    .map_err(|_| ProcessError::InvalidInput("Invalid hex input".to_string()))?;
+                let decoded = rle_decode_bytes(&bytes).map_err(ProcessError::InvalidInput)?;
+                String::from_utf8_lossy(&decoded).to_string()
+            }
+            (Direction::Encode, RleFormat::Text) => rle_encode_text(input),
+            (Direction::Decode, RleFormat::Text) => {
+                rle_decode_text(input).map_err(ProcessError::InvalidInput)?
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Format:");
+            ui.radio_value(&mut self.format, RleFormat::Text, "Text (3A2B)");
+            ui.radio_value(&mut self.format, RleFormat::Bytes, "Count/byte pairs (hex)");
+        });
+        match self.format {
+            RleFormat::Text => {
+                ui.label("Digits and '\\' in the data are escaped with a leading '\\'")
+            }
+            RleFormat::Bytes => ui.label("Runs longer than 255 are split into several pairs"),
+        };
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+// Run-length encoding helper functions
+fn rle_encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::new();
+    for run in data.chunk_by(|a, b| a == b) {
+        for part in run.chunks(255) {
+            result.push(part.len() as u8);
+            result.push(part[0]);
+        }
+    }
+    result
+}
+
+fn rle_decode_bytes(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !data.len().is_multiple_of(2) {
+        return Err("Count/byte pairs are incomplete".to_string());
+    }
+    let mut result = Vec::new();
+    for pair in data.chunks(2) {
+        if pair[0] == 0 {
+            return Err("Run count cannot be zero".to_string());
+        }
+        result.extend(std::iter::repeat_n(pair[1], pair[0] as usize));
+    }
+    Ok(result)
+}
+
+fn rle_encode_text(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut result = String::new();
+    for run in chars.chunk_by(|a, b| a == b) {
+        result.push_str(&run.len().to_string());
+        if run[0].is_ascii_digit() || run[0] == '\\' {
+            result.push('\\');
+        }
+        result.push(run[0]);
+    }
+    result
+}
+
+/// Most characters text RLE decoding produces, so a huge run count is an
+/// error instead of an attempt to allocate gigabytes
+const RLE_MAX_OUTPUT: usize = 10_000_000;
+
+fn rle_decode_text(input: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut produced = 0;
+    let mut chars = input.chars().peekable();
+    while chars.peek().is_some() {
+        let mut count = String::new();
+        while let Some(&c) = chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            count.push(c);
+            chars.next();
+        }
+        let count: usize = count.parse().map_err(|e: std::num::ParseIntError| {
+            match e.kind() {
+                std::num::IntErrorKind::PosOverflow => "Run count too large",
+                _ => "Expected a run count",
+            }
+            .to_string()
+        })?;
+        if count > RLE_MAX_OUTPUT - produced {
+            return Err("Run count too large".to_string());
+        }
+        produced += count;
+
+        let c = match chars.next() {
+            Some('\\') => chars.next(),
+            other => other,
+        }
+        .ok_or_else(|| "Run count without a character".to_string())?;
+
+        result.extend(std::iter::repeat_n(c, count));
+    }
+    Ok(result)
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum DnaSource {
+    /// Each byte becomes four bases, two bits per base
+    Bytes,
+    /// Base-4 digits 0-3 map straight to A, C, G, T
+    Base4,
+}
+
+const NUCLEOTIDES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+// DNA Module
+#[derive(Serialize, Deserialize)]
+pub struct DnaModule {
+    mode: Direction,
+    source: DnaSource,
+    /// How the byte side is read (encode) or shown (decode)
+    data: ByteFormat,
+}
+
+impl Default for DnaModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+            source: DnaSource::Bytes,
+            data: ByteFormat::Raw,
+        }
+    }
+}
+
+impl Module for DnaModule {
+    fn name(&self) -> &str {
+        "DNA Encoding"
+    }
+
+    fn id(&self) -> &str {
+        "dna"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match (self.mode, self.source) {
+            (Direction::Encode, DnaSource::Bytes) => {
+                dna_encode(&self.data.parse(input).map_err(ProcessError::InvalidInput)?)
+            }
+            (Direction::Decode, DnaSource::Bytes) => self
+                .data
+                .format(&dna_decode(input).map_err(ProcessError::InvalidInput)?),
+            (Direction::Encode, DnaSource::Base4) => input
+                .chars()
+                .map(|c| match c.to_digit(4) {
+                    Some(d) => NUCLEOTIDES[d as usize],
+                    None => c,
+                })
+                .collect(),
+            (Direction::Decode, DnaSource::Base4) => input
+                .chars()
+                .map(|c| match dna_base_value(c) {
+                    Some(d) => char::from(b'0' + d),
+                    None => c,
+                })
+                .collect(),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Source:");
+            ui.radio_value(&mut self.source, DnaSource::Bytes, "Bytes (4 bases each)");
+            ui.radio_value(&mut self.source, DnaSource::Base4, "Base-4 digits");
+        });
+        if self.source == DnaSource::Bytes {
+            let label = match self.mode {
+                Direction::Encode => "Input:",
+                Direction::Decode => "Output:",
+            };
+            self.data.ui(ui, label);
+        }
+        ui.label("00 = A, 01 = C, 10 = G, 11 = T");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+// DNA encoding helper functions
+fn dna_base_value(c: char) -> Option<u8> {
+    NUCLEOTIDES
+        .iter()
+        .position(|&n| n == c.to_ascii_uppercase())
+        .map(|i| i as u8)
+}
+
+/// Most significant bit pair first
+fn dna_encode(data: &[u8]) -> String {
+    data.iter()
+        .flat_map(|&b| [6, 4, 2, 0].map(|shift| NUCLEOTIDES[((b >> shift) & 0b11) as usize]))
+        .collect()
+}
+
+fn dna_decode(input: &str) -> Result<Vec<u8>, String> {
+    let values = input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| dna_base_value(c).ok_or_else(|| format!("'{}' is not a nucleotide", c)))
+        .collect::<Result<Vec<u8>, String>>()?;
+    if !values.len().is_multiple_of(4) {
+        return Err(format!(
+            "{} bases is not a multiple of four (one byte = four bases)",
+            values.len()
+        ));
+    }
+    Ok(values
+        .chunks(4)
+        .map(|quad| quad.iter().fold(0u8, |acc, &v| (acc << 2) | v))
+        .collect())
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum ParityMode {
+    /// Append a parity bit to every byte
+    Add,
+    /// Verify and strip the trailing parity bit of every 9-bit group
+    Check,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Parity {
+    Even,
+    Odd,
+}
+
+// Parity Module
+#[derive(Serialize, Deserialize)]
+pub struct ParityModule {
+    mode: ParityMode,
+    parity: Parity,
+    /// How the data bytes are read (add) or shown (check)
+    data: ByteFormat,
+}
+
+impl Default for ParityModule {
+    fn default() -> Self {
+        Self {
+            mode: ParityMode::Add,
+            parity: Parity::Even,
+            data: ByteFormat::Raw,
+        }
+    }
+}
+
+impl Module for ParityModule {
+    fn name(&self) -> &str {
+        "Parity Bit"
+    }
+
+    fn id(&self) -> &str {
+        "parity"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let result = match self.mode {
+            ParityMode::Add => self
+                .data
+                .parse(input)
+                .map(|bytes| add_parity(&bytes, self.parity)),
+            ParityMode::Check => {
+                check_parity(input, self.parity).map(|bytes| self.data.format(&bytes))
+            }
+        };
+        result.map_err(ProcessError::InvalidInput)
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        // Checking strips the parity bits that adding appended
+        let mode = match self.mode {
+            ParityMode::Add => ParityMode::Check,
+            ParityMode::Check => ParityMode::Add,
+        };
+        Some(Inverse::changed(self, serde_json::json!({ "mode": mode })))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, ParityMode::Add, "Add parity");
+            ui.radio_value(&mut self.mode, ParityMode::Check, "Check and strip");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Parity:");
+            ui.radio_value(&mut self.parity, Parity::Even, "Even");
+            ui.radio_value(&mut self.parity, Parity::Odd, "Odd");
+        });
+        let label = match self.mode {
+            ParityMode::Add => "Input:",
+            ParityMode::Check => "Output:",
+        };
+        self.data.ui(ui, label);
+        ui.label("Each byte is written as 9 bits: the 8 data bits, most significant first, then the parity bit");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+// Parity helper functions
+fn parity_bit(byte: u8, parity: Parity) -> u8 {
+    let ones = byte.count_ones() as u8 & 1;
+    match parity {
+        Parity::Even => ones,
+        Parity::Odd => ones ^ 1,
+    }
+}
+
+fn add_parity(data: &[u8], parity: Parity) -> String {
+    data.iter()
+        .map(|&b| format!("{:08b}{}", b, parity_bit(b, parity)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Strip the parity bits, failing with the 1-based positions of any bad groups.
+fn check_parity(input: &str, parity: Parity) -> Result<Vec<u8>, String> {
+    let bits: Vec<u8> = input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '0' => Ok(0),
+            '1' => Ok(1),
+            _ => Err(format!("'{}' is not a bit", c)),
+        })
+        .collect::<Result<_, _>>()?;
+    if !bits.len().is_multiple_of(9) {
+        return Err(format!(
+            "{} bits is not a multiple of nine (8 data bits + 1 parity bit)",
+            bits.len()
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(bits.len() / 9);
+    let mut bad = Vec::new();
+    for (i, group) in bits.chunks(9).enumerate() {
+        let byte = group[..8].iter().fold(0u8, |acc, &b| (acc << 1) | b);
+        if parity_bit(byte, parity) != group[8] {
+            bad.push((i + 1).to_string());
+        }
+        bytes.push(byte);
+    }
+    if !bad.is_empty() {
+        return Err(format!("Parity error in group {}", bad.join(", ")));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rle_round_trips_long_runs() {
+        let input = format!("{}B{}\\12", "A".repeat(300), "C".repeat(256));
+        for format in [RleFormat::Text, RleFormat::Bytes] {
+            let encode = RleModule {
+                mode: Direction::Encode,
+                format,
+            };
+            let decode = RleModule {
+                mode: Direction::Decode,
+                format,
+            };
+            let encoded = encode.process(&input).unwrap();
+            assert_eq!(decode.process(&encoded).unwrap(), input);
+        }
+        assert_eq!(rle_encode_text("AAA1\\"), "3A1\\11\\\\");
+        assert_eq!(hex::encode(rle_encode_bytes(&[7; 300])), "ff072d07");
+    }
+
+    #[test]
+    fn test_rle_rejects_huge_run_counts() {
+        assert_eq!(
+            rle_decode_text("9999999999A"),
+            Err("Run count too large".to_string())
+        );
+        assert_eq!(
+            rle_decode_text("99999999999999999999999A"),
+            Err("Run count too large".to_string())
+        );
+        assert!(rle_decode_text("10000000A").is_ok());
+        assert!(rle_decode_text("10000000A1B").is_err());
+    }
+
+    #[test]
+    fn test_rfc1924_ipv6_address() {
+        let mut module = Ascii85Module {
+            mode: Direction::Encode,
+            variant: Base85Variant::Rfc1924,
+            ipv6_address: true,
+            ..Default::default()
+        };
+        // The example from RFC 1924 itself
+        assert_eq!(
+            module.process("1080:0:0:0:8:800:200C:417A").unwrap(),
+            "4)+k&C#VzJ4br>0wv%Yp"
+        );
+        module.mode = Direction::Decode;
+        assert_eq!(
+            module.process("4)+k&C#VzJ4br>0wv%Yp").unwrap(),
+            "1080::8:800:200c:417a"
+        );
+    }
+
+    #[test]
+    fn test_base64_wrapped_round_trip() {
+        let input = "Base64 in MIME and PEM is wrapped at 64 or 76 columns.".repeat(3);
+        let unwrapped = Base64Module::default().process(&input).unwrap();
+        let wrapped = Base64Module {
+            wrap: 76,
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        }
+        .process(&input)
+        .unwrap();
+        assert!(wrapped.split("\r\n").all(|line| line.len() <= 76));
+        assert_eq!(wrapped.replace("\r\n", ""), unwrapped);
+
+        let decode = Base64Module {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(decode.process(&wrapped).unwrap(), input);
+        assert_eq!(decode.process(&unwrapped).unwrap(), input);
+    }
+
+    #[test]
+    fn test_pem_certificate_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let pem = pem_wrap(&data, "CERTIFICATE");
+        let lines: Vec<&str> = pem.lines().collect();
+        assert_eq!(lines[0], "-----BEGIN CERTIFICATE-----");
+        assert_eq!(lines[lines.len() - 1], "-----END CERTIFICATE-----");
+        assert!(lines.iter().all(|line| line.len() <= 64));
+        assert_eq!(pem_unwrap(&pem).unwrap(), ("CERTIFICATE".to_string(), data));
+
+        let mismatched = pem.replace("END CERTIFICATE", "END PUBLIC KEY");
+        assert!(pem_unwrap(&mismatched).is_err());
+        assert!(pem_unwrap(&lines[1..].join("\n")).is_err());
+        assert!(pem_unwrap(&lines[..lines.len() - 1].join("\n")).is_err());
+    }
+
+    #[test]
+    fn test_ascii85_zero_group_in_each_style() {
+        let zeros = [0u8; 4];
+        assert_eq!(encode_ascii85(&zeros, Ascii85Style::Adobe, true), "<~z~>");
+        assert_eq!(
+            encode_ascii85(&zeros, Ascii85Style::Adobe, false),
+            "<~!!!!!~>"
+        );
+        assert_eq!(encode_ascii85(&zeros, Ascii85Style::Btoa, true), "z");
+        assert_eq!(encode_ascii85(&zeros, Ascii85Style::Btoa, false), "!!!!!");
+        // A partial group is never shortened
+        assert_eq!(encode_ascii85(&[0; 3], Ascii85Style::Btoa, true), "!!!!");
+
+        for encoded in ["<~z~>", "<~!!!!!~>"] {
+            assert_eq!(decode_ascii85(encoded, Ascii85Style::Adobe).unwrap(), zeros);
+        }
+        for encoded in ["z", "!!!!!"] {
+            assert_eq!(decode_ascii85(encoded, Ascii85Style::Btoa).unwrap(), zeros);
+        }
+        assert!(decode_ascii85("!!z!!!", Ascii85Style::Btoa).is_err());
+        assert_eq!(decode_ascii85("y", Ascii85Style::Btoa).unwrap(), *b"    ");
+        assert!(decode_ascii85("y", Ascii85Style::Adobe).is_err());
+    }
+
+    #[test]
+    fn test_dna_round_trips_every_byte() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let encoded = dna_encode(&bytes);
+        assert_eq!(encoded.len(), 1024);
+        assert_eq!(&encoded[..8], "AAAAAAAC");
+        assert_eq!(dna_decode(&encoded).unwrap(), bytes);
+        assert_eq!(dna_decode("acgt tgca").unwrap(), [0x1b, 0xe4]);
+    }
+
+    #[test]
+    fn test_dna_rejects_partial_bytes() {
+        let decode = DnaModule {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert!(decode.process("ACGTA").is_err());
+        assert!(decode.process("ACGU").is_err());
+    }
+
+    #[test]
+    fn test_even_and_odd_parity() {
+        // 'A' has two set bits, 'C' three
+        assert_eq!(add_parity(b"AC", Parity::Even), "010000010 010000111");
+        assert_eq!(add_parity(b"AC", Parity::Odd), "010000011 010000110");
+        for parity in [Parity::Even, Parity::Odd] {
+            let bits: Vec<u8> = (0..=255).collect();
+            assert_eq!(
+                check_parity(&add_parity(&bits, parity), parity).unwrap(),
+                bits
+            );
+        }
+        assert_eq!(
+            check_parity("010000010 010000111", Parity::Odd),
+            Err("Parity error in group 1, 2".to_string())
+        );
+        assert!(check_parity("01000001", Parity::Even).is_err());
+    }
+
+    #[test]
+    fn test_decode_ignores_embedded_whitespace() {
+        let mut base64 = Base64Module {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(
+            base64.process(" SGVsbG8s\r\nIHdv cmxk\n\tIQ==\n").unwrap(),
+            "Hello, world!"
+        );
+        base64.ignore_whitespace = false;
+        assert!(base64.process("SGVsbG8s\nIHdvcmxkIQ==").is_err());
+        assert_eq!(
+            base64.process(" SGVsbG8sIHdvcmxkIQ==\n").unwrap(),
+            "Hello, world!"
+        );
+
+        let base32 = Base32Module {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(base32.process("JBSW Y3DP\nEE======").unwrap(), "Hello!");
+    }
+
+    #[test]
+    fn test_read_little_endian_u32() {
+        let mut module = IntegerModule {
+            mode: IntegerMode::BytesToWords,
+            little_endian: true,
+            ..Default::default()
+        };
+        assert_eq!(module.process("78563412").unwrap(), "305419896");
+        // A trailing partial word is left out
+        assert_eq!(module.process("78563412 ff").unwrap(), "305419896");
+        assert_eq!(module.process("ffffffff").unwrap(), "4294967295");
+        module.signed = true;
+        assert_eq!(module.process("ffffffff").unwrap(), "-1");
+
+        module.mode = IntegerMode::WordsToBytes;
+        assert_eq!(module.process("305419896, -1").unwrap(), "78563412ffffffff");
+        assert!(module.process("4294967295").is_err());
+    }
+
+    #[test]
+    fn test_url_round_trips_multibyte_text() {
+        let mut module = UrlEncodingModule::default();
+        assert_eq!(module.process("café").unwrap(), "caf%C3%A9");
+        assert_eq!(module.process("🦀").unwrap(), "%F0%9F%A6%80");
+        let text = "Crème brûlée & 🦀 ñandú";
+        let encoded = module.process(text).unwrap();
+        assert!(encoded.is_ascii());
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), text);
+        assert_eq!(module.process("caf%c3%a9").unwrap(), "café");
+    }
+
+    #[test]
+    fn test_binary_text_round_trip() {
+        for (separator, encoded) in [(" ", "01001000 01101001"), ("", "0100100001101001")] {
+            let encoder = BinaryTextModule {
+                separator: separator.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(encoder.process("Hi").unwrap(), encoded);
+            let decoder = BinaryTextModule {
+                mode: Direction::Decode,
+                separator: separator.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(decoder.process(encoded).unwrap(), "Hi");
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use crate::module::Module;
+use crate::module::{Inverse, Module, ProcessError};
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 
 /// Historical Enigma rotor wirings (I-VIII)
 const ROTOR_WIRINGS: [&str; 8] = [
@@ -25,13 +26,51 @@ const ROTOR_NOTCHES: [&str; 8] = [
     "ZM", // Rotor VIII (two notches)
 ];
 
+/// Naval M4 "Greek" wheels, which sit left of the three rotors and never step
+const GREEK_WIRINGS: [&str; 2] = [
+    "LEYJVCNIXWPBQMDRTAKZGFUHOS", // Beta
+    "FSOKANUERHMBTIYCWLQPZXVGJD", // Gamma
+];
+
 /// Historical reflector wirings
-const REFLECTOR_WIRINGS: [&str; 3] = [
+const REFLECTOR_WIRINGS: [&str; 4] = [
     "YRUHQSLDPXNGOKMIEBFZCWVJAT", // Reflector B
     "FVPJIAOYEDRZXWGCTKUQSBNMHL", // Reflector C
     "ENKQAUYWJICOPBLMDXZVFTHRGS", // Reflector B-Thin
+    "RDOBJNTKVEHMLFCWZAXGYIPSUQ", // Reflector C-Thin
+];
+
+const REFLECTOR_NAMES: [&str; 4] = [
+    "Reflector B",
+    "Reflector C",
+    "Reflector B-Thin",
+    "Reflector C-Thin",
 ];
 
+/// Thin reflectors only fit the 4-rotor M4, and B/C only the 3-rotor machines
+fn reflector_fits(reflector: usize, four_rotor: bool) -> bool {
+    match reflector {
+        0 | 1 => !four_rotor,
+        2 | 3 => four_rotor,
+        _ => false,
+    }
+}
+
+/// The letter shown for a position or ring setting, taken mod 26 since
+/// imported settings may be out of range
+fn letter(n: u8) -> char {
+    (b'A' + n % 26) as char
+}
+
+/// "Rotor N" for a rotor index, or "Unknown rotor" past rotor VIII
+fn rotor_label(rotor_num: usize) -> String {
+    if rotor_num < ROTOR_WIRINGS.len() {
+        format!("Rotor {}", rotor_num + 1)
+    } else {
+        "Unknown rotor".to_string()
+    }
+}
+
 #[derive(Clone)]
 struct Rotor {
     wiring: String,
@@ -41,17 +80,28 @@ struct Rotor {
 }
 
 impl Rotor {
-    fn new(rotor_num: usize, position: u8, ring_setting: u8) -> Self {
-        Self {
-            wiring: ROTOR_WIRINGS[rotor_num].to_string(),
-            notch: ROTOR_NOTCHES[rotor_num].to_string(),
+    /// Rotor I-VIII as index 0-7, or `None` for any other index
+    fn new(rotor_num: usize, position: u8, ring_setting: u8) -> Option<Self> {
+        Some(Self {
+            wiring: ROTOR_WIRINGS.get(rotor_num)?.to_string(),
+            notch: ROTOR_NOTCHES.get(rotor_num)?.to_string(),
             position: position % 26,
             ring_setting: ring_setting % 26,
-        }
+        })
+    }
+
+    /// Beta (0) or Gamma (1), or `None` for any other index
+    fn greek(greek_num: usize, position: u8, ring_setting: u8) -> Option<Self> {
+        Some(Self {
+            wiring: GREEK_WIRINGS.get(greek_num)?.to_string(),
+            notch: String::new(),
+            position: position % 26,
+            ring_setting: ring_setting % 26,
+        })
     }
 
     fn at_notch(&self) -> bool {
-        let pos_char = (b'A' + self.position) as char;
+        let pos_char = letter(self.position);
         self.notch.contains(pos_char)
     }
 
@@ -107,8 +157,8 @@ struct Plugboard {
 impl Plugboard {
     fn new(pairs: &str) -> Self {
         let mut mapping = [0u8; 26];
-        for i in 0..26 {
-            mapping[i] = i as u8;
+        for (i, slot) in mapping.iter_mut().enumerate() {
+            *slot = i as u8;
         }
 
         // Parse pairs like "AB CD EF"
@@ -132,7 +182,14 @@ impl Plugboard {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct EnigmaModule {
+    // M4 mode: adds the Greek wheel and uses the thin reflectors
+    four_rotor: bool,
+    greek_rotor: usize, // 0 = Beta, 1 = Gamma
+    greek_position: u8,
+    greek_ring: u8,
+
     // Rotor selection (0-7 for rotors I-VIII)
     left_rotor: usize,
     middle_rotor: usize,
@@ -148,7 +205,7 @@ pub struct EnigmaModule {
     middle_ring: u8,
     right_ring: u8,
 
-    // Reflector selection (0-2)
+    // Reflector selection (index into REFLECTOR_WIRINGS)
     reflector: usize,
 
     // Plugboard settings
@@ -158,6 +215,10 @@ pub struct EnigmaModule {
 impl Default for EnigmaModule {
     fn default() -> Self {
         Self {
+            four_rotor: false,
+            greek_rotor: 0,     // Beta
+            greek_position: 0,  // A
+            greek_ring: 0,      // A
             left_rotor: 0,      // Rotor I
             middle_rotor: 1,    // Rotor II
             right_rotor: 2,     // Rotor III
@@ -174,10 +235,44 @@ impl Default for EnigmaModule {
 }
 
 impl EnigmaModule {
+    /// Settings that no real machine could have, e.g. one rotor in two slots.
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let slots = [
+            ("left", self.left_rotor),
+            ("middle", self.middle_rotor),
+            ("right", self.right_rotor),
+        ];
+        for (i, (slot_a, rotor_a)) in slots.iter().enumerate() {
+            for (slot_b, rotor_b) in &slots[i + 1..] {
+                // Unknown rotors are reported by `process` instead
+                if rotor_a == rotor_b && *rotor_a < ROTOR_WIRINGS.len() {
+                    warnings.push(format!(
+                        "{} is in both the {} and {} slots; each machine had only one of each rotor",
+                        rotor_label(*rotor_a),
+                        slot_a,
+                        slot_b
+                    ));
+                }
+            }
+        }
+        if !reflector_fits(self.reflector, self.four_rotor) {
+            warnings.push(format!(
+                "{} does not fit a {}-rotor machine",
+                REFLECTOR_NAMES
+                    .get(self.reflector)
+                    .unwrap_or(&"Unknown reflector"),
+                if self.four_rotor { 4 } else { 3 }
+            ));
+        }
+        warnings
+    }
+
     fn encode_char(
         &self,
         c: char,
         rotors: &mut [Rotor; 3],
+        greek: Option<&Rotor>,
         reflector: &Reflector,
         plugboard: &Plugboard,
     ) -> char {
@@ -207,11 +302,17 @@ impl EnigmaModule {
         signal = rotors[2].forward(signal);
         signal = rotors[1].forward(signal);
         signal = rotors[0].forward(signal);
+        if let Some(greek) = greek {
+            signal = greek.forward(signal);
+        }
 
         // Through reflector
         signal = reflector.reflect(signal);
 
         // Back through rotors (left to right)
+        if let Some(greek) = greek {
+            signal = greek.backward(signal);
+        }
         signal = rotors[0].backward(signal);
         signal = rotors[1].backward(signal);
         signal = rotors[2].backward(signal);
@@ -229,30 +330,100 @@ impl Module for EnigmaModule {
         "Enigma Machine"
     }
 
-    fn process(&self, input: &str) -> String {
-        // Create rotors with current settings
+    fn id(&self) -> &str {
+        "enigma"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        if !reflector_fits(self.reflector, self.four_rotor) {
+            return Err(ProcessError::BadConfig(
+                "Thin reflectors need 4-rotor (M4) mode, and B/C need 3-rotor mode".to_string(),
+            ));
+        }
+
+        // Create rotors with current settings; imported recipes may name
+        // rotors that do not exist
+        let rotor = |slot: &str, rotor_num: usize, position: u8, ring: u8| {
+            Rotor::new(rotor_num, position, ring).ok_or_else(|| {
+                ProcessError::BadConfig(format!("The {} rotor must be one of I-VIII", slot))
+            })
+        };
         let mut rotors = [
-            Rotor::new(self.left_rotor, self.left_position, self.left_ring),
-            Rotor::new(self.middle_rotor, self.middle_position, self.middle_ring),
-            Rotor::new(self.right_rotor, self.right_position, self.right_ring),
+            rotor("left", self.left_rotor, self.left_position, self.left_ring)?,
+            rotor(
+                "middle",
+                self.middle_rotor,
+                self.middle_position,
+                self.middle_ring,
+            )?,
+            rotor(
+                "right",
+                self.right_rotor,
+                self.right_position,
+                self.right_ring,
+            )?,
         ];
 
+        let greek = if self.four_rotor {
+            let greek = Rotor::greek(self.greek_rotor, self.greek_position, self.greek_ring)
+                .ok_or_else(|| {
+                    ProcessError::BadConfig("The Greek wheel must be Beta or Gamma".to_string())
+                })?;
+            Some(greek)
+        } else {
+            None
+        };
+
         let reflector = Reflector::new(self.reflector);
         let plugboard = Plugboard::new(&self.plugboard_pairs);
 
-        input
+        Ok(input
             .chars()
-            .map(|c| self.encode_char(c, &mut rotors, &reflector, &plugboard))
-            .collect()
+            .map(|c| self.encode_char(c, &mut rotors, greek.as_ref(), &reflector, &plugboard))
+            .collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        // The machine is reciprocal: the same start settings decrypt
+        Some(Inverse::Itself)
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(
+            "Enigma is reciprocal: decrypt by encrypting the ciphertext with the same settings.",
+        );
+        for warning in self.warnings() {
+            ui.colored_label(
+                egui::Color32::from_rgb(220, 160, 0),
+                format!("⚠ {}", warning),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Machine:");
+            let before = self.four_rotor;
+            ui.radio_value(&mut self.four_rotor, false, "3-rotor (I, M3)");
+            ui.radio_value(&mut self.four_rotor, true, "4-rotor (M4)");
+            if before != self.four_rotor && !reflector_fits(self.reflector, self.four_rotor) {
+                // Swap to the matching reflector family (B <-> B-Thin, C <-> C-Thin)
+                self.reflector = (self.reflector + 2) % 4;
+            }
+        });
+
         ui.heading("Rotor Selection");
 
+        if self.four_rotor {
+            ui.horizontal(|ui| {
+                ui.label("Greek Wheel:");
+                ui.radio_value(&mut self.greek_rotor, 0, "Beta");
+                ui.radio_value(&mut self.greek_rotor, 1, "Gamma");
+            });
+        }
+
         ui.horizontal(|ui| {
             ui.label("Left Rotor:");
             egui::ComboBox::new("left_rotor", "")
-                .selected_text(format!("Rotor {}", self.left_rotor + 1))
+                .selected_text(rotor_label(self.left_rotor))
                 .show_ui(ui, |ui| {
                     for i in 0..8 {
                         ui.selectable_value(&mut self.left_rotor, i, format!("Rotor {}", i + 1));
@@ -263,7 +434,7 @@ impl Module for EnigmaModule {
         ui.horizontal(|ui| {
             ui.label("Middle Rotor:");
             egui::ComboBox::new("middle_rotor", "")
-                .selected_text(format!("Rotor {}", self.middle_rotor + 1))
+                .selected_text(rotor_label(self.middle_rotor))
                 .show_ui(ui, |ui| {
                     for i in 0..8 {
                         ui.selectable_value(&mut self.middle_rotor, i, format!("Rotor {}", i + 1));
@@ -274,7 +445,7 @@ impl Module for EnigmaModule {
         ui.horizontal(|ui| {
             ui.label("Right Rotor:");
             egui::ComboBox::new("right_rotor", "")
-                .selected_text(format!("Rotor {}", self.right_rotor + 1))
+                .selected_text(rotor_label(self.right_rotor))
                 .show_ui(ui, |ui| {
                     for i in 0..8 {
                         ui.selectable_value(&mut self.right_rotor, i, format!("Rotor {}", i + 1));
@@ -283,11 +454,31 @@ impl Module for EnigmaModule {
         });
 
         ui.separator();
-        ui.heading("Rotor Positions");
+        ui.horizontal(|ui| {
+            ui.heading("Rotor Positions");
+            if ui.button("Reset rotors to AAA").clicked() {
+                self.greek_position = 0;
+                self.left_position = 0;
+                self.middle_position = 0;
+                self.right_position = 0;
+            }
+        });
+        ui.small("Grundstellung: the letters showing in the windows when you start typing. The rotors step with every key press.");
+
+        if self.four_rotor {
+            ui.horizontal(|ui| {
+                ui.label("Greek:");
+                let greek_char = letter(self.greek_position);
+                ui.add(
+                    egui::Slider::new(&mut self.greek_position, 0..=25)
+                        .text(format!("{}", greek_char)),
+                );
+            });
+        }
 
         ui.horizontal(|ui| {
             ui.label("Left:");
-            let left_char = (b'A' + self.left_position) as char;
+            let left_char = letter(self.left_position);
             ui.add(
                 egui::Slider::new(&mut self.left_position, 0..=25).text(format!("{}", left_char)),
             );
@@ -295,7 +486,7 @@ impl Module for EnigmaModule {
 
         ui.horizontal(|ui| {
             ui.label("Middle:");
-            let middle_char = (b'A' + self.middle_position) as char;
+            let middle_char = letter(self.middle_position);
             ui.add(
                 egui::Slider::new(&mut self.middle_position, 0..=25)
                     .text(format!("{}", middle_char)),
@@ -304,7 +495,7 @@ impl Module for EnigmaModule {
 
         ui.horizontal(|ui| {
             ui.label("Right:");
-            let right_char = (b'A' + self.right_position) as char;
+            let right_char = letter(self.right_position);
             ui.add(
                 egui::Slider::new(&mut self.right_position, 0..=25).text(format!("{}", right_char)),
             );
@@ -312,10 +503,22 @@ impl Module for EnigmaModule {
 
         ui.separator();
         ui.heading("Ring Settings");
+        ui.small("Ringstellung: where the alphabet ring sits relative to the wiring. It is fixed for the day and changes the offset, not the starting letters.");
+
+        if self.four_rotor {
+            ui.horizontal(|ui| {
+                ui.label("Greek:");
+                let greek_ring_char = letter(self.greek_ring);
+                ui.add(
+                    egui::Slider::new(&mut self.greek_ring, 0..=25)
+                        .text(format!("{}", greek_ring_char)),
+                );
+            });
+        }
 
         ui.horizontal(|ui| {
             ui.label("Left:");
-            let left_ring_char = (b'A' + self.left_ring) as char;
+            let left_ring_char = letter(self.left_ring);
             ui.add(
                 egui::Slider::new(&mut self.left_ring, 0..=25).text(format!("{}", left_ring_char)),
             );
@@ -323,7 +526,7 @@ impl Module for EnigmaModule {
 
         ui.horizontal(|ui| {
             ui.label("Middle:");
-            let middle_ring_char = (b'A' + self.middle_ring) as char;
+            let middle_ring_char = letter(self.middle_ring);
             ui.add(
                 egui::Slider::new(&mut self.middle_ring, 0..=25)
                     .text(format!("{}", middle_ring_char)),
@@ -332,7 +535,7 @@ impl Module for EnigmaModule {
 
         ui.horizontal(|ui| {
             ui.label("Right:");
-            let right_ring_char = (b'A' + self.right_ring) as char;
+            let right_ring_char = letter(self.right_ring);
             ui.add(
                 egui::Slider::new(&mut self.right_ring, 0..=25)
                     .text(format!("{}", right_ring_char)),
@@ -344,16 +547,13 @@ impl Module for EnigmaModule {
 
         ui.horizontal(|ui| {
             egui::ComboBox::new("reflector", "")
-                .selected_text(match self.reflector {
-                    0 => "Reflector B",
-                    1 => "Reflector C",
-                    2 => "Reflector B-Thin",
-                    _ => "Unknown",
-                })
+                .selected_text(*REFLECTOR_NAMES.get(self.reflector).unwrap_or(&"Unknown"))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.reflector, 0, "Reflector B");
-                    ui.selectable_value(&mut self.reflector, 1, "Reflector C");
-                    ui.selectable_value(&mut self.reflector, 2, "Reflector B-Thin");
+                    for (i, name) in REFLECTOR_NAMES.iter().enumerate() {
+                        if reflector_fits(i, self.four_rotor) {
+                            ui.selectable_value(&mut self.reflector, i, *name);
+                        }
+                    }
                 });
         });
 
@@ -371,3 +571,42 @@ impl Module for EnigmaModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_rotor_twice_is_flagged() {
+        assert!(EnigmaModule::default().warnings().is_empty());
+        let module = EnigmaModule {
+            right_rotor: 0,
+            ..Default::default()
+        };
+        let warnings = module.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Rotor 1 is in both the left and right slots"));
+    }
+
+    #[test]
+    fn test_reflector_matches_rotor_count() {
+        // B and C fit the 3-rotor machines, B-Thin and C-Thin only the M4
+        for (reflector, fits_three, fits_four) in [
+            (0, true, false),
+            (1, true, false),
+            (2, false, true),
+            (3, false, true),
+        ] {
+            for (four_rotor, fits) in [(false, fits_three), (true, fits_four)] {
+                let module = EnigmaModule {
+                    four_rotor,
+                    reflector,
+                    ..Default::default()
+                };
+                assert_eq!(reflector_fits(reflector, four_rotor), fits);
+                assert_eq!(module.process("HELLO").is_ok(), fits);
+                assert_eq!(module.warnings().is_empty(), fits);
+            }
+        }
+    }
+}
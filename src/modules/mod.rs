@@ -1,28 +1,143 @@
 pub mod alphabet;
+pub mod analysis;
 pub mod cipher;
+pub mod compression;
 pub mod encoding;
 pub mod enigma;
 pub mod modern;
 pub mod polybius;
 pub mod transform;
+pub mod vic;
 
 use crate::module::Module;
 
+/// Every id `create_module` accepts, in sidebar order; the sidebar groups
+/// them by `category`.
+pub const MODULE_IDS: &[&str] = &[
+    "replace",
+    "reverse",
+    "case_transform",
+    "numeral",
+    "mixed_radix",
+    "number_words",
+    "bitwise",
+    "affix",
+    "separate",
+    "per_word",
+    "split_apply",
+    "byteorder",
+    "bit_reverse",
+    "gray",
+    "chain_xor",
+    "homoglyph",
+    "line_ending",
+    "morse",
+    "spelling",
+    "enigma",
+    "caesar",
+    "affine",
+    "hill",
+    "rot13",
+    "rot47",
+    "rotate",
+    "atbash",
+    "a1z26",
+    "vigenere",
+    "bacon",
+    "substitution",
+    "rail_fence",
+    "scytale",
+    "columnar",
+    "vic",
+    "polybius",
+    "tap_code",
+    "adfgx",
+    "bifid",
+    "nihilist",
+    "trifid",
+    "base32",
+    "base64",
+    "pem",
+    "ascii85",
+    "baudot",
+    "unicode",
+    "binary_text",
+    "url",
+    "punycode",
+    "bootstring",
+    "integer",
+    "rle",
+    "dna",
+    "parity",
+    "gzip",
+    "zlib",
+    "deflate",
+    "block_cipher",
+    "rc4",
+    "hash",
+    "hmac",
+    "condition",
+    "filetype",
+    "byte_heatmap",
+    "auto_peel",
+];
+
+/// The sidebar group a module id belongs to.
+pub fn category(id: &str) -> &'static str {
+    match id {
+        "replace" | "reverse" | "case_transform" | "numeral" | "mixed_radix" | "number_words"
+        | "bitwise" | "affix" | "separate" | "per_word" | "split_apply" | "byteorder"
+        | "bit_reverse" | "gray" | "chain_xor" | "homoglyph" | "line_ending" => "Transform",
+        "morse" | "spelling" => "Alphabets",
+        "enigma" | "caesar" | "affine" | "hill" | "rot13" | "rot47" | "rotate" | "atbash"
+        | "a1z26" | "vigenere" | "bacon" | "substitution" | "rail_fence" | "scytale"
+        | "columnar" | "vic" => "Ciphers",
+        "polybius" | "tap_code" | "adfgx" | "bifid" | "nihilist" | "trifid" => {
+            "Polybius Square Ciphers"
+        }
+        "base32" | "base64" | "pem" | "ascii85" | "baudot" | "unicode" | "binary_text" | "url"
+        | "punycode" | "bootstring" | "integer" | "rle" | "dna" | "parity" => "Encoding",
+        "gzip" | "zlib" | "deflate" => "Compression",
+        "block_cipher" | "rc4" | "hash" | "hmac" => "Modern Cryptography",
+        "condition" | "filetype" | "byte_heatmap" | "auto_peel" => "Analysis",
+        _ => "Other",
+    }
+}
+
 pub fn create_module(id: &str) -> Option<Box<dyn Module>> {
     match id {
         "reverse" => Some(Box::new(transform::ReverseModule)),
         "case_transform" => Some(Box::new(transform::CaseTransformModule::default())),
         "replace" => Some(Box::new(transform::ReplaceModule::default())),
         "numeral" => Some(Box::new(transform::NumeralSystemModule::default())),
+        "mixed_radix" => Some(Box::new(transform::MixedRadixModule::default())),
+        "number_words" => Some(Box::new(transform::NumberWordsModule::default())),
         "bitwise" => Some(Box::new(transform::BitwiseOperationModule::default())),
+        "affix" => Some(Box::new(transform::AffixModule::default())),
+        "separate" => Some(Box::new(transform::SeparateModule::default())),
+        "per_word" => Some(Box::new(transform::PerWordModule::default())),
+        "split_apply" => Some(Box::new(transform::SplitApplyModule::default())),
+        "byteorder" => Some(Box::new(transform::ByteOrderModule::default())),
+        "bit_reverse" => Some(Box::new(transform::BitReverseModule::default())),
+        "gray" => Some(Box::new(transform::GrayCodeModule::default())),
+        "chain_xor" => Some(Box::new(transform::ChainXorModule::default())),
+        "homoglyph" => Some(Box::new(transform::HomoglyphModule::default())),
+        "line_ending" => Some(Box::new(transform::LineEndingModule::default())),
         "morse" => Some(Box::new(alphabet::MorseCodeModule::default())),
         "spelling" => Some(Box::new(alphabet::SpellingAlphabetModule)),
         "caesar" => Some(Box::new(cipher::CaesarCipherModule::default())),
         "rot13" => Some(Box::new(cipher::ROT13Module)),
+        "rot47" => Some(Box::new(cipher::ROT47Module)),
+        "rotate" => Some(Box::new(cipher::RotationModule::default())),
+        "atbash" => Some(Box::new(cipher::AtbashModule)),
         "a1z26" => Some(Box::new(cipher::A1Z26Module::default())),
         "affine" => Some(Box::new(cipher::AffineCipherModule::default())),
+        "hill" => Some(Box::new(cipher::HillCipherModule::default())),
         "vigenere" => Some(Box::new(cipher::VigenereCipherModule::default())),
         "rail_fence" => Some(Box::new(cipher::RailFenceCipherModule::default())),
+        "scytale" => Some(Box::new(cipher::ScytaleModule::default())),
+        "columnar" => Some(Box::new(cipher::ColumnarTranspositionModule::default())),
+        "vic" => Some(Box::new(vic::VicCipherModule::default())),
         "bacon" => Some(Box::new(cipher::BaconCipherModule::default())),
         "substitution" => Some(Box::new(cipher::AlphabeticalSubstitutionModule::default())),
         "polybius" => Some(Box::new(polybius::PolybiusSquareModule::default())),
@@ -32,19 +147,210 @@ pub fn create_module(id: &str) -> Option<Box<dyn Module>> {
         "tap_code" => Some(Box::new(polybius::TapCodeModule::default())),
         "trifid" => Some(Box::new(polybius::TrifidCipherModule::default())),
         "base64" => Some(Box::new(encoding::Base64Module::default())),
+        "pem" => Some(Box::new(encoding::PemModule::default())),
         "base32" => Some(Box::new(encoding::Base32Module::default())),
         "ascii85" => Some(Box::new(encoding::Ascii85Module::default())),
         "baudot" => Some(Box::new(encoding::BaudotCodeModule::default())),
         "unicode" => Some(Box::new(encoding::UnicodeCodePointsModule::default())),
+        "binary_text" => Some(Box::new(encoding::BinaryTextModule::default())),
         "url" => Some(Box::new(encoding::UrlEncodingModule::default())),
         "punycode" => Some(Box::new(encoding::PunycodeModule::default())),
         "bootstring" => Some(Box::new(encoding::BootstringModule::default())),
         "integer" => Some(Box::new(encoding::IntegerModule::default())),
+        "rle" => Some(Box::new(encoding::RleModule::default())),
+        "dna" => Some(Box::new(encoding::DnaModule::default())),
+        "parity" => Some(Box::new(encoding::ParityModule::default())),
+        "gzip" => Some(Box::new(compression::CompressionModule::new(
+            compression::CompressionFormat::Gzip,
+        ))),
+        "zlib" => Some(Box::new(compression::CompressionModule::new(
+            compression::CompressionFormat::Zlib,
+        ))),
+        "deflate" => Some(Box::new(compression::CompressionModule::new(
+            compression::CompressionFormat::Deflate,
+        ))),
         "block_cipher" => Some(Box::new(modern::BlockCipherModule::default())),
         "rc4" => Some(Box::new(modern::RC4Module::default())),
         "hash" => Some(Box::new(modern::HashFunctionModule::default())),
         "hmac" => Some(Box::new(modern::HMACModule::default())),
         "enigma" => Some(Box::new(enigma::EnigmaModule::default())),
+        "condition" => Some(Box::new(analysis::ConditionModule::default())),
+        "filetype" => Some(Box::new(analysis::FileTypeModule::default())),
+        "byte_heatmap" => Some(Box::new(analysis::ByteHeatmapModule::default())),
+        "auto_peel" => Some(Box::new(analysis::AutoPeelModule::default())),
         _ => None,
     }
 }
+
+/// Names a module goes by in other tools, and how it differs from them.
+pub struct Equivalent {
+    pub cyberchef: Option<&'static str>,
+    pub dcode: Option<&'static str>,
+    pub note: Option<&'static str>,
+}
+
+const fn eq(
+    cyberchef: Option<&'static str>,
+    dcode: Option<&'static str>,
+    note: Option<&'static str>,
+) -> Equivalent {
+    Equivalent {
+        cyberchef,
+        dcode,
+        note,
+    }
+}
+
+/// The CyberChef operation and dCode tool matching the module `id`, if any.
+pub fn equivalent(id: &str) -> Option<Equivalent> {
+    let equivalent = match id {
+        "reverse" => eq(Some("Reverse"), None, None),
+        "case_transform" => eq(Some("To Upper case / To Lower case"), None, None),
+        "replace" => eq(
+            Some("Find / Replace"),
+            None,
+            Some("Plain text only; CyberChef also takes regexes"),
+        ),
+        "numeral" => eq(Some("To Base / From Base"), Some("Base N Convert"), None),
+        "bitwise" => eq(
+            Some("XOR, AND, OR, NOT, Bit shift, Rotate"),
+            None,
+            None,
+        ),
+        "byteorder" => eq(Some("Swap endianness"), None, None),
+        "chain_xor" => eq(
+            Some("XOR (Output differential / Input differential scheme)"),
+            None,
+            Some("CyberChef also XORs in a key and has no IV; key 00 matches IV 0"),
+        ),
+        "morse" => eq(
+            Some("To Morse Code / From Morse Code"),
+            Some("Morse Code"),
+            None,
+        ),
+        "spelling" => eq(None, Some("NATO Phonetic Alphabet"), None),
+        "caesar" => eq(
+            Some("ROT13 (with a custom amount)"),
+            Some("Caesar Cipher"),
+            None,
+        ),
+        "rot13" => eq(Some("ROT13"), Some("ROT-13 Cipher"), None),
+        "rot47" => eq(Some("ROT47"), Some("ROT-47 Cipher"), None),
+        "rotate" => eq(Some("ROT13 / ROT47"), Some("ROT Cipher"), None),
+        "atbash" => eq(Some("Atbash Cipher"), Some("Atbash Cipher"), None),
+        "a1z26" => eq(
+            Some("A1Z26 Cipher Encode / Decode"),
+            Some("Letter Number Code (A1Z26)"),
+            None,
+        ),
+        "affine" => eq(
+            Some("Affine Cipher Encode / Decode"),
+            Some("Affine Cipher"),
+            None,
+        ),
+        "hill" => eq(None, Some("Hill Cipher"), None),
+        "vigenere" => eq(
+            Some("Vigenère Encode / Decode"),
+            Some("Vigenere Cipher"),
+            None,
+        ),
+        "rail_fence" => eq(
+            Some("Rail Fence Cipher Encode / Decode"),
+            Some("Rail Fence (Zig-Zag) Cipher"),
+            Some("No offset setting; CyberChef's offset must be 0 to match"),
+        ),
+        "scytale" => eq(None, Some("Scytale Cipher"), None),
+        "columnar" => eq(
+            None,
+            Some("Columnar Transposition Cipher / Double Transposition Cipher"),
+            None,
+        ),
+        "bacon" => eq(
+            Some("Bacon Cipher Encode / Decode"),
+            Some("Bacon Cipher"),
+            None,
+        ),
+        "substitution" => eq(
+            Some("Substitute"),
+            Some("Mono-alphabetic Substitution"),
+            Some("Maps letters only and keeps their case; CyberChef's Substitute maps any character as given"),
+        ),
+        "polybius" => eq(None, Some("Polybius Cipher"), None),
+        "adfgx" => eq(None, Some("ADFGX Cipher"), None),
+        "bifid" => eq(
+            Some("Bifid Cipher Encode / Decode"),
+            Some("Bifid Cipher"),
+            None,
+        ),
+        "nihilist" => eq(None, Some("Nihilist Cipher"), None),
+        "tap_code" => eq(None, Some("Tap Code Cipher"), None),
+        "trifid" => eq(None, Some("Trifid Cipher"), None),
+        "base64" => eq(Some("To Base64 / From Base64"), Some("Base 64 Coding"), None),
+        "pem" => eq(Some("Hex to PEM / PEM to Hex"), None, None),
+        "base32" => eq(Some("To Base32 / From Base32"), Some("Base 32 Coding"), None),
+        "ascii85" => eq(
+            Some("To Base85 / From Base85"),
+            Some("ASCII85 Encoding"),
+            Some("The Adobe style always writes the <~ ~> framing, which CyberChef only adds with \"Include delimiter\""),
+        ),
+        "baudot" => eq(None, Some("Baudot Code"), None),
+        "unicode" => eq(Some("Escape Unicode Characters (U+ prefix)"), None, None),
+        "binary_text" => eq(Some("To Binary / From Binary"), Some("Binary Code"), None),
+        "url" => eq(Some("URL Encode / URL Decode"), Some("URL Encoding"), None),
+        "punycode" => eq(Some("To Punycode / From Punycode"), Some("Punycode"), None),
+        "rle" => eq(None, Some("RLE (Run-Length Encoding)"), None),
+        "gzip" => eq(Some("Gzip / Gunzip"), None, None),
+        "zlib" => eq(Some("Zlib Deflate / Zlib Inflate"), None, None),
+        "deflate" => eq(Some("Raw Deflate / Raw Inflate"), None, None),
+        "block_cipher" => eq(
+            Some("AES Encrypt / AES Decrypt"),
+            Some("AES Encryption"),
+            Some("AES-128 only (ECB, CBC or CTR)"),
+        ),
+        "rc4" => eq(Some("RC4"), Some("RC4 Cipher"), None),
+        "hash" => eq(
+            Some("MD5, SHA2, CRC-32 Checksum, Adler-32 Checksum"),
+            None,
+            Some("Only MD5 and SHA-256 among the digests"),
+        ),
+        "hmac" => eq(Some("HMAC"), None, None),
+        "enigma" => eq(Some("Enigma"), Some("Enigma Machine"), None),
+        "filetype" => eq(Some("Detect File Type"), None, None),
+        "auto_peel" => eq(
+            Some("Magic"),
+            None,
+            Some("Only tries a few text encodings, taking the first that decodes"),
+        ),
+        _ => return None,
+    };
+    Some(equivalent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Modules whose output for empty input is meaningful on its own: an
+    /// affix, a compressed stream, a padded cipher block or a digest.
+    const NON_EMPTY_FOR_EMPTY_INPUT: &[&str] = &[
+        "affix",
+        "gzip",
+        "zlib",
+        "deflate",
+        "block_cipher",
+        "hash",
+        "hmac",
+    ];
+
+    #[test]
+    fn test_empty_input_gives_empty_output() {
+        for &id in MODULE_IDS {
+            let output = create_module(id).unwrap().process("");
+            if NON_EMPTY_FOR_EMPTY_INPUT.contains(&id) {
+                assert!(!output.unwrap().is_empty(), "{id}");
+            } else {
+                assert_eq!(output.as_deref(), Ok(""), "{id}");
+            }
+        }
+    }
+}
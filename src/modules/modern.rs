@@ -1,115 +1,545 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit, StreamCipher};
 use aes::Aes128;
+use base64::prelude::*;
 use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
 use cbc::{Decryptor, Encryptor};
 use eframe::egui;
 use md5::{Digest as Md5Digest, Md5};
+use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use std::cell::RefCell;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 type Aes128CbcEnc = Encryptor<Aes128>;
 type Aes128CbcDec = Decryptor<Aes128>;
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
 
-#[derive(PartialEq, Clone, Copy)]
-enum BlockCipherMode {
-    Encrypt,
-    Decrypt,
+/// How the block cipher chains its 16-byte blocks
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum CipherMode {
+    /// Each block on its own, without an IV
+    Ecb,
+    Cbc,
+    /// A keystream from an encrypted counter that starts at the IV; no padding
+    Ctr,
 }
 
+impl CipherMode {
+    const ALL: [CipherMode; 3] = [CipherMode::Ecb, CipherMode::Cbc, CipherMode::Ctr];
+
+    fn label(&self) -> &'static str {
+        match self {
+            CipherMode::Ecb => "ECB",
+            CipherMode::Cbc => "CBC",
+            CipherMode::Ctr => "CTR",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Kdf {
+    Sha256,
+    Pbkdf2,
+}
+
+/// How a key or IV field is written
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum KeyFormat {
+    Utf8,
+    Hex,
+    Base64,
+}
+
+impl KeyFormat {
+    const ALL: [KeyFormat; 3] = [KeyFormat::Utf8, KeyFormat::Hex, KeyFormat::Base64];
+
+    fn label(&self) -> &'static str {
+        match self {
+            KeyFormat::Utf8 => "UTF-8",
+            KeyFormat::Hex => "Hex",
+            KeyFormat::Base64 => "Base64",
+        }
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<u8>, String> {
+        match self {
+            KeyFormat::Utf8 => Ok(text.as_bytes().to_vec()),
+            KeyFormat::Hex => {
+                let clean: String = text.split_whitespace().collect();
+                hex::decode(clean).map_err(|e| format!("not valid hex ({})", e))
+            }
+            KeyFormat::Base64 => BASE64_STANDARD
+                .decode(text.trim())
+                .map_err(|e| format!("not valid Base64 ({})", e)),
+        }
+    }
+
+    /// A dropdown to pick the format
+    fn ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        egui::ComboBox::from_id_salt(ui.id().with(id))
+            .width(70.0)
+            .selected_text(self.label())
+            .show_ui(ui, |ui| {
+                for format in KeyFormat::ALL {
+                    ui.selectable_value(self, format, format.label());
+                }
+            });
+    }
+}
+
+/// The bytes of a key or IV field, or a warning to show under it.
+fn parse_field(text: &str, format: KeyFormat, name: &str) -> Result<Vec<u8>, String> {
+    format.parse(text).map_err(|e| format!("{} is {}", name, e))
+}
+
+/// How a symmetric module turns the passphrase into key bytes. Off by
+/// default, which uses the raw passphrase bytes.
+#[derive(Serialize, Deserialize)]
+struct KeyDerivation {
+    derive_key: bool,
+    kdf: Kdf,
+    salt: String,
+    iterations: u32,
+}
+
+/// PBKDF2 runs on the UI thread, so imported settings cannot ask for more
+/// rounds than the slider allows.
+const MAX_ITERATIONS: u32 = 1_000_000;
+
+impl Default for KeyDerivation {
+    fn default() -> Self {
+        Self {
+            derive_key: false,
+            kdf: Kdf::Sha256,
+            salt: String::new(),
+            iterations: 1000,
+        }
+    }
+}
+
+impl KeyDerivation {
+    /// `len` key bytes derived from the passphrase, or the raw passphrase
+    /// bytes when derivation is off.
+    fn key_bytes(&self, passphrase: &[u8], len: usize) -> Vec<u8> {
+        if !self.derive_key {
+            return passphrase.to_vec();
+        }
+        match self.kdf {
+            Kdf::Sha256 => Sha256::digest(passphrase)[..len].to_vec(),
+            Kdf::Pbkdf2 => pbkdf2_sha256(passphrase, self.salt.as_bytes(), self.rounds(), len),
+        }
+    }
+
+    /// The PBKDF2 iteration count, kept within the range the UI offers.
+    fn rounds(&self) -> u32 {
+        self.iterations.clamp(1, MAX_ITERATIONS)
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.derive_key, "Derive key from passphrase")
+            .on_hover_text("Hash the passphrase into a key of the right length");
+        if self.derive_key {
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.kdf, Kdf::Sha256, "SHA-256");
+                ui.radio_value(&mut self.kdf, Kdf::Pbkdf2, "PBKDF2-SHA256");
+            });
+            if self.kdf == Kdf::Pbkdf2 {
+                ui.horizontal(|ui| {
+                    ui.label("Salt:");
+                    ui.text_edit_singleline(&mut self.salt);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Iterations:");
+                    ui.add(egui::DragValue::new(&mut self.iterations).range(1..=MAX_ITERATIONS));
+                });
+            }
+        }
+    }
+}
+
+/// Remove the PKCS#7 padding from decrypted blocks: 1 to 16 bytes that
+/// each hold the padding length. Anything else means a wrong key or IV, or
+/// altered ciphertext.
+fn strip_pkcs7(mut plaintext: Vec<u8>) -> Result<Vec<u8>, ProcessError> {
+    let Some(&last) = plaintext.last() else {
+        return Ok(plaintext);
+    };
+    let padding_len = last as usize;
+    let valid = (1..=16).contains(&padding_len)
+        && padding_len <= plaintext.len()
+        && plaintext[plaintext.len() - padding_len..]
+            .iter()
+            .all(|&b| b == last);
+    if !valid {
+        return Err(ProcessError::InvalidInput(
+            "Invalid padding: the last block does not end in PKCS#7 padding (wrong key or IV, or altered ciphertext?)"
+                .to_string(),
+        ));
+    }
+    plaintext.truncate(plaintext.len() - padding_len);
+    Ok(plaintext)
+}
+
+/// HMAC (RFC 2104) of `data` under `key`. MD5 and SHA-256 both hash in
+/// 64-byte blocks.
+fn hmac(algorithm: HashAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+    let digest = |parts: &[&[u8]]| match algorithm {
+        HashAlgorithm::MD5 => {
+            let mut hasher = Md5::new();
+            parts.iter().for_each(|part| hasher.update(part));
+            hasher.finalize().to_vec()
+        }
+        HashAlgorithm::SHA256 => {
+            let mut hasher = Sha256::new();
+            parts.iter().for_each(|part| hasher.update(part));
+            hasher.finalize().to_vec()
+        }
+    };
+
+    // Keys longer than a block are hashed first
+    let mut key_padded = [0u8; 64];
+    if key.len() <= key_padded.len() {
+        key_padded[..key.len()].copy_from_slice(key);
+    } else {
+        let hashed = digest(&[key]);
+        key_padded[..hashed.len()].copy_from_slice(&hashed);
+    }
+
+    let inner = digest(&[&key_padded.map(|b| b ^ 0x36), data]);
+    digest(&[&key_padded.map(|b| b ^ 0x5c), &inner])
+}
+
+/// PBKDF2 with HMAC-SHA256 (RFC 8018).
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(len);
+    let mut block_index = 1u32;
+    while key.len() < len {
+        let mut u = hmac(
+            HashAlgorithm::SHA256,
+            password,
+            &[salt, &block_index.to_be_bytes()].concat(),
+        );
+        let mut block = u.clone();
+        for _ in 1..iterations {
+            u = hmac(HashAlgorithm::SHA256, password, &u);
+            block.iter_mut().zip(&u).for_each(|(b, x)| *b ^= x);
+        }
+        let needed = (len - key.len()).min(block.len());
+        key.extend_from_slice(&block[..needed]);
+        block_index += 1;
+    }
+    key
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct BlockCipherModule {
-    mode: BlockCipherMode,
+    mode: Direction,
+    cipher_mode: CipherMode,
     key: String,
+    key_format: KeyFormat,
     iv: String,
+    iv_format: KeyFormat,
+    /// The ciphertext starts with the IV: prepended on encrypt, read from
+    /// the first 16 bytes on decrypt (the IV field is then ignored)
+    iv_prefix: bool,
+    /// ECB and CBC pad the plaintext with PKCS#7; without it the plaintext
+    /// must be whole blocks and decryption keeps every byte
+    pkcs7: bool,
+    #[serde(flatten)]
+    key_derivation: KeyDerivation,
+    /// Encrypt with a random IV, always prefixed to the ciphertext
+    random_iv: bool,
+    /// The random IV and a hash of the plaintext and key it was drawn for, so
+    /// the output stays stable until either changes
+    #[serde(skip)]
+    generated_iv: RefCell<Option<(u64, [u8; 16])>>,
 }
 
 impl Default for BlockCipherModule {
     fn default() -> Self {
         Self {
-            mode: BlockCipherMode::Encrypt,
+            mode: Direction::Encode,
+            cipher_mode: CipherMode::Cbc,
             key: "0123456789abcdef".to_string(), // 16 bytes for AES-128
-            iv: "fedcba9876543210".to_string(),  // 16 bytes IV
+            key_format: KeyFormat::Utf8,
+            iv: "fedcba9876543210".to_string(), // 16 bytes IV
+            iv_format: KeyFormat::Utf8,
+            iv_prefix: false,
+            pkcs7: true,
+            key_derivation: KeyDerivation::default(),
+            random_iv: false,
+            generated_iv: RefCell::new(None),
+        }
+    }
+}
+
+impl BlockCipherModule {
+    /// The random IV for this plaintext and key, drawing a new one when
+    /// either has changed since the last call.
+    fn random_iv_for(&self, input: &str) -> [u8; 16] {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        self.key.hash(&mut hasher);
+        let drawn_for = hasher.finish();
+
+        let mut generated = self.generated_iv.borrow_mut();
+        match *generated {
+            Some((hash, iv)) if hash == drawn_for => iv,
+            _ => {
+                let iv: [u8; 16] = rand::random();
+                *generated = Some((drawn_for, iv));
+                iv
+            }
+        }
+    }
+
+    /// The IV settings, which ECB ignores
+    fn iv_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.iv_prefix, "IV prefixed in ciphertext")
+            .on_hover_text(
+                "Encrypt writes the IV before the ciphertext; decrypt reads it from there",
+            );
+        if self.mode == Direction::Encode {
+            ui.checkbox(&mut self.random_iv, "Random IV").on_hover_text(
+                "Draw a fresh IV for each new plaintext or key and prefix it to the ciphertext",
+            );
+        }
+        if self.random_iv && self.mode == Direction::Encode {
+            ui.horizontal(|ui| {
+                let shown = match *self.generated_iv.borrow() {
+                    Some((_, iv)) => hex::encode(iv),
+                    None => "(drawn on the next run)".to_string(),
+                };
+                ui.label(format!("IV: {}", shown));
+                if ui.button("🎲 New IV").clicked() {
+                    self.generated_iv.replace(None);
+                }
+            });
+        } else if !(self.iv_prefix && self.mode == Direction::Decode) {
+            ui.horizontal(|ui| {
+                ui.label("IV (16 bytes):");
+                ui.text_edit_singleline(&mut self.iv);
+                self.iv_format.ui(ui, "iv_format");
+            });
+            if let Err(e) = parse_field(&self.iv, self.iv_format, "IV") {
+                ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+            }
         }
     }
 }
 
 impl Module for BlockCipherModule {
     fn name(&self) -> &str {
-        "Block Cipher (AES-128-CBC)"
+        "Block Cipher (AES-128)"
     }
 
-    fn process(&self, input: &str) -> String {
-        // Ensure key and IV are exactly 16 bytes
-        let mut key_bytes = [0u8; 16];
-        let mut iv_bytes = [0u8; 16];
+    fn id(&self) -> &str {
+        "block_cipher"
+    }
+
+    fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.generated_iv.borrow().hash(&mut hasher);
+        hasher.finish()
+    }
 
-        let key_src = self.key.as_bytes();
-        let iv_src = self.iv.as_bytes();
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(String::from_utf8_lossy(&self.output_bytes(input)?).into_owned())
+    }
+
+    fn binary_output(&self) -> bool {
+        self.mode == Direction::Decode
+    }
+
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
+        let uses_iv = self.cipher_mode != CipherMode::Ecb;
+        let random_iv = uses_iv && self.random_iv && self.mode == Direction::Encode;
+        let iv_field_used =
+            uses_iv && !random_iv && !(self.iv_prefix && self.mode == Direction::Decode);
+
+        let key =
+            parse_field(&self.key, self.key_format, "Key").map_err(ProcessError::BadConfig)?;
+        let key_src = self.key_derivation.key_bytes(&key, 16);
+        let iv_src = if iv_field_used {
+            parse_field(&self.iv, self.iv_format, "IV").map_err(ProcessError::BadConfig)?
+        } else {
+            Vec::new()
+        };
+        // UTF-8 keys and IVs are cut or zero-filled to 16 bytes; binary ones
+        // must be exact
+        if self.key_format != KeyFormat::Utf8 && key_src.len() != 16 {
+            return Err(ProcessError::BadConfig(format!(
+                "The key must be 16 bytes, not {}",
+                key_src.len()
+            )));
+        }
+        if iv_field_used && self.iv_format != KeyFormat::Utf8 && iv_src.len() != 16 {
+            return Err(ProcessError::BadConfig(format!(
+                "The IV must be 16 bytes, not {}",
+                iv_src.len()
+            )));
+        }
 
+        let mut key_bytes = [0u8; 16];
+        let mut iv_bytes = [0u8; 16];
         for i in 0..16 {
             key_bytes[i] = *key_src.get(i).unwrap_or(&0);
             iv_bytes[i] = *iv_src.get(i).unwrap_or(&0);
         }
+        if random_iv {
+            iv_bytes = self.random_iv_for(input);
+        }
 
         match self.mode {
-            BlockCipherMode::Encrypt => {
-                let input_bytes = input.as_bytes();
-                // Pad to multiple of 16 bytes (PKCS7 padding)
-                let padding_len = 16 - (input_bytes.len() % 16);
-                let mut buffer = input_bytes.to_vec();
-                buffer.extend(vec![padding_len as u8; padding_len]);
-
-                // Ensure buffer is large enough
-                let len = buffer.len();
-                buffer.resize(len + 16, 0); // Add extra space for padding
-
-                let cipher = Aes128CbcEnc::new(&key_bytes.into(), &iv_bytes.into());
-                match cipher
-                    .encrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut buffer, len)
-                {
-                    Ok(ciphertext) => hex::encode(ciphertext),
-                    Err(_) => "Encryption error".to_string(),
+            Direction::Encode => {
+                let mut buffer = input.as_bytes().to_vec();
+                if self.cipher_mode != CipherMode::Ctr {
+                    if self.pkcs7 {
+                        // Pad to multiple of 16 bytes (PKCS7 padding)
+                        let padding_len = 16 - (buffer.len() % 16);
+                        buffer.extend(vec![padding_len as u8; padding_len]);
+                    } else if !buffer.len().is_multiple_of(16) {
+                        return Err(ProcessError::InvalidInput(format!(
+                            "Without padding the input must be a whole number of 16-byte blocks, not {} bytes",
+                            buffer.len()
+                        )));
+                    }
+                }
+
+                match self.cipher_mode {
+                    CipherMode::Ecb => {
+                        let cipher = Aes128::new(&key_bytes.into());
+                        for block in buffer.chunks_exact_mut(16) {
+                            cipher.encrypt_block(aes::Block::from_mut_slice(block));
+                        }
+                    }
+                    CipherMode::Cbc => {
+                        let len = buffer.len();
+                        Aes128CbcEnc::new(&key_bytes.into(), &iv_bytes.into())
+                            .encrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(
+                                &mut buffer,
+                                len,
+                            )
+                            .map_err(|_| {
+                                ProcessError::InvalidInput("Encryption failed".to_string())
+                            })?;
+                    }
+                    CipherMode::Ctr => {
+                        Aes128Ctr::new(&key_bytes.into(), &iv_bytes.into())
+                            .apply_keystream(&mut buffer);
+                    }
+                }
+                if uses_iv && (self.iv_prefix || random_iv) {
+                    Ok(hex::encode([&iv_bytes[..], &buffer].concat()).into_bytes())
+                } else {
+                    Ok(hex::encode(buffer).into_bytes())
                 }
             }
-            BlockCipherMode::Decrypt => {
+            Direction::Decode => {
                 // Decode hex input
-                let mut ciphertext = match hex::decode(input.trim()) {
-                    Ok(ct) => ct,
-                    Err(_) => return "Invalid hex input".to_string(),
-                };
+                let mut ciphertext = hex::decode(input.trim())
+                    .map_err(|_| ProcessError::InvalidInput("Invalid hex input".to_string()))?;
+                if uses_iv && self.iv_prefix {
+                    if ciphertext.len() < 16 {
+                        return Err(ProcessError::InvalidInput(
+                            "Input is too short to start with a 16-byte IV".to_string(),
+                        ));
+                    }
+                    iv_bytes.copy_from_slice(&ciphertext[..16]);
+                    ciphertext.drain(..16);
+                }
+                if self.cipher_mode != CipherMode::Ctr && !ciphertext.len().is_multiple_of(16) {
+                    return Err(ProcessError::InvalidInput(
+                        "Decryption failed: the ciphertext is not a whole number of 16-byte blocks"
+                            .to_string(),
+                    ));
+                }
 
-                let cipher = Aes128CbcDec::new(&key_bytes.into(), &iv_bytes.into());
-                match cipher
-                    .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(&mut ciphertext)
-                {
-                    Ok(plaintext) => {
-                        // Remove PKCS7 padding
-                        let mut pt = plaintext.to_vec();
-                        if let Some(&padding_len) = pt.last() {
-                            if padding_len > 0 && padding_len <= 16 {
-                                let new_len = pt.len().saturating_sub(padding_len as usize);
-                                pt.truncate(new_len);
-                            }
+                match self.cipher_mode {
+                    CipherMode::Ecb => {
+                        let cipher = Aes128::new(&key_bytes.into());
+                        for block in ciphertext.chunks_exact_mut(16) {
+                            cipher.decrypt_block(aes::Block::from_mut_slice(block));
                         }
-                        String::from_utf8_lossy(&pt).to_string()
                     }
-                    Err(_) => "Decryption error".to_string(),
+                    CipherMode::Cbc => {
+                        Aes128CbcDec::new(&key_bytes.into(), &iv_bytes.into())
+                            .decrypt_padded_mut::<cbc::cipher::block_padding::NoPadding>(
+                                &mut ciphertext,
+                            )
+                            .map_err(|_| {
+                                ProcessError::InvalidInput("Decryption failed".to_string())
+                            })?;
+                    }
+                    CipherMode::Ctr => {
+                        Aes128Ctr::new(&key_bytes.into(), &iv_bytes.into())
+                            .apply_keystream(&mut ciphertext);
+                        return Ok(ciphertext);
+                    }
+                }
+                if self.pkcs7 {
+                    strip_pkcs7(ciphertext)
+                } else {
+                    Ok(ciphertext)
                 }
             }
         }
     }
 
+    fn inverse(&self) -> Option<Inverse> {
+        let uses_iv = self.cipher_mode != CipherMode::Ecb;
+        match self.mode {
+            // A random IV can only be read back from the prefix
+            Direction::Encode => Some(Inverse::changed(
+                self,
+                serde_json::json!({
+                    "mode": Direction::Decode,
+                    "iv_prefix": self.iv_prefix || (uses_iv && self.random_iv),
+                }),
+            )),
+            // Encrypting would prefix the IV field, not the IV that was read
+            Direction::Decode if uses_iv && self.iv_prefix => None,
+            Direction::Decode => Some(Inverse::changed(
+                self,
+                serde_json::json!({ "mode": Direction::Encode, "random_iv": false }),
+            )),
+        }
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, BlockCipherMode::Encrypt, "Encrypt");
-            ui.radio_value(&mut self.mode, BlockCipherMode::Decrypt, "Decrypt");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encrypt");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decrypt");
         });
         ui.horizontal(|ui| {
-            ui.label("Key (16 bytes):");
-            ui.text_edit_singleline(&mut self.key);
+            ui.label("Mode:");
+            for cipher_mode in CipherMode::ALL {
+                ui.radio_value(&mut self.cipher_mode, cipher_mode, cipher_mode.label());
+            }
         });
         ui.horizontal(|ui| {
-            ui.label("IV (16 bytes):");
-            ui.text_edit_singleline(&mut self.iv);
+            let label = if self.key_derivation.derive_key {
+                "Passphrase:"
+            } else {
+                "Key (16 bytes):"
+            };
+            ui.label(label);
+            ui.text_edit_singleline(&mut self.key);
+            self.key_format.ui(ui, "key_format");
         });
+        if let Err(e) = parse_field(&self.key, self.key_format, "Key") {
+            ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+        }
+        self.key_derivation.ui(ui);
+        if self.cipher_mode != CipherMode::Ctr {
+            ui.checkbox(&mut self.pkcs7, "PKCS#7 padding")
+                .on_hover_text(
+                "Pad the plaintext to whole blocks, and check and remove the padding on decrypt",
+            );
+        }
+        // ECB has no IV
+        ui.add_enabled_ui(self.cipher_mode != CipherMode::Ecb, |ui| self.iv_ui(ui));
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -121,29 +551,35 @@ impl Module for BlockCipherModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
-enum RC4Mode {
-    Encrypt,
-    Decrypt,
-}
-
+#[derive(Serialize, Deserialize)]
 pub struct RC4Module {
-    mode: RC4Mode,
+    mode: Direction,
     key: String,
+    key_format: KeyFormat,
+    #[serde(flatten)]
+    key_derivation: KeyDerivation,
 }
 
 impl Default for RC4Module {
     fn default() -> Self {
         Self {
-            mode: RC4Mode::Encrypt,
+            mode: Direction::Encode,
             key: "secret".to_string(),
+            key_format: KeyFormat::Utf8,
+            key_derivation: KeyDerivation::default(),
         }
     }
 }
 
 impl RC4Module {
-    fn rc4_keystream(&self, length: usize) -> Vec<u8> {
-        let key_bytes = self.key.as_bytes();
+    fn rc4_keystream(&self, length: usize) -> Result<Vec<u8>, ProcessError> {
+        // Derived RC4 keys are 128-bit
+        let key =
+            parse_field(&self.key, self.key_format, "Key").map_err(ProcessError::BadConfig)?;
+        let key_bytes = self.key_derivation.key_bytes(&key, 16);
+        if key_bytes.is_empty() {
+            return Err(ProcessError::BadConfig("Key cannot be empty".to_string()));
+        }
         let mut s: Vec<u8> = (0..=255).collect();
 
         // KSA (Key Scheduling Algorithm)
@@ -168,7 +604,7 @@ impl RC4Module {
             keystream.push(k);
         }
 
-        keystream
+        Ok(keystream)
     }
 }
 
@@ -177,45 +613,71 @@ impl Module for RC4Module {
         "RC4"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
-            RC4Mode::Encrypt => {
+    fn id(&self) -> &str {
+        "rc4"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(String::from_utf8_lossy(&self.output_bytes(input)?).into_owned())
+    }
+
+    fn binary_output(&self) -> bool {
+        self.mode == Direction::Decode
+    }
+
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
+        Ok(match self.mode {
+            Direction::Encode => {
                 let input_bytes = input.as_bytes();
-                let keystream = self.rc4_keystream(input_bytes.len());
+                let keystream = self.rc4_keystream(input_bytes.len())?;
                 let ciphertext: Vec<u8> = input_bytes
                     .iter()
                     .zip(keystream.iter())
                     .map(|(a, b)| a ^ b)
                     .collect();
-                hex::encode(ciphertext)
+                hex::encode(ciphertext).into_bytes()
             }
-            RC4Mode::Decrypt => {
+            Direction::Decode => {
                 // Decode hex input
-                let ciphertext = match hex::decode(input.trim()) {
-                    Ok(ct) => ct,
-                    Err(_) => return "Invalid hex input".to_string(),
-                };
+                let ciphertext = hex::decode(input.trim())
+                    .map_err(|_| ProcessError::InvalidInput("Invalid hex input".to_string()))?;
 
-                let keystream = self.rc4_keystream(ciphertext.len());
-                let plaintext: Vec<u8> = ciphertext
+                let keystream = self.rc4_keystream(ciphertext.len())?;
+                ciphertext
                     .iter()
                     .zip(keystream.iter())
                     .map(|(a, b)| a ^ b)
-                    .collect();
-                String::from_utf8_lossy(&plaintext).to_string()
+                    .collect()
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, RC4Mode::Encrypt, "Encrypt");
-            ui.radio_value(&mut self.mode, RC4Mode::Decrypt, "Decrypt");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encrypt");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decrypt");
         });
         ui.horizontal(|ui| {
-            ui.label("Key:");
+            let label = if self.key_derivation.derive_key {
+                "Passphrase:"
+            } else {
+                "Key:"
+            };
+            ui.label(label);
             ui.text_edit_singleline(&mut self.key);
+            self.key_format.ui(ui, "key_format");
         });
+        if let Err(e) = parse_field(&self.key, self.key_format, "Key") {
+            ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+        }
+        self.key_derivation.ui(ui);
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -227,20 +689,68 @@ impl Module for RC4Module {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 enum HashAlgorithm {
     MD5,
     SHA256,
 }
 
+/// Digests and checksums the hash module offers; HMAC keeps to `HashAlgorithm`
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum HashFunction {
+    MD5,
+    SHA256,
+    CRC32,
+    Adler32,
+}
+
+/// Lookup table for the reflected CRC-32 polynomial 0xEDB88320
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// The CRC-32 used by zip, gzip and PNG
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The Adler-32 checksum used by zlib
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % MOD_ADLER;
+        (a, (b + a) % MOD_ADLER)
+    });
+    (b << 16) | a
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct HashFunctionModule {
-    algorithm: HashAlgorithm,
+    algorithm: HashFunction,
 }
 
 impl Default for HashFunctionModule {
     fn default() -> Self {
         Self {
-            algorithm: HashAlgorithm::SHA256,
+            algorithm: HashFunction::SHA256,
         }
     }
 }
@@ -250,26 +760,35 @@ impl Module for HashFunctionModule {
         "Hash Function"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.algorithm {
-            HashAlgorithm::MD5 => {
+    fn id(&self) -> &str {
+        "hash"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.algorithm {
+            HashFunction::MD5 => {
                 let mut hasher = Md5::new();
                 hasher.update(input.as_bytes());
                 format!("{:x}", hasher.finalize())
             }
-            HashAlgorithm::SHA256 => {
+            HashFunction::SHA256 => {
                 let mut hasher = Sha256::new();
                 hasher.update(input.as_bytes());
                 format!("{:x}", hasher.finalize())
             }
-        }
+            // Eight hex digits, as `unzip -v` and PNG tools print them
+            HashFunction::CRC32 => format!("{:08x}", crc32(input.as_bytes())),
+            HashFunction::Adler32 => format!("{:08x}", adler32(input.as_bytes())),
+        })
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
             ui.label("Algorithm:");
-            ui.radio_value(&mut self.algorithm, HashAlgorithm::MD5, "MD5");
-            ui.radio_value(&mut self.algorithm, HashAlgorithm::SHA256, "SHA256");
+            ui.radio_value(&mut self.algorithm, HashFunction::MD5, "MD5");
+            ui.radio_value(&mut self.algorithm, HashFunction::SHA256, "SHA256");
+            ui.radio_value(&mut self.algorithm, HashFunction::CRC32, "CRC32");
+            ui.radio_value(&mut self.algorithm, HashFunction::Adler32, "Adler-32");
         });
     }
 
@@ -282,6 +801,7 @@ impl Module for HashFunctionModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct HMACModule {
     key: String,
     algorithm: HashAlgorithm,
@@ -301,73 +821,16 @@ impl Module for HMACModule {
         "HMAC"
     }
 
-    fn process(&self, input: &str) -> String {
-        // Simple HMAC implementation
-        let key_bytes = self.key.as_bytes();
-        let block_size = 64; // For both MD5 and SHA256
-
-        let mut key_padded = vec![0u8; block_size];
-        if key_bytes.len() <= block_size {
-            key_padded[..key_bytes.len()].copy_from_slice(key_bytes);
-        } else {
-            // Hash the key if it's too long
-            match self.algorithm {
-                HashAlgorithm::MD5 => {
-                    let mut hasher = Md5::new();
-                    hasher.update(key_bytes);
-                    let result = hasher.finalize();
-                    key_padded[..result.len()].copy_from_slice(&result);
-                }
-                HashAlgorithm::SHA256 => {
-                    let mut hasher = Sha256::new();
-                    hasher.update(key_bytes);
-                    let result = hasher.finalize();
-                    key_padded[..result.len()].copy_from_slice(&result);
-                }
-            }
-        }
-
-        let mut o_key_pad = vec![0x5c; block_size];
-        let mut i_key_pad = vec![0x36; block_size];
-
-        for i in 0..block_size {
-            o_key_pad[i] ^= key_padded[i];
-            i_key_pad[i] ^= key_padded[i];
-        }
-
-        // Inner hash
-        let mut inner_data = i_key_pad;
-        inner_data.extend_from_slice(input.as_bytes());
-
-        let inner_hash = match self.algorithm {
-            HashAlgorithm::MD5 => {
-                let mut hasher = Md5::new();
-                hasher.update(&inner_data);
-                hasher.finalize().to_vec()
-            }
-            HashAlgorithm::SHA256 => {
-                let mut hasher = Sha256::new();
-                hasher.update(&inner_data);
-                hasher.finalize().to_vec()
-            }
-        };
-
-        // Outer hash
-        let mut outer_data = o_key_pad;
-        outer_data.extend_from_slice(&inner_hash);
+    fn id(&self) -> &str {
+        "hmac"
+    }
 
-        match self.algorithm {
-            HashAlgorithm::MD5 => {
-                let mut hasher = Md5::new();
-                hasher.update(&outer_data);
-                format!("{:x}", hasher.finalize())
-            }
-            HashAlgorithm::SHA256 => {
-                let mut hasher = Sha256::new();
-                hasher.update(&outer_data);
-                format!("{:x}", hasher.finalize())
-            }
-        }
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(hex::encode(hmac(
+            self.algorithm,
+            self.key.as_bytes(),
+            input.as_bytes(),
+        )))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -390,3 +853,251 @@ impl Module for HMACModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iv_prefix_round_trip() {
+        let encrypt = BlockCipherModule {
+            iv_prefix: true,
+            ..Default::default()
+        };
+        let ciphertext = encrypt.process("Attack at dawn, not at dusk").unwrap();
+        assert!(ciphertext.starts_with(&hex::encode("fedcba9876543210")));
+        assert_eq!(ciphertext.len(), 2 * (16 + 32));
+
+        // The IV field is ignored when decrypting a prefixed ciphertext
+        let decrypt = BlockCipherModule {
+            mode: Direction::Decode,
+            iv_prefix: true,
+            iv: "a different iv!!".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            decrypt.process(&ciphertext).unwrap(),
+            "Attack at dawn, not at dusk"
+        );
+        assert!(decrypt.process(&ciphertext[..30]).is_err());
+    }
+
+    #[test]
+    fn test_random_iv_encryptions_differ() {
+        let encrypt = BlockCipherModule {
+            random_iv: true,
+            ..Default::default()
+        };
+        let first = encrypt.process("same plaintext").unwrap();
+        // Redrawing the pipeline reuses the IV until the input or key changes
+        assert_eq!(encrypt.process("same plaintext").unwrap(), first);
+        encrypt.generated_iv.replace(None);
+        let second = encrypt.process("same plaintext").unwrap();
+        assert_ne!(first, second);
+
+        let decrypt = BlockCipherModule {
+            mode: Direction::Decode,
+            iv_prefix: true,
+            ..Default::default()
+        };
+        assert_eq!(decrypt.process(&first).unwrap(), "same plaintext");
+        assert_eq!(decrypt.process(&second).unwrap(), "same plaintext");
+    }
+
+    #[test]
+    fn test_passphrase_derives_expected_key() {
+        let mut derivation = KeyDerivation {
+            derive_key: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            hex::encode(derivation.key_bytes(b"password", 16)),
+            "5e884898da28047151d0e56f8dc62927"
+        );
+        derivation.kdf = Kdf::Pbkdf2;
+        derivation.salt = "salt".to_string();
+        assert_eq!(
+            hex::encode(derivation.key_bytes(b"password", 16)),
+            "632c2812e46d4604102ba7618e9d6d7d"
+        );
+        // RFC 7914 section 11, which needs two HMAC blocks
+        assert_eq!(
+            hex::encode(pbkdf2_sha256(b"passwd", b"salt", 1, 64)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        derivation.derive_key = false;
+        assert_eq!(derivation.key_bytes(b"password", 16), b"password");
+    }
+
+    #[test]
+    fn test_hmac_matches_rfc_vectors() {
+        // RFC 2104 and RFC 4231 test case 2
+        let mut module = HMACModule {
+            key: "Jefe".to_string(),
+            algorithm: HashAlgorithm::MD5,
+        };
+        let data = "what do ya want for nothing?";
+        assert_eq!(
+            module.process(data).unwrap(),
+            "750c783e6ab0b503eaa86e310a5db738"
+        );
+        module.algorithm = HashAlgorithm::SHA256;
+        assert_eq!(
+            module.process(data).unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // RFC 4231 test case 6, a key longer than the block
+        assert_eq!(
+            hex::encode(hmac(
+                HashAlgorithm::SHA256,
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_imported_iterations_are_capped() {
+        let mut derivation: KeyDerivation = serde_json::from_value(serde_json::json!({
+            "derive_key": true,
+            "kdf": "Pbkdf2",
+            "salt": "",
+            "iterations": u32::MAX,
+        }))
+        .unwrap();
+        assert_eq!(derivation.rounds(), MAX_ITERATIONS);
+        derivation.iterations = 0;
+        assert_eq!(derivation.rounds(), 1);
+    }
+
+    #[test]
+    fn test_ecb_repeats_identical_blocks() {
+        let plaintext = "YELLOW SUBMARINEYELLOW SUBMARINE";
+        let blocks = |cipher_mode| {
+            let ciphertext = BlockCipherModule {
+                cipher_mode,
+                ..Default::default()
+            }
+            .process(plaintext)
+            .unwrap();
+            // Two plaintext blocks and a block of padding
+            assert_eq!(ciphertext.len(), 2 * 48);
+            (ciphertext[..32].to_string(), ciphertext[32..64].to_string())
+        };
+        let (first, second) = blocks(CipherMode::Ecb);
+        assert_eq!(first, second);
+        let (first, second) = blocks(CipherMode::Cbc);
+        assert_ne!(first, second);
+
+        // CTR is a stream cipher, so nothing is padded
+        let ctr = BlockCipherModule {
+            cipher_mode: CipherMode::Ctr,
+            ..Default::default()
+        };
+        let ciphertext = ctr.process("odd length").unwrap();
+        assert_eq!(ciphertext.len(), 2 * "odd length".len());
+        let decrypt = BlockCipherModule {
+            mode: Direction::Decode,
+            cipher_mode: CipherMode::Ctr,
+            ..Default::default()
+        };
+        assert_eq!(decrypt.process(&ciphertext).unwrap(), "odd length");
+    }
+
+    #[test]
+    fn test_key_format_parsing() {
+        assert_eq!(KeyFormat::Utf8.parse("Key").unwrap(), b"Key");
+        assert_eq!(KeyFormat::Hex.parse("4b 65 79\n").unwrap(), b"Key");
+        assert_eq!(KeyFormat::Hex.parse("4B6579").unwrap(), b"Key");
+        assert_eq!(KeyFormat::Base64.parse(" S2V5 ").unwrap(), b"Key");
+        assert!(KeyFormat::Hex.parse("4b657").is_err());
+        assert!(KeyFormat::Hex.parse("zz").is_err());
+        assert!(KeyFormat::Base64.parse("S2V5!").is_err());
+
+        // The RC4 vector for key "Key", however the key is written
+        for (key, key_format) in [
+            ("Key", KeyFormat::Utf8),
+            ("4b6579", KeyFormat::Hex),
+            ("S2V5", KeyFormat::Base64),
+        ] {
+            let rc4 = RC4Module {
+                key: key.to_string(),
+                key_format,
+                ..Default::default()
+            };
+            assert_eq!(rc4.process("Plaintext").unwrap(), "bbf316e8d940af0ad3");
+        }
+        let bad_key = RC4Module {
+            key: "not hex".to_string(),
+            key_format: KeyFormat::Hex,
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad_key.process("Plaintext"),
+            Err(ProcessError::BadConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_pkcs7_padding_validation() {
+        let mut valid = b"YELLOW SUBMARINE".to_vec();
+        valid.extend([4; 4]);
+        assert_eq!(strip_pkcs7(valid).unwrap(), b"YELLOW SUBMARINE");
+        assert_eq!(strip_pkcs7(vec![16; 16]).unwrap(), b"");
+        for invalid in [
+            [&b"YELLOW SUBMARINE"[..], &[1, 2, 3, 4]].concat(),
+            [&b"YELLOW SUBMARINE"[..], &[5, 5, 4, 4]].concat(),
+            [&b"YELLOW SUBMARINE"[..], &[0]].concat(),
+            vec![17; 17],
+        ] {
+            assert!(matches!(
+                strip_pkcs7(invalid),
+                Err(ProcessError::InvalidInput(_))
+            ));
+        }
+
+        let padded = BlockCipherModule::default().process("attack").unwrap();
+        let decrypt = |pkcs7, ciphertext: &str| {
+            BlockCipherModule {
+                mode: Direction::Decode,
+                pkcs7,
+                ..Default::default()
+            }
+            .output_bytes(ciphertext)
+        };
+        assert_eq!(decrypt(true, &padded).unwrap(), b"attack");
+        // Without PKCS#7 the padding is kept as it is
+        let mut kept = b"attack".to_vec();
+        kept.extend([10; 10]);
+        assert_eq!(decrypt(false, &padded).unwrap(), kept);
+
+        // Unpadded ciphertext does not end in valid padding
+        let unpadded = BlockCipherModule {
+            pkcs7: false,
+            ..Default::default()
+        };
+        assert!(unpadded.process("attack").is_err());
+        let ciphertext = unpadded.process("YELLOW SUBMARINE").unwrap();
+        assert_eq!(ciphertext.len(), 32);
+        assert_eq!(decrypt(false, &ciphertext).unwrap(), b"YELLOW SUBMARINE");
+        assert!(decrypt(true, &ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_crc32_and_adler32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b""), 1);
+
+        let checksum = |algorithm| {
+            HashFunctionModule { algorithm }
+                .process("123456789")
+                .unwrap()
+        };
+        assert_eq!(checksum(HashFunction::CRC32), "cbf43926");
+        assert_eq!(checksum(HashFunction::Adler32), "091e01de");
+    }
+}
@@ -1,16 +1,61 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
+use crate::modules::analysis::word_score;
+use crate::modules::cipher::{
+    columnar_columns, columnar_decrypt, keyed_alphabet, KeyFill, LATIN_ALPHABET,
+};
+use crate::modules::transform::split_tokens;
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Lowercase the n-th character of `text` wherever `lowercase[n]` is set.
+/// The fractionating ciphers produce one output letter per input letter,
+/// so carrying the case pattern over by position survives a round trip.
+fn apply_case_pattern(text: &str, lowercase: &[bool]) -> String {
+    text.chars()
+        .zip(lowercase.iter().chain(std::iter::repeat(&false)))
+        .map(|(c, &lower)| if lower { c.to_ascii_lowercase() } else { c })
+        .collect()
+}
+
+/// How decoding a 5×5 square writes the cell I and J share
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum MergedCell {
+    /// The letter in the square, usually I
+    AsIs,
+    /// "I/J", so the merge is visible
+    Annotate,
+    /// I or J, whichever makes more English words around it
+    Restore,
+}
 
-#[derive(PartialEq, Clone, Copy)]
-pub enum PolybiusMode {
-    Encode,
-    Decode,
+/// Where the Polybius square's letters come from
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum SquareSource {
+    /// Keyword first, then the rest of the alphabet
+    Key,
+    /// A complete square typed or pasted row by row
+    Grid,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct PolybiusSquareModule {
     key: String,
     size: usize, // 5 for 5x5, 6 for 6x6
-    pub mode: PolybiusMode,
+    source: SquareSource,
+    /// Rows separated by newlines; spaces between cells are optional
+    grid: String,
+    pub mode: Direction,
+    /// Decode to lowercase letters instead of the grid's uppercase
+    lowercase_output: bool,
+    /// Decoding output for the shared I/J cell of a 5×5 square
+    merged_cell: MergedCell,
+    /// Coordinates are written column first instead of row first
+    column_first: bool,
+    /// Rows and columns are numbered from 0 instead of 1
+    zero_based: bool,
+    /// Encode to nothing but space-separated coordinate pairs, and only
+    /// accept such pairs when decoding
+    coordinates_only: bool,
 }
 
 impl Default for PolybiusSquareModule {
@@ -18,7 +63,14 @@ impl Default for PolybiusSquareModule {
         Self {
             key: String::new(),
             size: 5,
-            mode: PolybiusMode::Encode,
+            source: SquareSource::Key,
+            grid: String::new(),
+            mode: Direction::Encode,
+            lowercase_output: false,
+            merged_cell: MergedCell::AsIs,
+            column_first: false,
+            zero_based: false,
+            coordinates_only: false,
         }
     }
 }
@@ -28,17 +80,30 @@ impl Module for PolybiusSquareModule {
         "Polybius Square"
     }
 
-    fn process(&self, input: &str) -> String {
-        let square = self.generate_square();
+    fn id(&self) -> &str {
+        "polybius"
+    }
 
-        match self.mode {
-            PolybiusMode::Encode => {
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        check_square_size(self.size)?;
+        let square = match self.source {
+            SquareSource::Key => self.generate_square(),
+            SquareSource::Grid => self.parse_grid().map_err(ProcessError::BadConfig)?,
+        };
+
+        Ok(match self.mode {
+            Direction::Encode if self.coordinates_only => input
+                .to_uppercase()
+                .chars()
+                .filter_map(|c| self.find_in_square(&square, c))
+                .map(|pos| self.coordinate(pos))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Direction::Encode => {
                 let mut result = String::new();
                 for c in input.to_uppercase().chars() {
                     if let Some(pos) = self.find_in_square(&square, c) {
-                        let row = pos / self.size;
-                        let col = pos % self.size;
-                        result.push_str(&format!("{}{}", row + 1, col + 1));
+                        result.push_str(&self.coordinate(pos));
                         result.push(' ');
                     } else {
                         result.push(c);
@@ -46,49 +111,121 @@ impl Module for PolybiusSquareModule {
                 }
                 result
             }
-            PolybiusMode::Decode => {
+            Direction::Decode => {
                 let mut result = String::new();
-                let digits: Vec<char> = input.chars().filter(|c| c.is_ascii_digit()).collect();
+                let digits: Vec<u32> = if self.coordinates_only {
+                    self.parse_coordinates(input)
+                        .map_err(ProcessError::InvalidInput)?
+                } else {
+                    input.chars().filter_map(|c| c.to_digit(10)).collect()
+                };
 
-                for pair in digits.chunks(2) {
-                    if pair.len() == 2 {
-                        if let (Some(r), Some(c)) = (pair[0].to_digit(10), pair[1].to_digit(10)) {
-                            let row = r as usize;
-                            let col = c as usize;
-                            if row > 0 && col > 0 && row <= self.size && col <= self.size {
-                                let pos = (row - 1) * self.size + (col - 1);
-                                if pos < square.len() {
-                                    result.push(square[pos]);
-                                }
-                            }
-                        }
+                for pair in digits.chunks_exact(2) {
+                    if let Some(pos) = self.position(pair[0], pair[1]) {
+                        result.push(square[pos]);
+                    } else if self.coordinates_only {
+                        return Err(ProcessError::InvalidInput(format!(
+                            "Coordinate {}{} is outside the {}×{} square",
+                            pair[0], pair[1], self.size, self.size
+                        )));
                     }
                 }
-                result
+                if self.size == 5 {
+                    result = match self.merged_cell {
+                        MergedCell::AsIs => result,
+                        MergedCell::Annotate => result
+                            .chars()
+                            .map(|c| match c {
+                                'I' | 'J' => "I/J".to_string(),
+                                _ => c.to_string(),
+                            })
+                            .collect(),
+                        MergedCell::Restore => restore_i_j(&result),
+                    };
+                }
+                if self.lowercase_output {
+                    result.to_lowercase()
+                } else {
+                    result
+                }
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Grid Size:");
             ui.radio_value(&mut self.size, 5, "5×5 (I/J merged)");
             ui.radio_value(&mut self.size, 6, "6×6 (with digits)");
         });
+        if self.mode == Direction::Decode {
+            ui.checkbox(&mut self.lowercase_output, "Lowercase output");
+            if self.size == 5 {
+                ui.horizontal(|ui| {
+                    ui.label("I/J cell:");
+                    ui.radio_value(&mut self.merged_cell, MergedCell::AsIs, "As in square")
+                        .on_hover_text(
+                            "I and J share a cell, so a J in the plaintext decodes as I",
+                        );
+                    ui.radio_value(&mut self.merged_cell, MergedCell::Annotate, "Show I/J");
+                    ui.radio_value(&mut self.merged_cell, MergedCell::Restore, "Guess")
+                        .on_hover_text("Use J wherever it makes more English words than I");
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Coordinates:");
+            ui.radio_value(&mut self.column_first, false, "Row, column");
+            ui.radio_value(&mut self.column_first, true, "Column, row");
+            ui.checkbox(&mut self.zero_based, "Start at 0");
+        });
+        ui.checkbox(&mut self.coordinates_only, "Coordinates only")
+            .on_hover_text(
+                "Encode to space-separated coordinate pairs alone, dropping other characters; \
+             decode only accepts such pairs",
+            );
 
         ui.horizontal(|ui| {
-            ui.label("Custom Key:");
-            ui.text_edit_singleline(&mut self.key);
-            if ui.button("Clear").clicked() {
-                self.key.clear();
-            }
+            ui.label("Square from:");
+            ui.radio_value(&mut self.source, SquareSource::Key, "Key");
+            ui.radio_value(&mut self.source, SquareSource::Grid, "Pasted grid");
         });
 
-        ui.label("Leave key empty for standard alphabetical order");
+        match self.source {
+            SquareSource::Key => {
+                ui.horizontal(|ui| {
+                    ui.label("Custom Key:");
+                    ui.text_edit_singleline(&mut self.key);
+                    if ui.button("Clear").clicked() {
+                        self.key.clear();
+                    }
+                });
+
+                ui.label("Leave key empty for standard alphabetical order");
+            }
+            SquareSource::Grid => {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.grid)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_rows(self.size)
+                        .hint_text("One row per line, e.g.\nPHQGM\nEAYLN\n..."),
+                );
+                if let Err(e) = self.parse_grid() {
+                    ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+                }
+            }
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -100,65 +237,163 @@ impl Module for PolybiusSquareModule {
     }
 }
 
+/// Check a square side from the settings: 5×5 and 6×6 are the only squares
+/// there are alphabets for.
+fn check_square_size(size: usize) -> Result<(), ProcessError> {
+    if size == 5 || size == 6 {
+        Ok(())
+    } else {
+        Err(ProcessError::BadConfig(format!(
+            "The square must be 5×5 or 6×6, not {}×{}",
+            size, size
+        )))
+    }
+}
+
+/// Letters around a merged I/J cell that `restore_i_j` looks at
+const RESTORE_WINDOW: usize = 12;
+
+/// Turn each I or J of uppercase `text` into whichever of the two gives the
+/// surrounding letters the better English word score.
+fn restore_i_j(text: &str) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    for i in 0..chars.len() {
+        if chars[i] != 'I' && chars[i] != 'J' {
+            continue;
+        }
+        let start = i.saturating_sub(RESTORE_WINDOW);
+        let end = (i + RESTORE_WINDOW + 1).min(chars.len());
+        let score = |letter: char, chars: &mut Vec<char>| {
+            chars[i] = letter;
+            word_score(&chars[start..end].iter().collect::<String>())
+        };
+        let as_i = score('I', &mut chars);
+        let as_j = score('J', &mut chars);
+        chars[i] = if as_j > as_i { 'J' } else { 'I' };
+    }
+    chars.into_iter().collect()
+}
+
 impl PolybiusSquareModule {
     /// Generate the Polybius square based on key and size
     fn generate_square(&self) -> Vec<char> {
-        let mut square = Vec::new();
+        let alphabet = if self.size == 5 {
+            // 5x5: A-Z with I/J merged (25 cells)
+            LATIN_ALPHABET.replace('J', "")
+        } else {
+            // 6x6: A-Z + 0-9 (36 cells)
+            format!("{}0123456789", LATIN_ALPHABET)
+        };
+        keyed_alphabet(&self.key, &alphabet, KeyFill::Forward, self.size == 5)
+            .chars()
+            .collect()
+    }
+
+    /// Read a pasted square: `size` rows of `size` distinct cells each.
+    fn parse_grid(&self) -> Result<Vec<char>, String> {
+        let rows: Vec<Vec<char>> = self
+            .grid
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+        if rows.len() != self.size {
+            return Err(format!(
+                "The grid has {} rows, expected {}",
+                rows.len(),
+                self.size
+            ));
+        }
+        if let Some((idx, row)) = rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.len() != self.size)
+        {
+            return Err(format!(
+                "Row {} has {} cells, expected {}",
+                idx + 1,
+                row.len(),
+                self.size
+            ));
+        }
+
+        let square: Vec<char> = rows.concat();
         let mut seen = std::collections::HashSet::new();
+        if let Some(c) = square.iter().find(|&&c| !seen.insert(c)) {
+            return Err(format!("'{}' appears more than once", c));
+        }
+        Ok(square)
+    }
 
-        // Add characters from key first (deduplicated)
-        for c in self.key.to_uppercase().chars() {
-            if c.is_ascii_alphanumeric() && !seen.contains(&c) {
-                let normalized = if self.size == 5 && c == 'J' { 'I' } else { c };
-                if !seen.contains(&normalized) {
-                    square.push(normalized);
-                    seen.insert(normalized);
-                }
-            }
+    /// The two-digit coordinate of a square position, in the configured
+    /// order and numbering.
+    fn coordinate(&self, pos: usize) -> String {
+        let offset = if self.zero_based { 0 } else { 1 };
+        let (row, col) = (pos / self.size + offset, pos % self.size + offset);
+        if self.column_first {
+            format!("{}{}", col, row)
+        } else {
+            format!("{}{}", row, col)
         }
+    }
 
-        // Fill remaining with alphabet (and digits for 6x6)
-        if self.size == 5 {
-            // 5x5: A-Z with I/J merged (25 cells)
-            for c in 'A'..='Z' {
-                if c == 'J' {
-                    continue;
-                } // Skip J, use I instead
-                if !seen.contains(&c) {
-                    square.push(c);
-                    seen.insert(c);
-                }
-            }
+    /// The square position of a coordinate, if it lies inside the square.
+    fn position(&self, first: u32, second: u32) -> Option<usize> {
+        let (row, col) = if self.column_first {
+            (second, first)
         } else {
-            // 6x6: A-Z + 0-9 (36 cells)
-            for c in 'A'..='Z' {
-                if !seen.contains(&c) {
-                    square.push(c);
-                    seen.insert(c);
-                }
-            }
-            for c in '0'..='9' {
-                if !seen.contains(&c) {
-                    square.push(c);
-                    seen.insert(c);
-                }
+            (first, second)
+        };
+        let offset = if self.zero_based { 0 } else { 1 };
+        let row = (row as usize).checked_sub(offset)?;
+        let col = (col as usize).checked_sub(offset)?;
+        (row < self.size && col < self.size).then_some(row * self.size + col)
+    }
+
+    /// Digits of coordinate-only input: pairs of digits, optionally separated
+    /// by whitespace or a punctuation character.
+    fn parse_coordinates(&self, input: &str) -> Result<Vec<u32>, String> {
+        let mut digits = Vec::new();
+        for c in split_tokens(input).into_iter().flat_map(str::chars) {
+            match c.to_digit(10) {
+                Some(digit) => digits.push(digit),
+                None => return Err(format!("'{}' is not part of a coordinate", c)),
             }
         }
-
-        square
+        if !digits.len().is_multiple_of(2) {
+            return Err("Coordinates must be pairs of digits".to_string());
+        }
+        Ok(digits)
     }
 
-    /// Find the position of a character in the square
+    /// Find the position of a character in the square. In a 5×5 square I and
+    /// J share a cell, whichever of the two the square contains.
     fn find_in_square(&self, square: &[char], c: char) -> Option<usize> {
-        let search_char = if self.size == 5 && c == 'J' { 'I' } else { c };
-        square.iter().position(|&ch| ch == search_char)
+        square.iter().position(|&ch| ch == c).or_else(|| {
+            let other = match c {
+                'I' if self.size == 5 => 'J',
+                'J' if self.size == 5 => 'I',
+                _ => return None,
+            };
+            square.iter().position(|&ch| ch == other)
+        })
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct ADFGXCipherModule {
     polybius_key: String,
     transposition_key: String,
-    mode: PolybiusMode,
+    /// Row/column labels, one per row of the square ("ADFGX" historically)
+    headers: String,
+    mode: Direction,
+    /// Decode to lowercase letters instead of the grid's uppercase
+    lowercase_output: bool,
 }
 
 impl Default for ADFGXCipherModule {
@@ -166,26 +401,57 @@ impl Default for ADFGXCipherModule {
         Self {
             polybius_key: String::new(),
             transposition_key: String::new(),
-            mode: PolybiusMode::Encode,
+            headers: String::from("ADFGX"),
+            mode: Direction::Encode,
+            lowercase_output: false,
         }
     }
 }
 
+impl ADFGXCipherModule {
+    /// The header symbols, uppercased: exactly five distinct non-space characters.
+    fn header_chars(&self) -> Result<Vec<char>, String> {
+        let headers: Vec<char> = self
+            .headers
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_uppercase)
+            .collect();
+        if headers.len() != 5 {
+            return Err(format!(
+                "Headers must be 5 characters (one per row of the square), got {}",
+                headers.len()
+            ));
+        }
+        let mut seen = std::collections::HashSet::new();
+        if let Some(c) = headers.iter().find(|&&c| !seen.insert(c)) {
+            return Err(format!("Header '{}' is used more than once", c));
+        }
+        Ok(headers)
+    }
+}
+
 impl Module for ADFGXCipherModule {
     fn name(&self) -> &str {
         "ADFGX Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "adfgx"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         // 1. Generate 5x5 Polybius Square (I/J merged)
-        let mut poly = PolybiusSquareModule::default();
-        poly.key = self.polybius_key.clone();
-        poly.size = 5;
+        let poly = PolybiusSquareModule {
+            key: self.polybius_key.clone(),
+            size: 5,
+            ..Default::default()
+        };
         let square = poly.generate_square();
-        let headers = ['A', 'D', 'F', 'G', 'X'];
+        let headers = self.header_chars().map_err(ProcessError::BadConfig)?;
 
-        match self.mode {
-            PolybiusMode::Encode => {
+        Ok(match self.mode {
+            Direction::Encode => {
                 // Step 1: Substitution
                 let mut substituted = String::new();
                 for c in input.to_uppercase().chars() {
@@ -198,93 +464,38 @@ impl Module for ADFGXCipherModule {
                 }
 
                 // Step 2: Columnar Transposition
-                let key = self.transposition_key.to_uppercase();
-                let key_chars: Vec<char> =
-                    key.chars().filter(|c| c.is_ascii_alphabetic()).collect();
-                if key_chars.is_empty() {
-                    return substituted;
+                if !self
+                    .transposition_key
+                    .chars()
+                    .any(|c| c.is_ascii_alphabetic())
+                {
+                    return Ok(substituted);
                 }
-
-                let num_cols = key_chars.len();
-                let num_rows = (substituted.len() + num_cols - 1) / num_cols;
-                let mut grid = vec![vec![' '; num_cols]; num_rows];
                 let sub_chars: Vec<char> = substituted.chars().collect();
-
-                for (i, &c) in sub_chars.iter().enumerate() {
-                    grid[i / num_cols][i % num_cols] = c;
-                }
-
-                // Sort key to determine column order
-                let mut key_indices: Vec<usize> = (0..num_cols).collect();
-                key_indices.sort_by_key(|&i| key_chars[i]);
-
                 let mut result = String::new();
-                for &col_idx in &key_indices {
-                    for row in 0..num_rows {
-                        let c = grid[row][col_idx];
-                        if c != ' ' {
-                            result.push(c);
-                        }
-                    }
+                for column in columnar_columns(&sub_chars, &self.transposition_key) {
+                    result.push_str(&column);
                     result.push(' '); // Space between columns for readability
                 }
                 result
             }
-            PolybiusMode::Decode => {
-                let input_clean: String = input.chars().filter(|c| "ADFGX".contains(*c)).collect();
-                let key = self.transposition_key.to_uppercase();
-                let key_chars: Vec<char> =
-                    key.chars().filter(|c| c.is_ascii_alphabetic()).collect();
-
-                if key_chars.is_empty() || input_clean.is_empty() {
-                    return String::new();
-                }
-
-                let num_cols = key_chars.len();
-                let total_len = input_clean.len();
-                let num_rows = (total_len + num_cols - 1) / num_cols;
-                let num_full_cols = total_len % num_cols; // Columns that have full rows
-                let num_full_cols = if num_full_cols == 0 {
-                    num_cols
-                } else {
-                    num_full_cols
-                };
-
-                // Determine column lengths
-                let mut col_lengths = vec![num_rows - 1; num_cols];
-                for i in 0..num_full_cols {
-                    col_lengths[i] = num_rows;
+            Direction::Decode => {
+                let input_clean: String = input
+                    .chars()
+                    .flat_map(char::to_uppercase)
+                    .filter(|c| headers.contains(c))
+                    .collect();
+                if !self
+                    .transposition_key
+                    .chars()
+                    .any(|c| c.is_ascii_alphabetic())
+                    || input_clean.is_empty()
+                {
+                    return Ok(String::new());
                 }
 
-                // Sort key to determine reading order
-                let mut key_indices: Vec<usize> = (0..num_cols).collect();
-                key_indices.sort_by_key(|&i| key_chars[i]);
-
-                // Fill columns based on sorted key
-                let mut grid = vec![vec![' '; num_cols]; num_rows];
-                let mut current_idx = 0;
                 let input_chars: Vec<char> = input_clean.chars().collect();
-
-                for &col_idx in &key_indices {
-                    let len = col_lengths[col_idx];
-                    for row in 0..len {
-                        if current_idx < input_chars.len() {
-                            grid[row][col_idx] = input_chars[current_idx];
-                            current_idx += 1;
-                        }
-                    }
-                }
-
-                // Read rows to get substituted text
-                let mut substituted = String::new();
-                for row in 0..num_rows {
-                    for col in 0..num_cols {
-                        let c = grid[row][col];
-                        if c != ' ' {
-                            substituted.push(c);
-                        }
-                    }
-                }
+                let substituted = columnar_decrypt(&input_chars, &self.transposition_key);
 
                 // Reverse Substitution
                 let mut result = String::new();
@@ -304,15 +515,26 @@ impl Module for ADFGXCipherModule {
                         }
                     }
                 }
-                result
+                if self.lowercase_output {
+                    result.to_lowercase()
+                } else {
+                    result
+                }
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Polybius Key:");
@@ -322,6 +544,19 @@ impl Module for ADFGXCipherModule {
             ui.label("Transposition Key:");
             ui.text_edit_singleline(&mut self.transposition_key);
         });
+        ui.horizontal(|ui| {
+            ui.label("Headers:");
+            ui.add(egui::TextEdit::singleline(&mut self.headers).desired_width(80.0));
+            if ui.button("Reset").clicked() {
+                self.headers = String::from("ADFGX");
+            }
+        });
+        if let Err(e) = self.header_chars() {
+            ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+        }
+        if self.mode == Direction::Decode {
+            ui.checkbox(&mut self.lowercase_output, "Lowercase output");
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -333,16 +568,33 @@ impl Module for ADFGXCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct BifidCipherModule {
     key: String,
-    mode: PolybiusMode,
+    /// Square side: 5 (I/J merged) or 6 (with digits)
+    size: usize,
+    mode: Direction,
+    /// Give each output letter the case of the input letter in the same position
+    preserve_case: bool,
 }
 
 impl Default for BifidCipherModule {
     fn default() -> Self {
         Self {
             key: String::new(),
-            mode: PolybiusMode::Encode,
+            size: 5,
+            mode: Direction::Encode,
+            preserve_case: false,
+        }
+    }
+}
+
+impl BifidCipherModule {
+    fn apply_case(&self, text: String, lowercase: &[bool]) -> String {
+        if self.preserve_case {
+            apply_case_pattern(&text, lowercase)
+        } else {
+            text
         }
     }
 }
@@ -352,22 +604,32 @@ impl Module for BifidCipherModule {
         "Bifid Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        let mut poly = PolybiusSquareModule::default();
-        poly.key = self.key.clone();
-        poly.size = 5;
+    fn id(&self) -> &str {
+        "bifid"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        check_square_size(self.size)?;
+        let poly = PolybiusSquareModule {
+            key: self.key.clone(),
+            size: self.size,
+            ..Default::default()
+        };
         let square = poly.generate_square();
+        let size = self.size;
 
-        match self.mode {
-            PolybiusMode::Encode => {
+        Ok(match self.mode {
+            Direction::Encode => {
                 let mut rows = Vec::new();
                 let mut cols = Vec::new();
+                let mut lowercase = Vec::new();
 
                 // 1. Get coordinates
-                for c in input.to_uppercase().chars() {
-                    if let Some(pos) = poly.find_in_square(&square, c) {
-                        rows.push(pos / 5);
-                        cols.push(pos % 5);
+                for c in input.chars() {
+                    if let Some(pos) = poly.find_in_square(&square, c.to_ascii_uppercase()) {
+                        rows.push(pos / size);
+                        cols.push(pos % size);
+                        lowercase.push(c.is_ascii_lowercase());
                     }
                 }
 
@@ -379,25 +641,29 @@ impl Module for BifidCipherModule {
                 let mut result = String::new();
                 for pair in combined.chunks(2) {
                     if pair.len() == 2 {
-                        let pos = pair[0] * 5 + pair[1];
+                        let pos = pair[0] * size + pair[1];
                         if pos < square.len() {
                             result.push(square[pos]);
                         }
                     }
                 }
-                result
+                self.apply_case(result, &lowercase)
             }
-            PolybiusMode::Decode => {
+            Direction::Decode => {
                 let mut coords = Vec::new();
-                for c in input.to_uppercase().chars() {
-                    if let Some(pos) = poly.find_in_square(&square, c) {
-                        coords.push(pos / 5);
-                        coords.push(pos % 5);
+                let mut lowercase = Vec::new();
+                for c in input.chars() {
+                    if let Some(pos) = poly.find_in_square(&square, c.to_ascii_uppercase()) {
+                        coords.push(pos / size);
+                        coords.push(pos % size);
+                        lowercase.push(c.is_ascii_lowercase());
                     }
                 }
 
                 if coords.len() % 2 != 0 {
-                    return "Error: Odd number of coordinates".to_string();
+                    return Err(ProcessError::InvalidInput(
+                        "Odd number of coordinates".to_string(),
+                    ));
                 }
 
                 let mid = coords.len() / 2;
@@ -406,25 +672,38 @@ impl Module for BifidCipherModule {
 
                 let mut result = String::new();
                 for i in 0..mid {
-                    let pos = rows[i] * 5 + cols[i];
+                    let pos = rows[i] * size + cols[i];
                     if pos < square.len() {
                         result.push(square[pos]);
                     }
                 }
-                result
+                self.apply_case(result, &lowercase)
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Grid Size:");
+            ui.radio_value(&mut self.size, 5, "5×5 (I/J merged)");
+            ui.radio_value(&mut self.size, 6, "6×6 (with digits)");
         });
         ui.horizontal(|ui| {
             ui.label("Key:");
             ui.text_edit_singleline(&mut self.key);
         });
+        ui.checkbox(&mut self.preserve_case, "Preserve letter case by position");
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -436,10 +715,15 @@ impl Module for BifidCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct NihilistCipherModule {
     polybius_key: String,
     keyword: String,
-    mode: PolybiusMode,
+    /// Square side: 5 (I/J merged) or 6 (with digits)
+    size: usize,
+    mode: Direction,
+    /// Decode to lowercase letters instead of the grid's uppercase
+    lowercase_output: bool,
 }
 
 impl Default for NihilistCipherModule {
@@ -447,7 +731,9 @@ impl Default for NihilistCipherModule {
         Self {
             polybius_key: String::new(),
             keyword: String::new(),
-            mode: PolybiusMode::Encode,
+            size: 5,
+            mode: Direction::Encode,
+            lowercase_output: false,
         }
     }
 }
@@ -457,35 +743,49 @@ impl Module for NihilistCipherModule {
         "Nihilist Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        let mut poly = PolybiusSquareModule::default();
-        poly.key = self.polybius_key.clone();
-        poly.size = 5;
+    fn id(&self) -> &str {
+        "nihilist"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        if input.is_empty() {
+            return Ok(String::new());
+        }
+        check_square_size(self.size)?;
+        let size = self.size;
+        let poly = PolybiusSquareModule {
+            key: self.polybius_key.clone(),
+            size,
+            ..Default::default()
+        };
         let square = poly.generate_square();
 
-        // Convert keyword to coordinates
+        // Convert keyword to coordinates; rows and columns stay single
+        // digits up to 6, so each is the two-digit number row * 10 + col
         let mut key_coords = Vec::new();
         for c in self.keyword.to_uppercase().chars() {
             if let Some(pos) = poly.find_in_square(&square, c) {
-                let row = pos / 5 + 1;
-                let col = pos % 5 + 1;
+                let row = pos / size + 1;
+                let col = pos % size + 1;
                 key_coords.push(row * 10 + col);
             }
         }
 
         if key_coords.is_empty() {
-            return "Error: Keyword cannot be empty".to_string();
+            return Err(ProcessError::BadConfig(
+                "Keyword cannot be empty".to_string(),
+            ));
         }
 
-        match self.mode {
-            PolybiusMode::Encode => {
+        Ok(match self.mode {
+            Direction::Encode => {
                 let mut result = Vec::new();
                 let mut key_idx = 0;
 
                 for c in input.to_uppercase().chars() {
                     if let Some(pos) = poly.find_in_square(&square, c) {
-                        let row = pos / 5 + 1;
-                        let col = pos % 5 + 1;
+                        let row = pos / size + 1;
+                        let col = pos % size + 1;
                         let val = row * 10 + col;
 
                         let key_val = key_coords[key_idx % key_coords.len()];
@@ -496,12 +796,11 @@ impl Module for NihilistCipherModule {
                 }
                 result.join(" ")
             }
-            PolybiusMode::Decode => {
+            Direction::Decode => {
                 let mut result = String::new();
                 let mut key_idx = 0;
 
-                let nums: Vec<&str> = input.split_whitespace().collect();
-                for num_str in nums {
+                for num_str in split_tokens(input) {
                     if let Ok(val) = num_str.parse::<usize>() {
                         let key_val = key_coords[key_idx % key_coords.len()];
                         if val > key_val {
@@ -509,8 +808,8 @@ impl Module for NihilistCipherModule {
                             let row = diff / 10;
                             let col = diff % 10;
 
-                            if row > 0 && col > 0 && row <= 5 && col <= 5 {
-                                let pos = (row - 1) * 5 + (col - 1);
+                            if row > 0 && col > 0 && row <= size && col <= size {
+                                let pos = (row - 1) * size + (col - 1);
                                 if pos < square.len() {
                                     result.push(square[pos]);
                                 }
@@ -519,15 +818,31 @@ impl Module for NihilistCipherModule {
                         key_idx += 1;
                     }
                 }
-                result
+                if self.lowercase_output {
+                    result.to_lowercase()
+                } else {
+                    result
+                }
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Grid Size:");
+            ui.radio_value(&mut self.size, 5, "5×5 (I/J merged)");
+            ui.radio_value(&mut self.size, 6, "6×6 (with digits)");
         });
         ui.horizontal(|ui| {
             ui.label("Polybius Key:");
@@ -537,6 +852,9 @@ impl Module for NihilistCipherModule {
             ui.label("Keyword:");
             ui.text_edit_singleline(&mut self.keyword);
         });
+        if self.mode == Direction::Decode {
+            ui.checkbox(&mut self.lowercase_output, "Lowercase output");
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -548,14 +866,18 @@ impl Module for NihilistCipherModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct TapCodeModule {
-    mode: PolybiusMode,
+    mode: Direction,
+    /// Decode to lowercase letters instead of uppercase
+    lowercase_output: bool,
 }
 
 impl Default for TapCodeModule {
     fn default() -> Self {
         Self {
-            mode: PolybiusMode::Encode,
+            mode: Direction::Encode,
+            lowercase_output: false,
         }
     }
 }
@@ -564,14 +886,20 @@ impl Module for TapCodeModule {
     fn name(&self) -> &str {
         "Tap Code"
     }
-    fn process(&self, input: &str) -> String {
+
+    fn id(&self) -> &str {
+        "tap_code"
+    }
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         match self.mode {
-            PolybiusMode::Encode => {
+            Direction::Encode => {
                 // Tap code is basically Polybius square with dots
-                let mut poly = PolybiusSquareModule::default();
-                poly.mode = PolybiusMode::Encode;
-                let coords = poly.process(input);
-                coords
+                let poly = PolybiusSquareModule {
+                    mode: Direction::Encode,
+                    ..Default::default()
+                };
+                let coords = poly.process(input)?;
+                Ok(coords
                     .chars()
                     .map(|c| {
                         if let Some(d) = c.to_digit(10) {
@@ -580,9 +908,9 @@ impl Module for TapCodeModule {
                             c.to_string()
                         }
                     })
-                    .collect()
+                    .collect())
             }
-            PolybiusMode::Decode => {
+            Direction::Decode => {
                 // Count dots to get coordinates, then decode
                 let mut coords = String::new();
                 let groups: Vec<&str> = input.split_whitespace().collect();
@@ -595,17 +923,30 @@ impl Module for TapCodeModule {
                 }
 
                 // Use Polybius decoder
-                let mut poly = PolybiusSquareModule::default();
-                poly.mode = PolybiusMode::Decode;
+                let poly = PolybiusSquareModule {
+                    mode: Direction::Decode,
+                    lowercase_output: self.lowercase_output,
+                    ..Default::default()
+                };
                 poly.process(&coords)
             }
         }
     }
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
+        if self.mode == Direction::Decode {
+            ui.checkbox(&mut self.lowercase_output, "Lowercase output");
+        }
     }
     fn as_any(&self) -> &dyn std::any::Any {
         self
@@ -615,16 +956,54 @@ impl Module for TapCodeModule {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct TrifidCipherModule {
     key: String,
-    mode: PolybiusMode,
+    /// Cells of the cube before keying; its length must be a cube number
+    alphabet: String,
+    mode: Direction,
+    /// Give each output letter the case of the input letter in the same position
+    preserve_case: bool,
 }
 
 impl Default for TrifidCipherModule {
     fn default() -> Self {
         Self {
             key: String::new(),
-            mode: PolybiusMode::Encode,
+            alphabet: TRIFID_ALPHABET.to_string(),
+            mode: Direction::Encode,
+            preserve_case: false,
+        }
+    }
+}
+
+/// The classic 27-cell Trifid alphabet
+const TRIFID_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ.";
+/// A 4×4×4 alphabet with room for digits and punctuation
+const TRIFID_ALPHABET_64: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.,:;!?'\"()-+*/=@#&%$_<>[]^~ ";
+
+impl TrifidCipherModule {
+    /// The keyed cube as a flat list of cells, and its side length.
+    fn cube(&self) -> Result<(Vec<char>, usize), String> {
+        let cube: Vec<char> = keyed_alphabet(&self.key, &self.alphabet, KeyFill::Forward, false)
+            .chars()
+            .collect();
+        let side = (2..=6).find(|side| side * side * side == cube.len());
+        match side {
+            Some(side) => Ok((cube, side)),
+            None => Err(format!(
+                "The alphabet must have a cube number of distinct characters (27 or 64), not {}",
+                cube.len()
+            )),
+        }
+    }
+
+    fn apply_case(&self, text: String, lowercase: &[bool]) -> String {
+        if self.preserve_case {
+            apply_case_pattern(&text, lowercase)
+        } else {
+            text
         }
     }
 }
@@ -634,42 +1013,35 @@ impl Module for TrifidCipherModule {
         "Trifid Cipher"
     }
 
-    fn process(&self, input: &str) -> String {
-        // Generate 27-char square (A-Z + .)
-        let mut square = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-
-        // Add key chars
-        for c in self.key.to_uppercase().chars() {
-            if (c.is_ascii_alphabetic() || c == '.') && !seen.contains(&c) {
-                square.push(c);
-                seen.insert(c);
-            }
-        }
-
-        // Add remaining chars
-        for c in 'A'..='Z' {
-            if !seen.contains(&c) {
-                square.push(c);
-                seen.insert(c);
-            }
-        }
-        if !seen.contains(&'.') {
-            square.push('.');
-        }
+    fn id(&self) -> &str {
+        "trifid"
+    }
 
-        match self.mode {
-            PolybiusMode::Encode => {
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let (square, side) = self.cube().map_err(ProcessError::BadConfig)?;
+        let layer = side * side;
+        // Exact match first, so alphabets with lowercase letters work too
+        let find = |c: char| {
+            square
+                .iter()
+                .position(|&x| x == c)
+                .or_else(|| square.iter().position(|&x| x == c.to_ascii_uppercase()))
+        };
+
+        Ok(match self.mode {
+            Direction::Encode => {
                 let mut layers = Vec::new();
                 let mut rows = Vec::new();
                 let mut cols = Vec::new();
+                let mut lowercase = Vec::new();
 
                 // 1. Get coordinates (Layer, Row, Col)
-                for c in input.to_uppercase().chars() {
-                    if let Some(pos) = square.iter().position(|&x| x == c) {
-                        layers.push(pos / 9);
-                        rows.push((pos % 9) / 3);
-                        cols.push(pos % 3);
+                for c in input.chars() {
+                    if let Some(pos) = find(c) {
+                        layers.push(pos / layer);
+                        rows.push((pos % layer) / side);
+                        cols.push(pos % side);
+                        lowercase.push(c.is_ascii_lowercase());
                     }
                 }
 
@@ -682,26 +1054,30 @@ impl Module for TrifidCipherModule {
                 let mut result = String::new();
                 for triplet in combined.chunks(3) {
                     if triplet.len() == 3 {
-                        let pos = triplet[0] * 9 + triplet[1] * 3 + triplet[2];
+                        let pos = triplet[0] * layer + triplet[1] * side + triplet[2];
                         if pos < square.len() {
                             result.push(square[pos]);
                         }
                     }
                 }
-                result
+                self.apply_case(result, &lowercase)
             }
-            PolybiusMode::Decode => {
+            Direction::Decode => {
                 let mut coords = Vec::new();
-                for c in input.to_uppercase().chars() {
-                    if let Some(pos) = square.iter().position(|&x| x == c) {
-                        coords.push(pos / 9);
-                        coords.push((pos % 9) / 3);
-                        coords.push(pos % 3);
+                let mut lowercase = Vec::new();
+                for c in input.chars() {
+                    if let Some(pos) = find(c) {
+                        coords.push(pos / layer);
+                        coords.push((pos % layer) / side);
+                        coords.push(pos % side);
+                        lowercase.push(c.is_ascii_lowercase());
                     }
                 }
 
                 if coords.len() % 3 != 0 {
-                    return "Error: Number of coordinates must be divisible by 3".to_string();
+                    return Err(ProcessError::InvalidInput(
+                        "Number of coordinates must be divisible by 3".to_string(),
+                    ));
                 }
 
                 let third = coords.len() / 3;
@@ -711,26 +1087,52 @@ impl Module for TrifidCipherModule {
 
                 let mut result = String::new();
                 for i in 0..third {
-                    let pos = layers[i] * 9 + rows[i] * 3 + cols[i];
+                    let pos = layers[i] * layer + rows[i] * side + cols[i];
                     if pos < square.len() {
                         result.push(square[pos]);
                     }
                 }
-                result
+                self.apply_case(result, &lowercase)
             }
-        }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            ui.radio_value(&mut self.mode, PolybiusMode::Encode, "Encode");
-            ui.radio_value(&mut self.mode, PolybiusMode::Decode, "Decode");
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
         });
         ui.horizontal(|ui| {
             ui.label("Key:");
             ui.text_edit_singleline(&mut self.key);
         });
-        ui.label("Note: Uses 27-char alphabet (A-Z + .)");
+        ui.horizontal(|ui| {
+            ui.label("Alphabet:");
+            ui.text_edit_singleline(&mut self.alphabet);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("A-Z + . (27)").clicked() {
+                self.alphabet = TRIFID_ALPHABET.to_string();
+            }
+            if ui
+                .button("With digits (64)")
+                .on_hover_text("A 4×4×4 cube: A-Z, 0-9, punctuation and space")
+                .clicked()
+            {
+                self.alphabet = TRIFID_ALPHABET_64.to_string();
+            }
+        });
+        if let Err(e) = self.cube() {
+            ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+        }
+        ui.checkbox(&mut self.preserve_case, "Preserve letter case by position");
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -741,3 +1143,203 @@ impl Module for TrifidCipherModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_preserved_when_enabled() {
+        let input = "FleeAtOnce";
+        let bifid = |mode| BifidCipherModule {
+            mode,
+            preserve_case: true,
+            ..Default::default()
+        };
+        let encoded = bifid(Direction::Encode).process(input).unwrap();
+        assert_eq!(
+            encoded.to_uppercase(),
+            BifidCipherModule::default().process(input).unwrap()
+        );
+        assert_eq!(bifid(Direction::Decode).process(&encoded).unwrap(), input);
+
+        let trifid = |mode| TrifidCipherModule {
+            mode,
+            preserve_case: true,
+            ..Default::default()
+        };
+        let encoded = trifid(Direction::Encode).process(input).unwrap();
+        assert_eq!(trifid(Direction::Decode).process(&encoded).unwrap(), input);
+
+        let encoded = PolybiusSquareModule::default().process("hello").unwrap();
+        let decode = PolybiusSquareModule {
+            mode: Direction::Decode,
+            lowercase_output: true,
+            ..Default::default()
+        };
+        assert_eq!(decode.process(&encoded).unwrap(), "hello");
+        // Off by default: the square's letters are uppercase
+        let decode = PolybiusSquareModule {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        assert_eq!(decode.process(&encoded).unwrap(), "HELLO");
+    }
+
+    #[test]
+    fn test_pasted_grid_sets_coordinates() {
+        let mut module = PolybiusSquareModule {
+            source: SquareSource::Grid,
+            grid: "Z Y X W V\nU T S R Q\nP O N M L\nK I H G F\nE D C B A".to_string(),
+            coordinates_only: true,
+            ..Default::default()
+        };
+        assert_eq!(module.process("A").unwrap(), "55");
+        assert_eq!(module.process("zoo").unwrap(), "11 32 32");
+        module.mode = Direction::Decode;
+        assert_eq!(module.process("11 32 32").unwrap(), "ZOO");
+
+        module.grid = "ZYXWV\nUTSRQ\nPONML\nKIHGF".to_string();
+        assert!(module.process("A").is_err());
+        module.grid = "ZYXWV\nUTSRQ\nPONML\nKIHGF\nEDCBZ".to_string();
+        assert!(module.process("A").is_err());
+    }
+
+    #[test]
+    fn test_adfgx_custom_headers_round_trip() {
+        let mut module = ADFGXCipherModule {
+            polybius_key: "BTALPDHOZKQFVSNGICUXMREWY".to_string(),
+            transposition_key: "CARGO".to_string(),
+            headers: "12345".to_string(),
+            ..Default::default()
+        };
+        let encoded = module.process("ATTACKATONCE").unwrap();
+        assert!(encoded
+            .chars()
+            .all(|c| ('1'..='5').contains(&c) || c == ' '));
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "ATTACKATONCE");
+
+        module.headers = "1234".to_string();
+        assert!(module.process(&encoded).is_err());
+        module.headers = "12344".to_string();
+        assert!(module.process(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_coordinates_only_round_trip() {
+        for (column_first, zero_based, expected) in [
+            (false, false, "23 15 31 31 34"),
+            (true, false, "32 51 13 13 43"),
+            (false, true, "12 04 20 20 23"),
+        ] {
+            let mut module = PolybiusSquareModule {
+                coordinates_only: true,
+                column_first,
+                zero_based,
+                ..Default::default()
+            };
+            assert_eq!(module.process("Hello!").unwrap(), expected);
+            module.mode = Direction::Decode;
+            assert_eq!(module.process(expected).unwrap(), "HELLO");
+        }
+        let decode = PolybiusSquareModule {
+            mode: Direction::Decode,
+            coordinates_only: true,
+            ..Default::default()
+        };
+        assert!(decode.process("23 16").is_err());
+        assert!(decode.process("23 H").is_err());
+    }
+
+    #[test]
+    fn test_merged_i_j_cell_on_decode() {
+        let encoded = PolybiusSquareModule::default()
+            .process("just in joke")
+            .unwrap();
+        let decode = |merged_cell| {
+            PolybiusSquareModule {
+                mode: Direction::Decode,
+                merged_cell,
+                ..Default::default()
+            }
+            .process(&encoded)
+            .unwrap()
+        };
+        // J shares I's cell, so plain decoding shows I for both
+        assert_eq!(decode(MergedCell::AsIs), "IUSTINIOKE");
+        assert_eq!(decode(MergedCell::Annotate), "I/JUSTI/JNI/JOKE");
+        assert_eq!(decode(MergedCell::Restore), "JUSTINJOKE");
+    }
+
+    #[test]
+    fn test_bifid_6x6_with_digits_round_trip() {
+        let mut module = BifidCipherModule {
+            key: "ENIGMA".to_string(),
+            size: 6,
+            ..Default::default()
+        };
+        let encoded = module.process("MEET AT 0900 ON PIER 17").unwrap();
+        assert_ne!(encoded, "MEETAT0900ONPIER17");
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "MEETAT0900ONPIER17");
+        for size in [0, 4, 7, usize::MAX] {
+            module.size = size;
+            assert!(matches!(
+                module.process(&encoded),
+                Err(ProcessError::BadConfig(_))
+            ));
+        }
+
+        // The 64-cell Trifid alphabet also has digits
+        let mut module = TrifidCipherModule {
+            alphabet: TRIFID_ALPHABET_64.to_string(),
+            ..Default::default()
+        };
+        let encoded = module.process("AGENT 007").unwrap();
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "AGENT 007");
+    }
+
+    #[test]
+    fn test_nihilist_6x6_with_digits_round_trip() {
+        let mut module = NihilistCipherModule {
+            polybius_key: "ZEBRAS".to_string(),
+            keyword: "RUSSIAN".to_string(),
+            ..Default::default()
+        };
+        // The 5x5 example from Wikipedia
+        assert_eq!(
+            module.process("DYNAMITE WINTER PALACE").unwrap(),
+            "37 106 62 36 67 47 86 26 104 53 62 77 27 55 57 66 55 36 54 27"
+        );
+
+        module.size = 6;
+        module.keyword = "AGENT7".to_string();
+        let encoded = module.process("MEET AT 0900 ON PIER 17").unwrap();
+        assert_eq!(encoded.split(' ').count(), 18);
+        module.mode = Direction::Decode;
+        assert_eq!(module.process(&encoded).unwrap(), "MEETAT0900ONPIER17");
+    }
+
+    #[test]
+    fn test_decoders_accept_any_separator() {
+        let polybius = PolybiusSquareModule {
+            mode: Direction::Decode,
+            coordinates_only: true,
+            ..Default::default()
+        };
+        let nihilist = NihilistCipherModule {
+            polybius_key: "ZEBRAS".to_string(),
+            keyword: "RUSSIAN".to_string(),
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        for separator in [" ", ",", ", ", "\n", "-"] {
+            let coordinates = ["23", "15", "31", "31", "34"].join(separator);
+            assert_eq!(polybius.process(&coordinates).unwrap(), "HELLO");
+            let numbers = ["37", "106", "62", "36", "67", "47", "86", "26"].join(separator);
+            assert_eq!(nihilist.process(&numbers).unwrap(), "DYNAMITE");
+        }
+    }
+}
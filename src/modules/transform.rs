@@ -1,7 +1,8 @@
-use crate::module::Module;
+use crate::module::{Direction, Inverse, Module, ProcessError};
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct ReverseModule;
 
 impl Module for ReverseModule {
@@ -9,8 +10,16 @@ impl Module for ReverseModule {
         "Reverse"
     }
 
-    fn process(&self, input: &str) -> String {
-        input.chars().rev().collect()
+    fn id(&self) -> &str {
+        "reverse"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(input.chars().rev().collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::Itself)
     }
 
     fn ui(&mut self, _ui: &mut egui::Ui) {
@@ -26,7 +35,7 @@ impl Module for ReverseModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum CaseMode {
     LowerCase,
     UpperCase,
@@ -34,6 +43,7 @@ pub enum CaseMode {
     Alternating,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct CaseTransformModule {
     mode: CaseMode,
 }
@@ -51,8 +61,12 @@ impl Module for CaseTransformModule {
         "Case Transform"
     }
 
-    fn process(&self, input: &str) -> String {
-        match self.mode {
+    fn id(&self) -> &str {
+        "case_transform"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
             CaseMode::LowerCase => input.to_lowercase(),
             CaseMode::UpperCase => input.to_uppercase(),
             CaseMode::Capitalize => input
@@ -77,7 +91,7 @@ impl Module for CaseTransformModule {
                     }
                 })
                 .collect(),
-        }
+        })
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -105,7 +119,7 @@ impl Module for CaseTransformModule {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct ReplaceModule {
     find: String,
     replace: String,
@@ -116,12 +130,16 @@ impl Module for ReplaceModule {
         "Replace"
     }
 
-    fn process(&self, input: &str) -> String {
-        if self.find.is_empty() {
+    fn id(&self) -> &str {
+        "replace"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(if self.find.is_empty() {
             input.to_string()
         } else {
             input.replace(&self.find, &self.replace)
-        }
+        })
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -142,7 +160,7 @@ impl Module for ReplaceModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum NumeralSystem {
     Decimal,
     Binary,
@@ -150,6 +168,7 @@ pub enum NumeralSystem {
     Hexadecimal,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct NumeralSystemModule {
     from: NumeralSystem,
     to: NumeralSystem,
@@ -169,9 +188,13 @@ impl Module for NumeralSystemModule {
         "Numeral System"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "numeral"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
         // Split by whitespace and process each number
-        input
+        Ok(input
             .split_whitespace()
             .map(|s| {
                 let val = match self.from {
@@ -193,7 +216,14 @@ impl Module for NumeralSystemModule {
                 }
             })
             .collect::<Vec<_>>()
-            .join(" ")
+            .join(" "))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "from": self.to, "to": self.from }),
+        ))
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -228,7 +258,590 @@ impl Module for NumeralSystemModule {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct MixedRadixModule {
+    /// Comma-separated radices, most significant first, e.g. "24,60,60"
+    radices: String,
+    /// Written between digits, e.g. ":" for clock times
+    separator: String,
+    direction: Direction,
+}
+
+impl Default for MixedRadixModule {
+    fn default() -> Self {
+        Self {
+            radices: String::from("24,60,60"),
+            separator: String::from(":"),
+            direction: Direction::Encode,
+        }
+    }
+}
+
+impl MixedRadixModule {
+    fn parse_radices(&self) -> Result<Vec<u128>, String> {
+        let radices = self
+            .radices
+            .split(',')
+            .map(|r| match r.trim().parse::<u128>() {
+                Ok(r) if r >= 2 => Ok(r),
+                _ => Err(format!(
+                    "Invalid radix \"{}\" (must be at least 2)",
+                    r.trim()
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // The full range has to fit in a u128
+        radices
+            .iter()
+            .try_fold(1u128, |acc, &r| acc.checked_mul(r))
+            .ok_or("The radices are too large")?;
+        Ok(radices)
+    }
+}
+
+/// Split `value` into one digit per radix, most significant first.
+pub fn to_mixed_radix(value: u128, radices: &[u128]) -> Result<Vec<u128>, String> {
+    let mut rest = value;
+    let mut digits = vec![0; radices.len()];
+    for (digit, &radix) in digits.iter_mut().zip(radices).rev() {
+        *digit = rest % radix;
+        rest /= radix;
+    }
+    if rest != 0 {
+        let limit: u128 = radices.iter().product();
+        return Err(format!(
+            "{} does not fit the radices (maximum {})",
+            value,
+            limit - 1
+        ));
+    }
+    Ok(digits)
+}
+
+/// Combine digits (most significant first) back into a single value.
+pub fn from_mixed_radix(digits: &[u128], radices: &[u128]) -> Result<u128, String> {
+    if digits.len() != radices.len() {
+        return Err(format!(
+            "Expected {} digits, got {}",
+            radices.len(),
+            digits.len()
+        ));
+    }
+    let mut value = 0u128;
+    for (&digit, &radix) in digits.iter().zip(radices) {
+        if digit >= radix {
+            return Err(format!(
+                "Digit {} is out of range for radix {}",
+                digit, radix
+            ));
+        }
+        value = value * radix + digit;
+    }
+    Ok(value)
+}
+
+impl Module for MixedRadixModule {
+    fn name(&self) -> &str {
+        "Mixed Radix"
+    }
+
+    fn id(&self) -> &str {
+        "mixed_radix"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let radices = self.parse_radices().map_err(ProcessError::BadConfig)?;
+        if self.separator.trim().is_empty() {
+            return Err(ProcessError::BadConfig(
+                "The separator cannot be empty or whitespace".to_string(),
+            ));
+        }
+
+        // Each whitespace-separated token is one value
+        input
+            .split_whitespace()
+            .map(|token| match self.direction {
+                Direction::Encode => {
+                    let value = token
+                        .parse::<u128>()
+                        .map_err(|_| format!("\"{}\" is not a non-negative integer", token))?;
+                    let digits = to_mixed_radix(value, &radices)?;
+                    // Pad all but the leading digit to the width of their radix
+                    Ok(digits
+                        .iter()
+                        .zip(&radices)
+                        .enumerate()
+                        .map(|(i, (digit, radix))| {
+                            let width = if i == 0 {
+                                1
+                            } else {
+                                (radix - 1).to_string().len()
+                            };
+                            format!("{:0width$}", digit, width = width)
+                        })
+                        .collect::<Vec<_>>()
+                        .join(&self.separator))
+                }
+                Direction::Decode => {
+                    let digits = token
+                        .split(self.separator.as_str())
+                        .map(|d| {
+                            d.parse::<u128>()
+                                .map_err(|_| format!("\"{}\" is not a digit value", d))
+                        })
+                        .collect::<Result<Vec<_>, String>>()?;
+                    from_mixed_radix(&digits, &radices).map(|v| v.to_string())
+                }
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(|values| values.join(" "))
+            .map_err(ProcessError::InvalidInput)
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "direction": self.direction.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.direction, Direction::Encode, "Integer → digits");
+            ui.radio_value(&mut self.direction, Direction::Decode, "Digits → integer");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Radices:");
+            ui.text_edit_singleline(&mut self.radices)
+                .on_hover_text("Most significant first, e.g. 24,60,60 for h:m:s");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Separator:");
+            ui.add(egui::TextEdit::singleline(&mut self.separator).desired_width(40.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Presets:");
+            if ui.button("h:m:s").clicked() {
+                self.radices = String::from("24,60,60");
+                self.separator = String::from(":");
+            }
+            if ui.button("d:h:m:s").clicked() {
+                self.radices = String::from("365,24,60,60");
+                self.separator = String::from(":");
+            }
+            if ui.button("m:s").clicked() {
+                self.radices = String::from("60,60");
+                self.separator = String::from(":");
+            }
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+const SMALL_NUMBER_WORDS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS_WORDS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Scale words by power of 1000, covering the whole i64 range
+const SCALE_WORDS: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+/// English words for `n`, e.g. 1234 -> "one thousand two hundred thirty-four"
+pub fn number_to_words(n: i64) -> String {
+    if n == 0 {
+        return SMALL_NUMBER_WORDS[0].to_string();
+    }
+    let mut groups = Vec::new();
+    let mut rest = n.unsigned_abs();
+    let mut scale = 0;
+    while rest > 0 {
+        let group = (rest % 1000) as usize;
+        if group > 0 {
+            let mut words = Vec::new();
+            if group >= 100 {
+                words.push(format!("{} hundred", SMALL_NUMBER_WORDS[group / 100]));
+            }
+            match group % 100 {
+                0 => {}
+                small @ 1..=19 => words.push(SMALL_NUMBER_WORDS[small].to_string()),
+                tens if tens % 10 == 0 => words.push(TENS_WORDS[tens / 10].to_string()),
+                tens => words.push(format!(
+                    "{}-{}",
+                    TENS_WORDS[tens / 10],
+                    SMALL_NUMBER_WORDS[tens % 10]
+                )),
+            }
+            if scale > 0 {
+                words.push(SCALE_WORDS[scale].to_string());
+            }
+            groups.push(words.join(" "));
+        }
+        rest /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+    let words = groups.join(" ");
+    if n < 0 {
+        format!("minus {}", words)
+    } else {
+        words
+    }
+}
+
+/// Where a number being read from words is within its current group of
+/// three digits, which decides the words that may follow.
+#[derive(Clone, Copy, PartialEq)]
+enum NumberWordsState {
+    /// Nothing yet in this group
+    Empty,
+    /// Units only, so "hundred" may follow
+    Units,
+    /// Just read "hundred"
+    Hundred,
+    /// Read twenty to ninety, so a unit may follow
+    Tens,
+    /// The last two digits are complete
+    Done,
+}
+
+/// A number read from words, as built up one word at a time
+#[derive(Clone, Copy)]
+struct NumberWordsReader {
+    total: i128,
+    group: i128,
+    /// Smallest scale used so far; later scales must be smaller
+    last_scale: Option<i128>,
+    state: NumberWordsState,
+}
+
+impl NumberWordsReader {
+    fn new() -> Self {
+        Self {
+            total: 0,
+            group: 0,
+            last_scale: None,
+            state: NumberWordsState::Empty,
+        }
+    }
+
+    /// Add `word` to the number, or return false when it cannot continue it
+    fn push(&mut self, word: &str) -> bool {
+        use NumberWordsState::*;
+        if let Some(value) = SMALL_NUMBER_WORDS.iter().position(|&w| w == word) {
+            let value = value as i128;
+            let next = match (self.state, value) {
+                (_, 0) => return false,
+                (Empty, 1..=9) => Units,
+                (Hundred | Tens, 1..=9) => Done,
+                (Empty | Hundred, _) => Done,
+                _ => return false,
+            };
+            self.group += value;
+            self.state = next;
+        } else if let Some(tens) = TENS_WORDS[2..].iter().position(|&w| w == word) {
+            if !matches!(self.state, Empty | Hundred) {
+                return false;
+            }
+            self.group += (tens as i128 + 2) * 10;
+            self.state = Tens;
+        } else if word == "hundred" {
+            if self.state != Units {
+                return false;
+            }
+            self.group *= 100;
+            self.state = Hundred;
+        } else if let Some(power) = SCALE_WORDS[1..].iter().position(|&w| w == word) {
+            let scale = 1000i128.pow(power as u32 + 1);
+            if self.state == Empty || self.last_scale.is_some_and(|last| scale >= last) {
+                return false;
+            }
+            self.total += self.group * scale;
+            self.group = 0;
+            self.last_scale = Some(scale);
+            self.state = Empty;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn value(&self) -> i128 {
+        self.total + self.group
+    }
+}
+
+/// Replace every run of English number words in `text` with its digits,
+/// e.g. "minus forty-two and one hundred and one" -> "-42 and 101". Words
+/// that do not form a number are left as they are.
+pub fn words_to_numbers(text: &str) -> String {
+    // Words with their byte ranges; hyphens and spaces both separate them
+    let words: Vec<(usize, usize, String)> =
+        text.match_indices(|c: char| c.is_ascii_alphabetic()).fold(
+            Vec::new(),
+            |mut words: Vec<(usize, usize, String)>, (i, c)| {
+                match words.last_mut() {
+                    Some((_, end, word)) if *end == i => {
+                        *end += 1;
+                        word.push_str(&c.to_ascii_lowercase());
+                    }
+                    _ => words.push((i, i + 1, c.to_ascii_lowercase())),
+                }
+                words
+            },
+        );
+    // Only spaces and hyphens may sit between the words of one number
+    let joined = |a: usize, b: usize| text[a..b].chars().all(|c| c == ' ' || c == '-');
+
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < words.len() {
+        let negative = matches!(words[i].2.as_str(), "minus" | "negative")
+            && words
+                .get(i + 1)
+                .is_some_and(|next| joined(words[i].1, next.0));
+        let first = if negative { i + 1 } else { i };
+        let mut reader = NumberWordsReader::new();
+        let mut end = first;
+        if words[first].2 == "zero" {
+            end = first + 1;
+        } else {
+            while end < words.len() {
+                if end > first && !joined(words[end - 1].1, words[end].0) {
+                    break;
+                }
+                // "and" may join parts, as in "one hundred and one"
+                if words[end].2 == "and" && end > first && end + 1 < words.len() {
+                    let mut ahead = reader;
+                    if joined(words[end].1, words[end + 1].0) && ahead.push(&words[end + 1].2) {
+                        reader = ahead;
+                        end += 2;
+                        continue;
+                    }
+                }
+                if !reader.push(&words[end].2) {
+                    break;
+                }
+                end += 1;
+            }
+        }
+        if end == first {
+            i += 1;
+            continue;
+        }
+        let value = if words[first].2 == "zero" {
+            0
+        } else {
+            reader.value()
+        };
+        result.push_str(&text[copied..words[i].0]);
+        if negative {
+            result.push('-');
+        }
+        result.push_str(&value.to_string());
+        copied = words[end - 1].1;
+        i = end;
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NumberWordsModule {
+    /// Encode writes numbers as words, decode reads them back
+    mode: Direction,
+}
+
+impl Default for NumberWordsModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+        }
+    }
+}
+
+impl Module for NumberWordsModule {
+    fn name(&self) -> &str {
+        "Number Words"
+    }
+
+    fn id(&self) -> &str {
+        "number_words"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        match self.mode {
+            Direction::Encode => {
+                let mut result = String::new();
+                let mut copied = 0;
+                let mut pos = 0;
+                while let Some(offset) = input[pos..].find(|c: char| c.is_ascii_digit()) {
+                    let start = pos + offset;
+                    let end = input[start..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .map_or(input.len(), |len| start + len);
+                    // A minus sign counts unless it joins two words, as in a date
+                    let negative = input[..start].ends_with('-')
+                        && !input[..start - 1]
+                            .chars()
+                            .next_back()
+                            .is_some_and(char::is_alphanumeric);
+                    let sign_start = if negative { start - 1 } else { start };
+                    let number = input[sign_start..end].parse::<i64>().map_err(|_| {
+                        ProcessError::InvalidInput(format!("{} is too large", &input[start..end]))
+                    })?;
+                    result.push_str(&input[copied..sign_start]);
+                    result.push_str(&number_to_words(number));
+                    copied = end;
+                    pos = end;
+                }
+                result.push_str(&input[copied..]);
+                Ok(result)
+            }
+            Direction::Decode => Ok(words_to_numbers(input)),
+        }
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Numbers → words");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Words → numbers");
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Split the input of a numeric decoder at whatever separator it uses:
+/// whitespace plus its most common punctuation character, so "8,5,12",
+/// "8-5-12" and "8 5 12" give the same tokens.
+pub fn split_tokens(input: &str) -> Vec<&str> {
+    let mut counts: Vec<(char, usize)> = Vec::new();
+    for c in input
+        .chars()
+        .filter(|c| !c.is_alphanumeric() && !c.is_whitespace())
+    {
+        match counts.iter_mut().find(|(seen, _)| *seen == c) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((c, 1)),
+        }
+    }
+    // `max_by_key` keeps the last of equal counts, so walk backwards to
+    // prefer the character seen first
+    let separator = counts.iter().rev().max_by_key(|(_, n)| *n).map(|&(c, _)| c);
+    input
+        .split(|c: char| c.is_whitespace() || Some(c) == separator)
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// How byte-oriented modules render their result.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ByteFormat {
+    /// Interpret the bytes as UTF-8 (invalid sequences become U+FFFD)
+    Raw,
+    Hex,
+    Binary,
+}
+
+impl ByteFormat {
+    pub fn format(&self, bytes: &[u8]) -> String {
+        match self {
+            ByteFormat::Raw => String::from_utf8_lossy(bytes).to_string(),
+            ByteFormat::Hex => hex::encode(bytes),
+            ByteFormat::Binary => bytes
+                .iter()
+                .map(|b| format!("{:08b}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    /// Parse text written in this format back into bytes.
+    pub fn parse(&self, text: &str) -> Result<Vec<u8>, String> {
+        match self {
+            ByteFormat::Raw => Ok(text.as_bytes().to_vec()),
+            ByteFormat::Hex => {
+                let clean: String = text.split_whitespace().collect();
+                hex::decode(clean).map_err(|_| "Invalid hex input".to_string())
+            }
+            ByteFormat::Binary => {
+                let clean: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+                if !clean.len().is_multiple_of(8) || clean.iter().any(|&c| c != '0' && c != '1') {
+                    return Err("Binary input must be groups of 8 bits".to_string());
+                }
+                Ok(clean
+                    .chunks(8)
+                    .map(|bits| {
+                        bits.iter()
+                            .fold(0u8, |acc, &c| (acc << 1) | (c == '1') as u8)
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui, label: &str) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.radio_value(self, ByteFormat::Raw, "Raw");
+            ui.radio_value(self, ByteFormat::Hex, "Hex");
+            ui.radio_value(self, ByteFormat::Binary, "Binary");
+        });
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum BitwiseOp {
     NOT,
     AND,
@@ -237,11 +850,26 @@ pub enum BitwiseOp {
     NAND,
     NOR,
     XNOR,
+    SHL,
+    SHR,
+    ROL,
+    ROR,
 }
 
+impl BitwiseOp {
+    fn is_shift(&self) -> bool {
+        matches!(
+            self,
+            BitwiseOp::SHL | BitwiseOp::SHR | BitwiseOp::ROL | BitwiseOp::ROR
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct BitwiseOperationModule {
     op: BitwiseOp,
-    operand: String, // For binary ops
+    operand: String, // For binary ops, or the bit count for shifts
+    output: ByteFormat,
 }
 
 impl Default for BitwiseOperationModule {
@@ -249,6 +877,29 @@ impl Default for BitwiseOperationModule {
         Self {
             op: BitwiseOp::NOT,
             operand: "0".to_string(),
+            output: ByteFormat::Raw,
+        }
+    }
+}
+
+impl BitwiseOperationModule {
+    /// The operand as a byte, or the bit count for shifts and rotations.
+    /// Accepts decimal or 0x-prefixed hex.
+    fn operand(&self) -> Result<u8, String> {
+        let text = self.operand.trim();
+        let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => u8::from_str_radix(hex, 16),
+            None => text.parse::<u8>(),
+        };
+        match value {
+            Ok(bits) if self.op.is_shift() && bits > 7 => {
+                Err(format!("Bit count must be 0-7, not {}", bits))
+            }
+            Ok(value) => Ok(value),
+            Err(_) if self.op.is_shift() => {
+                Err(format!("\"{}\" is not a bit count from 0 to 7", text))
+            }
+            Err(_) => Err(format!("\"{}\" is not a byte value from 0 to 255", text)),
         }
     }
 }
@@ -258,11 +909,28 @@ impl Module for BitwiseOperationModule {
         "Bitwise Operation"
     }
 
-    fn process(&self, input: &str) -> String {
+    fn id(&self) -> &str {
+        "bitwise"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(self.output.format(&self.output_bytes(input)?))
+    }
+
+    fn binary_output(&self) -> bool {
+        self.output == ByteFormat::Raw
+    }
+
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
         // Treat input as bytes
-        let operand_val = self.operand.parse::<u8>().unwrap_or(0);
+        let operand_val = match self.op {
+            BitwiseOp::NOT => 0,
+            _ => self.operand().map_err(ProcessError::BadConfig)?,
+        };
+        // Shifts and rotations work within each byte
+        let bits = operand_val as u32;
 
-        let result: Vec<u8> = input
+        Ok(input
             .bytes()
             .map(|b| match self.op {
                 BitwiseOp::NOT => !b,
@@ -272,11 +940,31 @@ impl Module for BitwiseOperationModule {
                 BitwiseOp::NAND => !(b & operand_val),
                 BitwiseOp::NOR => !(b | operand_val),
                 BitwiseOp::XNOR => !(b ^ operand_val),
+                BitwiseOp::SHL => b.checked_shl(bits).unwrap_or(0),
+                BitwiseOp::SHR => b.checked_shr(bits).unwrap_or(0),
+                BitwiseOp::ROL => b.rotate_left(bits % 8),
+                BitwiseOp::ROR => b.rotate_right(bits % 8),
             })
-            .collect();
+            .collect())
+    }
 
-        // Try to convert back to string, or show hex
-        String::from_utf8_lossy(&result).to_string()
+    fn inverse(&self) -> Option<Inverse> {
+        // Formatted output cannot be read back as the input bytes
+        if self.output != ByteFormat::Raw {
+            return None;
+        }
+        match self.op {
+            BitwiseOp::NOT | BitwiseOp::XOR | BitwiseOp::XNOR => Some(Inverse::Itself),
+            BitwiseOp::ROL => Some(Inverse::changed(
+                self,
+                serde_json::json!({ "op": BitwiseOp::ROR }),
+            )),
+            BitwiseOp::ROR => Some(Inverse::changed(
+                self,
+                serde_json::json!({ "op": BitwiseOp::ROL }),
+            )),
+            _ => None,
+        }
     }
 
     fn ui(&mut self, ui: &mut egui::Ui) {
@@ -291,13 +979,109 @@ impl Module for BitwiseOperationModule {
                     ui.selectable_value(&mut self.op, BitwiseOp::NAND, "NAND");
                     ui.selectable_value(&mut self.op, BitwiseOp::NOR, "NOR");
                     ui.selectable_value(&mut self.op, BitwiseOp::XNOR, "XNOR");
+                    ui.selectable_value(&mut self.op, BitwiseOp::SHL, "SHL (shift left)");
+                    ui.selectable_value(&mut self.op, BitwiseOp::SHR, "SHR (shift right)");
+                    ui.selectable_value(&mut self.op, BitwiseOp::ROL, "ROL (rotate left)");
+                    ui.selectable_value(&mut self.op, BitwiseOp::ROR, "ROR (rotate right)");
                 });
 
-            if self.op != BitwiseOp::NOT {
-                ui.label("Operand (0-255):");
+            if self.op.is_shift() {
+                ui.label("Bits (0-7):");
+                ui.text_edit_singleline(&mut self.operand);
+            } else if self.op != BitwiseOp::NOT {
+                ui.label("Operand (0-255 or 0x00-0xFF):");
                 ui.text_edit_singleline(&mut self.operand);
             }
         });
+        if self.op != BitwiseOp::NOT {
+            if let Err(e) = self.operand() {
+                ui.colored_label(egui::Color32::from_rgb(220, 160, 0), format!("⚠ {}", e));
+            }
+        }
+        self.output.ui(ui, "Output:");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AffixMode {
+    Add,
+    Remove,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AffixModule {
+    prefix: String,
+    suffix: String,
+    mode: AffixMode,
+}
+
+impl Default for AffixModule {
+    fn default() -> Self {
+        Self {
+            prefix: "flag{".to_string(),
+            suffix: "}".to_string(),
+            mode: AffixMode::Add,
+        }
+    }
+}
+
+impl Module for AffixModule {
+    fn name(&self) -> &str {
+        "Prefix / Suffix"
+    }
+
+    fn id(&self) -> &str {
+        "affix"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            AffixMode::Add => format!("{}{}{}", self.prefix, input, self.suffix),
+            AffixMode::Remove => {
+                let Some(rest) = input.strip_prefix(self.prefix.as_str()) else {
+                    return Err(ProcessError::InvalidInput(format!(
+                        "Prefix \"{}\" not found",
+                        self.prefix
+                    )));
+                };
+                let Some(rest) = rest.strip_suffix(self.suffix.as_str()) else {
+                    return Err(ProcessError::InvalidInput(format!(
+                        "Suffix \"{}\" not found",
+                        self.suffix
+                    )));
+                };
+                rest.to_string()
+            }
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        let mode = match self.mode {
+            AffixMode::Add => AffixMode::Remove,
+            AffixMode::Remove => AffixMode::Add,
+        };
+        Some(Inverse::changed(self, serde_json::json!({ "mode": mode })))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, AffixMode::Add, "Add");
+            ui.radio_value(&mut self.mode, AffixMode::Remove, "Remove");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Prefix:");
+            ui.text_edit_singleline(&mut self.prefix);
+            ui.label("Suffix:");
+            ui.text_edit_singleline(&mut self.suffix);
+        });
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -308,3 +1092,1368 @@ impl Module for BitwiseOperationModule {
         self
     }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct SeparateModule {
+    count: usize,
+    separator: String,
+    mode: AffixMode,
+}
+
+impl Default for SeparateModule {
+    fn default() -> Self {
+        Self {
+            count: 2,
+            separator: " ".to_string(),
+            mode: AffixMode::Add,
+        }
+    }
+}
+
+impl Module for SeparateModule {
+    fn name(&self) -> &str {
+        "Separate"
+    }
+
+    fn id(&self) -> &str {
+        "separate"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(match self.mode {
+            // Every character counts, whitespace included
+            AffixMode::Add => input
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(self.count.max(1))
+                .map(|chunk| chunk.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join(&self.separator),
+            AffixMode::Remove if self.separator.is_empty() => input.to_string(),
+            AffixMode::Remove => input.replace(&self.separator, ""),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        let mode = match self.mode {
+            AffixMode::Add => AffixMode::Remove,
+            AffixMode::Remove => AffixMode::Add,
+        };
+        Some(Inverse::changed(self, serde_json::json!({ "mode": mode })))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, AffixMode::Add, "Insert");
+            ui.radio_value(&mut self.mode, AffixMode::Remove, "Remove");
+        });
+        ui.horizontal(|ui| {
+            if self.mode == AffixMode::Add {
+                ui.label("Every");
+                ui.add(egui::DragValue::new(&mut self.count).range(1..=1024));
+                ui.label("characters, insert:");
+            } else {
+                ui.label("Remove every:");
+            }
+            ui.text_edit_singleline(&mut self.separator);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Bytes (2)").clicked() {
+                self.count = 2;
+            }
+            if ui.button("Nibbles (4)").clicked() {
+                self.count = 4;
+            }
+            if ui.button("Octets (8)").clicked() {
+                self.count = 8;
+            }
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum WordOp {
+    Reverse,
+    Capitalize,
+    /// Rotate the characters of each word left by `amount`
+    Rotate,
+    SortLetters,
+}
+
+impl WordOp {
+    const ALL: [WordOp; 4] = [
+        WordOp::Reverse,
+        WordOp::Capitalize,
+        WordOp::Rotate,
+        WordOp::SortLetters,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            WordOp::Reverse => "Reverse each word",
+            WordOp::Capitalize => "Capitalize each word",
+            WordOp::Rotate => "Rotate letters in each word",
+            WordOp::SortLetters => "Sort letters in each word",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PerWordModule {
+    op: WordOp,
+    amount: i32,
+}
+
+impl Default for PerWordModule {
+    fn default() -> Self {
+        Self {
+            op: WordOp::Reverse,
+            amount: 1,
+        }
+    }
+}
+
+impl PerWordModule {
+    fn apply(&self, word: &str) -> String {
+        let mut chars: Vec<char> = word.chars().collect();
+        match self.op {
+            WordOp::Reverse => chars.reverse(),
+            WordOp::Capitalize => {
+                return chars
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, c)| {
+                        if i == 0 {
+                            c.to_uppercase().collect::<Vec<_>>()
+                        } else {
+                            c.to_lowercase().collect()
+                        }
+                    })
+                    .collect();
+            }
+            WordOp::Rotate => {
+                let len = chars.len() as i32;
+                if len > 0 {
+                    chars.rotate_left(self.amount.rem_euclid(len) as usize);
+                }
+            }
+            WordOp::SortLetters => chars.sort_unstable(),
+        }
+        chars.into_iter().collect()
+    }
+}
+
+impl Module for PerWordModule {
+    fn name(&self) -> &str {
+        "Per Word"
+    }
+
+    fn id(&self) -> &str {
+        "per_word"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Walk runs of whitespace / non-whitespace so the spacing survives untouched
+        let mut result = String::with_capacity(input.len());
+        let mut word = String::new();
+        for c in input.chars() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    result.push_str(&self.apply(&word));
+                    word.clear();
+                }
+                result.push(c);
+            } else {
+                word.push(c);
+            }
+        }
+        if !word.is_empty() {
+            result.push_str(&self.apply(&word));
+        }
+        Ok(result)
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        match self.op {
+            WordOp::Reverse => Some(Inverse::Itself),
+            WordOp::Rotate => Some(Inverse::changed(
+                self,
+                serde_json::json!({ "amount": self.amount.wrapping_neg() }),
+            )),
+            WordOp::Capitalize | WordOp::SortLetters => None,
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::ComboBox::from_label("Operation")
+            .selected_text(self.op.label())
+            .show_ui(ui, |ui| {
+                for op in WordOp::ALL {
+                    ui.selectable_value(&mut self.op, op, op.label());
+                }
+            });
+        if self.op == WordOp::Rotate {
+            ui.horizontal(|ui| {
+                ui.label("Rotate left by:");
+                ui.add(egui::DragValue::new(&mut self.amount));
+            });
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// A module held inside another one, saved as its id plus its own settings.
+struct InnerModule {
+    module: Box<dyn Module>,
+}
+
+impl InnerModule {
+    fn new(id: &str) -> Option<Self> {
+        Some(Self {
+            module: crate::modules::create_module(id)?,
+        })
+    }
+}
+
+impl Serialize for InnerModule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde_json::json!({ "id": self.module.id(), "config": self.module.export_config() })
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for InnerModule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Saved {
+            id: String,
+            #[serde(default)]
+            config: serde_json::Value,
+        }
+        let saved = Saved::deserialize(deserializer)?;
+        if saved.id == "split_apply" {
+            return Err(serde::de::Error::custom(
+                "Split & Apply cannot be nested in itself",
+            ));
+        }
+        let mut inner = InnerModule::new(&saved.id)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown module \"{}\"", saved.id)))?;
+        inner
+            .module
+            .import_config(saved.config)
+            .map_err(serde::de::Error::custom)?;
+        Ok(inner)
+    }
+}
+
+/// Turn the escapes `\n`, `\t` and `\\` typed into a delimiter field into
+/// the characters they stand for.
+fn unescape_delimiter(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SplitApplyModule {
+    /// Split the input on this (escapes: \n, \t)
+    split_on: String,
+    /// Rejoin the processed parts with this
+    join_with: String,
+    inner: InnerModule,
+}
+
+impl Default for SplitApplyModule {
+    fn default() -> Self {
+        let mut inner = InnerModule::new("case_transform").expect("case_transform exists");
+        let _ = inner
+            .module
+            .import_config(serde_json::json!({ "mode": "UpperCase" }));
+        Self {
+            split_on: String::from(","),
+            join_with: String::from(","),
+            inner,
+        }
+    }
+}
+
+impl Module for SplitApplyModule {
+    fn name(&self) -> &str {
+        "Split & Apply"
+    }
+
+    fn id(&self) -> &str {
+        "split_apply"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let split_on = unescape_delimiter(&self.split_on);
+        if split_on.is_empty() {
+            return self.inner.module.process(input);
+        }
+        let parts = input
+            .split(split_on.as_str())
+            .map(|part| self.inner.module.process(part))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(parts.join(&unescape_delimiter(&self.join_with)))
+    }
+
+    fn binary_output(&self) -> bool {
+        self.inner.module.binary_output()
+    }
+
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
+        let split_on = unescape_delimiter(&self.split_on);
+        if split_on.is_empty() {
+            return self.inner.module.output_bytes(input);
+        }
+        let parts = input
+            .split(split_on.as_str())
+            .map(|part| self.inner.module.output_bytes(part))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(parts.join(unescape_delimiter(&self.join_with).as_bytes()))
+    }
+
+    fn state_hash(&self) -> u64 {
+        self.inner.module.state_hash()
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        let inner = match self.inner.module.inverse()? {
+            Inverse::PassThrough => return Some(Inverse::PassThrough),
+            Inverse::Itself if self.split_on == self.join_with => return Some(Inverse::Itself),
+            Inverse::Itself => self.inner.module.export_config(),
+            Inverse::Settings(config) => config,
+        };
+        // Split where the parts were joined, and join them as they were split
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({
+                "split_on": self.join_with,
+                "join_with": self.split_on,
+                "inner": { "id": self.inner.module.id(), "config": inner },
+            }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Split on:");
+            ui.add(egui::TextEdit::singleline(&mut self.split_on).desired_width(60.0));
+            ui.label("Join with:");
+            ui.add(egui::TextEdit::singleline(&mut self.join_with).desired_width(60.0));
+        })
+        .response
+        .on_hover_text("Use \\n for a newline and \\t for a tab");
+        ui.horizontal(|ui| {
+            ui.label("Apply to each part:");
+            egui::ComboBox::from_id_salt("split_apply_inner")
+                .selected_text(rust_i18n::t!(format!("modules.{}", self.inner.module.id())))
+                .show_ui(ui, |ui| {
+                    // Nesting another container is not supported
+                    for &id in crate::modules::MODULE_IDS
+                        .iter()
+                        .filter(|&&id| id != "split_apply")
+                    {
+                        if ui
+                            .selectable_label(
+                                self.inner.module.id() == id,
+                                rust_i18n::t!(format!("modules.{}", id)),
+                            )
+                            .clicked()
+                        {
+                            if let Some(inner) = InnerModule::new(id) {
+                                self.inner = inner;
+                            }
+                        }
+                    }
+                });
+        });
+        ui.group(|ui| {
+            ui.push_id("split_apply_inner_ui", |ui| self.inner.module.ui(ui));
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SwapType {
+    /// Reverse the byte order within each word (endianness conversion)
+    Bytes,
+    /// Swap the high and low nibble of every byte
+    Nibbles,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Remainder {
+    /// Leave a trailing partial word untouched
+    Leave,
+    /// Zero-pad a trailing partial word to the full size before swapping
+    Pad,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ByteOrderModule {
+    swap: SwapType,
+    word_size: usize,
+    remainder: Remainder,
+    input: ByteFormat,
+    output: ByteFormat,
+}
+
+impl Default for ByteOrderModule {
+    fn default() -> Self {
+        Self {
+            swap: SwapType::Bytes,
+            word_size: 4,
+            remainder: Remainder::Leave,
+            input: ByteFormat::Hex,
+            output: ByteFormat::Hex,
+        }
+    }
+}
+
+impl Module for ByteOrderModule {
+    fn name(&self) -> &str {
+        "Byte Order"
+    }
+
+    fn id(&self) -> &str {
+        "byteorder"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let mut bytes = self
+            .input
+            .parse(input)
+            .map_err(ProcessError::InvalidInput)?;
+
+        match self.swap {
+            SwapType::Nibbles => {
+                for b in bytes.iter_mut() {
+                    *b = b.rotate_left(4);
+                }
+            }
+            SwapType::Bytes => {
+                if ![2, 4, 8].contains(&self.word_size) {
+                    return Err(ProcessError::BadConfig(format!(
+                        "Word size must be 2, 4 or 8 bytes, got {}",
+                        self.word_size
+                    )));
+                }
+                if self.remainder == Remainder::Pad {
+                    let padded_len = bytes.len().div_ceil(self.word_size) * self.word_size;
+                    bytes.resize(padded_len, 0);
+                }
+                for word in bytes.chunks_exact_mut(self.word_size) {
+                    word.reverse();
+                }
+            }
+        }
+
+        Ok(self.output.format(&bytes))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        // Padding a partial word adds bytes that a second swap keeps
+        if self.swap == SwapType::Bytes && self.remainder == Remainder::Pad {
+            return None;
+        }
+        if self.input == self.output {
+            return Some(Inverse::Itself);
+        }
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "input": self.output, "output": self.input }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.swap, SwapType::Bytes, "Swap byte order");
+            ui.radio_value(&mut self.swap, SwapType::Nibbles, "Swap nibbles");
+        });
+        if self.swap == SwapType::Bytes {
+            ui.horizontal(|ui| {
+                ui.label("Word size:");
+                ui.radio_value(&mut self.word_size, 2, "2 bytes");
+                ui.radio_value(&mut self.word_size, 4, "4 bytes");
+                ui.radio_value(&mut self.word_size, 8, "8 bytes");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Partial last word:");
+                ui.radio_value(&mut self.remainder, Remainder::Leave, "Leave as is");
+                ui.radio_value(&mut self.remainder, Remainder::Pad, "Zero-pad");
+            });
+        }
+        self.input.ui(ui, "Input:");
+        self.output.ui(ui, "Output:");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BitReverseModule {
+    input: ByteFormat,
+    output: ByteFormat,
+}
+
+impl Default for BitReverseModule {
+    fn default() -> Self {
+        Self {
+            input: ByteFormat::Hex,
+            output: ByteFormat::Hex,
+        }
+    }
+}
+
+impl Module for BitReverseModule {
+    fn name(&self) -> &str {
+        "Bit Reverse"
+    }
+
+    fn id(&self) -> &str {
+        "bit_reverse"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let bytes = self
+            .input
+            .parse(input)
+            .map_err(ProcessError::InvalidInput)?;
+        let reversed: Vec<u8> = bytes.iter().map(|b| b.reverse_bits()).collect();
+        Ok(self.output.format(&reversed))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        if self.input == self.output {
+            return Some(Inverse::Itself);
+        }
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "input": self.output, "output": self.input }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Reverse the bit order within each byte (MSB ↔ LSB)");
+        self.input.ui(ui, "Input:");
+        self.output.ui(ui, "Output:");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Convert a byte to reflected binary Gray code
+pub fn to_gray(b: u8) -> u8 {
+    b ^ (b >> 1)
+}
+
+/// Invert `to_gray` by folding the shifted value back in
+pub fn from_gray(g: u8) -> u8 {
+    let mut b = g;
+    let mut shift = g >> 1;
+    while shift != 0 {
+        b ^= shift;
+        shift >>= 1;
+    }
+    b
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GrayCodeModule {
+    direction: Direction,
+    input: ByteFormat,
+    output: ByteFormat,
+}
+
+impl Default for GrayCodeModule {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Encode,
+            input: ByteFormat::Raw,
+            output: ByteFormat::Binary,
+        }
+    }
+}
+
+impl Module for GrayCodeModule {
+    fn name(&self) -> &str {
+        "Gray Code"
+    }
+
+    fn id(&self) -> &str {
+        "gray"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let bytes = self
+            .input
+            .parse(input)
+            .map_err(ProcessError::InvalidInput)?;
+        let result: Vec<u8> = bytes
+            .iter()
+            .map(|&b| match self.direction {
+                Direction::Encode => to_gray(b),
+                Direction::Decode => from_gray(b),
+            })
+            .collect();
+        Ok(self.output.format(&result))
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({
+                "direction": self.direction.inverted(),
+                "input": self.output,
+                "output": self.input,
+            }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.direction, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.direction, Direction::Decode, "Decode");
+        });
+        self.input.ui(ui, "Input:");
+        self.output.ui(ui, "Output:");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// XOR every byte with the previous ciphertext byte, starting from `iv`
+pub fn chain_xor_encode(bytes: &[u8], iv: u8) -> Vec<u8> {
+    let mut previous = iv;
+    bytes
+        .iter()
+        .map(|&b| {
+            previous ^= b;
+            previous
+        })
+        .collect()
+}
+
+/// Undo `chain_xor_encode` by XORing every byte with the previous input byte
+pub fn chain_xor_decode(bytes: &[u8], iv: u8) -> Vec<u8> {
+    let mut previous = iv;
+    bytes
+        .iter()
+        .map(|&b| {
+            let plain = b ^ previous;
+            previous = b;
+            plain
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChainXorModule {
+    direction: Direction,
+    iv: String,
+    input: ByteFormat,
+    output: ByteFormat,
+}
+
+impl Default for ChainXorModule {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Encode,
+            iv: "0".to_string(),
+            input: ByteFormat::Raw,
+            output: ByteFormat::Hex,
+        }
+    }
+}
+
+impl Module for ChainXorModule {
+    fn name(&self) -> &str {
+        "Chained XOR"
+    }
+
+    fn id(&self) -> &str {
+        "chain_xor"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(self.output.format(&self.output_bytes(input)?))
+    }
+
+    fn binary_output(&self) -> bool {
+        self.output == ByteFormat::Raw
+    }
+
+    fn output_bytes(&self, input: &str) -> Result<Vec<u8>, ProcessError> {
+        let Ok(iv) = self.iv.trim().parse::<u8>() else {
+            return Err(ProcessError::BadConfig(format!(
+                "Invalid IV '{}', expected 0-255",
+                self.iv.trim()
+            )));
+        };
+        let bytes = self
+            .input
+            .parse(input)
+            .map_err(ProcessError::InvalidInput)?;
+        Ok(match self.direction {
+            Direction::Encode => chain_xor_encode(&bytes, iv),
+            Direction::Decode => chain_xor_decode(&bytes, iv),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({
+                "direction": self.direction.inverted(),
+                "input": self.output,
+                "output": self.input,
+            }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.direction, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.direction, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("IV (0-255):");
+            ui.text_edit_singleline(&mut self.iv)
+                .on_hover_text("XORed with the first byte in place of a previous one");
+        });
+        self.input.ui(ui, "Input:");
+        self.output.ui(ui, "Output:");
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// Characters that look like ASCII ones, paired with the ASCII character.
+/// The first entry for a character is the one "Lookalike" writes.
+const CONFUSABLES: &[(char, char)] = &[
+    // Cyrillic
+    ('\u{0430}', 'a'),
+    ('\u{0441}', 'c'),
+    ('\u{0501}', 'd'),
+    ('\u{0435}', 'e'),
+    ('\u{04BB}', 'h'),
+    ('\u{0456}', 'i'),
+    ('\u{0458}', 'j'),
+    ('\u{043E}', 'o'),
+    ('\u{0440}', 'p'),
+    ('\u{051B}', 'q'),
+    ('\u{0455}', 's'),
+    ('\u{051D}', 'w'),
+    ('\u{0445}', 'x'),
+    ('\u{0443}', 'y'),
+    ('\u{0410}', 'A'),
+    ('\u{0412}', 'B'),
+    ('\u{0421}', 'C'),
+    ('\u{0415}', 'E'),
+    ('\u{041D}', 'H'),
+    ('\u{0406}', 'I'),
+    ('\u{0408}', 'J'),
+    ('\u{041A}', 'K'),
+    ('\u{041C}', 'M'),
+    ('\u{041E}', 'O'),
+    ('\u{0420}', 'P'),
+    ('\u{0405}', 'S'),
+    ('\u{0422}', 'T'),
+    ('\u{0425}', 'X'),
+    ('\u{04AE}', 'Y'),
+    // Greek
+    ('\u{03BD}', 'v'),
+    ('\u{039D}', 'N'),
+    ('\u{0396}', 'Z'),
+    ('\u{03BF}', 'o'),
+    ('\u{0391}', 'A'),
+    ('\u{0392}', 'B'),
+    ('\u{0395}', 'E'),
+    ('\u{0397}', 'H'),
+    ('\u{0399}', 'I'),
+    ('\u{039A}', 'K'),
+    ('\u{039C}', 'M'),
+    ('\u{039F}', 'O'),
+    ('\u{03A1}', 'P'),
+    ('\u{03A4}', 'T'),
+    ('\u{03A5}', 'Y'),
+    ('\u{03A7}', 'X'),
+    // Other scripts and symbols
+    ('\u{0261}', 'g'),
+    ('\u{217C}', 'l'),
+    ('\u{1D0F}', 'o'),
+    ('\u{2170}', 'i'),
+    ('\u{2160}', 'I'),
+    ('\u{2010}', '-'),
+];
+
+/// Offset from ASCII '!'..='~' to the fullwidth forms U+FF01..=U+FF5E
+const FULLWIDTH_OFFSET: u32 = 0xFEE0;
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum HomoglyphStyle {
+    /// Cyrillic and Greek letters that look like Latin ones
+    Lookalike,
+    /// Fullwidth forms of every printable ASCII character
+    Fullwidth,
+}
+
+/// The ASCII character `c` is meant to look like, if it is a known
+/// confusable or a fullwidth form
+pub fn skeleton_char(c: char) -> Option<char> {
+    if let Some(&(_, ascii)) = CONFUSABLES.iter().find(|&&(confusable, _)| confusable == c) {
+        return Some(ascii);
+    }
+    match c as u32 {
+        0xFF01..=0xFF5E => char::from_u32(c as u32 - FULLWIDTH_OFFSET),
+        0x3000 => Some(' '),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HomoglyphModule {
+    /// Encode swaps in lookalikes, decode maps them back to ASCII (the skeleton)
+    mode: Direction,
+    style: HomoglyphStyle,
+}
+
+impl Default for HomoglyphModule {
+    fn default() -> Self {
+        Self {
+            mode: Direction::Encode,
+            style: HomoglyphStyle::Lookalike,
+        }
+    }
+}
+
+impl Module for HomoglyphModule {
+    fn name(&self) -> &str {
+        "Homoglyphs"
+    }
+
+    fn id(&self) -> &str {
+        "homoglyph"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        Ok(input
+            .chars()
+            .map(|c| match (self.mode, self.style) {
+                (Direction::Encode, HomoglyphStyle::Lookalike) => CONFUSABLES
+                    .iter()
+                    .find(|&&(_, ascii)| ascii == c && c.is_ascii_alphabetic())
+                    .map_or(c, |&(confusable, _)| confusable),
+                (Direction::Encode, HomoglyphStyle::Fullwidth) => match c {
+                    '!'..='~' => char::from_u32(c as u32 + FULLWIDTH_OFFSET).unwrap_or(c),
+                    ' ' => '\u{3000}',
+                    _ => c,
+                },
+                (Direction::Decode, _) => skeleton_char(c).unwrap_or(c),
+            })
+            .collect())
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Disguise");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Skeleton");
+        });
+        if self.mode == Direction::Encode {
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.style, HomoglyphStyle::Lookalike, "Cyrillic/Greek")
+                    .on_hover_text("Swap letters that have a lookalike in another script");
+                ui.radio_value(&mut self.style, HomoglyphStyle::Fullwidth, "Fullwidth")
+                    .on_hover_text("Use the fullwidth form of every character");
+            });
+        }
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        let disguised = input
+            .chars()
+            .filter(|&c| skeleton_char(c).is_some())
+            .count();
+        if disguised > 0 {
+            ui.small(format!(
+                "The input has {} character(s) that imitate ASCII",
+                disguised
+            ));
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// What the line ending module turns every line break into
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum LineEndingMode {
+    Lf,
+    CrLf,
+    Cr,
+    /// Remove line breaks altogether
+    Strip,
+}
+
+impl LineEndingMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEndingMode::Lf => "\n",
+            LineEndingMode::CrLf => "\r\n",
+            LineEndingMode::Cr => "\r",
+            LineEndingMode::Strip => "",
+        }
+    }
+}
+
+/// How many CRLF, LF and CR line breaks `input` has, in that order. A CR
+/// directly followed by LF counts once, as CRLF.
+pub fn count_line_endings(input: &str) -> [usize; 3] {
+    let mut counts = [0; 3];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {
+                chars.next();
+                counts[0] += 1;
+            }
+            '\n' => counts[1] += 1,
+            '\r' => counts[2] += 1,
+            _ => {}
+        }
+    }
+    counts
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LineEndingModule {
+    mode: LineEndingMode,
+}
+
+impl Default for LineEndingModule {
+    fn default() -> Self {
+        Self {
+            mode: LineEndingMode::Lf,
+        }
+    }
+}
+
+impl Module for LineEndingModule {
+    fn name(&self) -> &str {
+        "Line Endings"
+    }
+
+    fn id(&self) -> &str {
+        "line_ending"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        // Any mix of CRLF, LF and CR comes out with the one chosen ending
+        Ok(input
+            .replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .replace('\n', self.mode.as_str()))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, LineEndingMode::Lf, "LF")
+                .on_hover_text("Unix, \\n");
+            ui.radio_value(&mut self.mode, LineEndingMode::CrLf, "CRLF")
+                .on_hover_text("Windows and most network protocols, \\r\\n");
+            ui.radio_value(&mut self.mode, LineEndingMode::Cr, "CR")
+                .on_hover_text("Classic Mac OS, \\r");
+            ui.radio_value(&mut self.mode, LineEndingMode::Strip, "Strip")
+                .on_hover_text("Remove every line break, joining the lines");
+        });
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, input: &str, _output: &str) {
+        let [crlf, lf, cr] = count_line_endings(input);
+        let found: Vec<String> = [(crlf, "CRLF"), (lf, "LF"), (cr, "CR")]
+            .iter()
+            .filter(|&&(count, _)| count > 0)
+            .map(|(count, name)| format!("{} {}", count, name))
+            .collect();
+        if found.is_empty() {
+            ui.small("The input has no line breaks");
+        } else if found.len() > 1 {
+            ui.small(format!(
+                "The input mixes line endings: {}",
+                found.join(", ")
+            ));
+        } else {
+            ui.small(format!("The input has {}", found[0]));
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::ModuleConfig;
+
+    #[test]
+    fn test_affix_add_then_remove() {
+        let mut module = AffixModule::default();
+        let framed = module.process("s3cret").unwrap();
+        assert_eq!(framed, "flag{s3cret}");
+        module.mode = AffixMode::Remove;
+        assert_eq!(module.process(&framed).unwrap(), "s3cret");
+        assert!(module.process("s3cret}").is_err());
+        assert!(module.process("flag{s3cret").is_err());
+    }
+
+    #[test]
+    fn test_bitwise_rotate_by_one() {
+        let rotate = |op| BitwiseOperationModule {
+            op,
+            operand: "1".to_string(),
+            output: ByteFormat::Hex,
+        };
+        // "é" is the bytes c3 a9
+        assert_eq!(rotate(BitwiseOp::ROL).process("é").unwrap(), "8753");
+        assert_eq!(rotate(BitwiseOp::ROR).process("é").unwrap(), "e1d4");
+        assert_eq!(rotate(BitwiseOp::SHL).process("é").unwrap(), "8652");
+        assert_eq!(rotate(BitwiseOp::SHR).process("é").unwrap(), "6154");
+    }
+
+    #[test]
+    fn test_byte_order_swaps_4_byte_words() {
+        let mut module = ByteOrderModule::default();
+        assert_eq!(
+            module.process("12345678 9abcdef0").unwrap(),
+            "78563412f0debc9a"
+        );
+        assert_eq!(module.process("1234567890ab").unwrap(), "7856341290ab");
+        module.remainder = Remainder::Pad;
+        assert_eq!(module.process("1234567890ab").unwrap(), "785634120000ab90");
+        for word_size in [0, 3, 1 << 40] {
+            module.word_size = word_size;
+            assert!(matches!(
+                module.process("1234"),
+                Err(ProcessError::BadConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_gray_code_round_trips_every_byte() {
+        for b in 0..=255u8 {
+            assert_eq!(from_gray(to_gray(b)), b);
+            // Neighbouring values differ in exactly one bit
+            if b > 0 {
+                assert_eq!((to_gray(b) ^ to_gray(b - 1)).count_ones(), 1);
+            }
+        }
+        let decode = GrayCodeModule {
+            direction: Direction::Decode,
+            input: ByteFormat::Binary,
+            output: ByteFormat::Raw,
+        };
+        let encoded = GrayCodeModule::default().process("Gray").unwrap();
+        assert_eq!(decode.process(&encoded).unwrap(), "Gray");
+    }
+
+    #[test]
+    fn test_separate_round_trip() {
+        let mut module = SeparateModule {
+            count: 2,
+            separator: ":".to_string(),
+            mode: AffixMode::Add,
+        };
+        let separated = module.process("deadbeef0").unwrap();
+        assert_eq!(separated, "de:ad:be:ef:0");
+        module.mode = AffixMode::Remove;
+        assert_eq!(module.process(&separated).unwrap(), "deadbeef0");
+    }
+
+    #[test]
+    fn test_per_word_reverse() {
+        let module = PerWordModule::default();
+        assert_eq!(module.process("hello world").unwrap(), "olleh dlrow");
+        assert_eq!(module.process("  two\tspaces ").unwrap(), "  owt\tsecaps ");
+    }
+
+    #[test]
+    fn test_mixed_radix_clock_time() {
+        let mut module = MixedRadixModule::default();
+        assert_eq!(module.process("3661").unwrap(), "1:01:01");
+        assert_eq!(module.process("86399").unwrap(), "23:59:59");
+        assert!(module.process("86400").is_err());
+        module.direction = Direction::Decode;
+        assert_eq!(module.process("1:01:01").unwrap(), "3661");
+        assert!(module.process("1:60:01").is_err());
+    }
+
+    #[test]
+    fn test_split_apply_uppercases_each_part() {
+        let mut module = SplitApplyModule::default();
+        assert_eq!(
+            module.process("alpha,bravo,,charlie").unwrap(),
+            "ALPHA,BRAVO,,CHARLIE"
+        );
+        module.join_with = "\\n".to_string();
+        assert_eq!(module.process("alpha,bravo").unwrap(), "ALPHA\nBRAVO");
+    }
+
+    #[test]
+    fn test_split_apply_forwards_to_inner_module() {
+        let mut module = SplitApplyModule::default();
+        module
+            .import_config(serde_json::json!({
+                "split_on": ",",
+                "join_with": ";",
+                "inner": { "id": "rc4", "config": { "mode": "Decode" } },
+            }))
+            .unwrap();
+        let rc4 = &module.inner.module;
+        assert!(module.binary_output());
+        assert_eq!(
+            module.output_bytes("8f,9d").unwrap(),
+            [
+                rc4.output_bytes("8f").unwrap(),
+                rc4.output_bytes("9d").unwrap()
+            ]
+            .join(&b';')
+        );
+
+        // A freshly drawn random IV changes the hash, so cached output is not reused
+        module
+            .import_config(serde_json::json!({
+                "inner": { "id": "block_cipher", "config": { "random_iv": true } },
+            }))
+            .unwrap();
+        let before = module.state_hash();
+        module.process("attack,dawn").unwrap();
+        assert_ne!(module.state_hash(), before);
+        assert_eq!(module.state_hash(), module.inner.module.state_hash());
+
+        let nested = module.import_config(serde_json::json!({
+            "inner": { "id": "split_apply", "config": {} },
+        }));
+        assert!(nested.is_err());
+    }
+
+    #[test]
+    fn test_bit_reverse_twice_is_identity() {
+        let module = BitReverseModule::default();
+        let all: String = (0..=255u8).map(|b| format!("{:02x}", b)).collect();
+        let once = module.process(&all).unwrap();
+        assert_ne!(once, all);
+        assert_eq!(module.process(&once).unwrap(), all);
+        assert_eq!(module.process("01 80 0f").unwrap(), "8001f0");
+    }
+
+    #[test]
+    fn test_chain_xor_round_trip_with_iv() {
+        assert_eq!(chain_xor_encode(b"AAA", 0x10), [0x51, 0x10, 0x51]);
+        assert_eq!(chain_xor_decode(&[0x51, 0x10, 0x51], 0x10), *b"AAA");
+
+        let encode = ChainXorModule {
+            iv: "90".to_string(),
+            ..Default::default()
+        };
+        let decode = ChainXorModule {
+            direction: Direction::Decode,
+            iv: "90".to_string(),
+            input: ByteFormat::Hex,
+            output: ByteFormat::Raw,
+        };
+        let encoded = encode.process("Chained ✓").unwrap();
+        assert_eq!(decode.process(&encoded).unwrap(), "Chained ✓");
+        assert!(ChainXorModule {
+            iv: "256".to_string(),
+            ..Default::default()
+        }
+        .process("x")
+        .is_err());
+    }
+
+    #[test]
+    fn test_bitwise_invalid_operand_is_an_error() {
+        let module = |op, operand: &str| BitwiseOperationModule {
+            op,
+            operand: operand.to_string(),
+            output: ByteFormat::Hex,
+        };
+        assert_eq!(module(BitwiseOp::XOR, "0x20").process("A").unwrap(), "61");
+        assert_eq!(module(BitwiseOp::XOR, "32").process("A").unwrap(), "61");
+        for (op, operand) in [
+            (BitwiseOp::XOR, "abc"),
+            (BitwiseOp::AND, "256"),
+            (BitwiseOp::OR, ""),
+            (BitwiseOp::ROL, "8"),
+            (BitwiseOp::SHL, "-1"),
+        ] {
+            assert!(matches!(
+                module(op, operand).process("A"),
+                Err(ProcessError::BadConfig(_))
+            ));
+        }
+        // NOT takes no operand
+        assert_eq!(module(BitwiseOp::NOT, "abc").process("A").unwrap(), "be");
+    }
+
+    #[test]
+    fn test_number_words_both_directions() {
+        let cases = [
+            (0, "zero"),
+            (13, "thirteen"),
+            (40, "forty"),
+            (1234, "one thousand two hundred thirty-four"),
+            (-507, "minus five hundred seven"),
+            (2_000_019, "two million nineteen"),
+        ];
+        for (n, words) in cases {
+            assert_eq!(number_to_words(n), words);
+            assert_eq!(words_to_numbers(words), n.to_string());
+        }
+        let encoder = NumberWordsModule::default();
+        assert_eq!(
+            encoder.process("7 dwarfs, 101 dalmatians").unwrap(),
+            "seven dwarfs, one hundred one dalmatians"
+        );
+        assert_eq!(
+            words_to_numbers("One hundred and one dalmatians"),
+            "101 dalmatians"
+        );
+    }
+
+    #[test]
+    fn test_homoglyph_skeleton_recovers_ascii() {
+        let text = "Paypal login: Secure-Account 2024!";
+        let skeleton = HomoglyphModule {
+            mode: Direction::Decode,
+            ..Default::default()
+        };
+        for style in [HomoglyphStyle::Lookalike, HomoglyphStyle::Fullwidth] {
+            let disguised = HomoglyphModule {
+                style,
+                ..Default::default()
+            }
+            .process(text)
+            .unwrap();
+            assert_ne!(disguised, text);
+            assert!(!disguised.is_ascii());
+            assert_eq!(skeleton.process(&disguised).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn test_split_tokens_detects_separator() {
+        for input in [
+            "8,5,12", "8 5 12", "8, 5, 12", "8\n5\n12", "8-5-12", " 8;5;12;",
+        ] {
+            assert_eq!(split_tokens(input), ["8", "5", "12"], "{:?}", input);
+        }
+        // The most common punctuation wins, so other characters stay inside tokens
+        assert_eq!(split_tokens("1.5,2,3"), ["1.5", "2", "3"]);
+    }
+
+    #[test]
+    fn test_line_endings_convert_and_are_idempotent() {
+        let mixed = "one\r\ntwo\nthree\rfour\n\rfive";
+        assert_eq!(count_line_endings(mixed), [1, 2, 2]);
+        for (mode, expected) in [
+            (LineEndingMode::Lf, "one\ntwo\nthree\nfour\n\nfive"),
+            (
+                LineEndingMode::CrLf,
+                "one\r\ntwo\r\nthree\r\nfour\r\n\r\nfive",
+            ),
+            (LineEndingMode::Cr, "one\rtwo\rthree\rfour\r\rfive"),
+            (LineEndingMode::Strip, "onetwothreefourfive"),
+        ] {
+            let module = LineEndingModule { mode };
+            let converted = module.process(mixed).unwrap();
+            assert_eq!(converted, expected);
+            assert_eq!(module.process(&converted).unwrap(), converted);
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use crate::module::{Direction, Inverse, Module, ProcessError};
+use crate::modules::cipher::{columnar_columns, columnar_decrypt};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Top row of the English "AT ONE SIR" straddling checkerboard; the two
+/// blanks become the row digits of the two lower rows
+const CHECKERBOARD_TOP: [Option<char>; 10] = [
+    Some('A'),
+    Some('T'),
+    None,
+    Some('O'),
+    Some('N'),
+    Some('E'),
+    None,
+    Some('S'),
+    Some('I'),
+    Some('R'),
+];
+
+/// Lower rows of the checkerboard; '/' is the figure shift for digits
+const CHECKERBOARD_ROWS: [&str; 2] = ["BCDFGHJKLM", "PQUVWXYZ./"];
+
+const FIGURE_SHIFT: char = '/';
+
+/// Everything derived from the key material, named after the lines of the
+/// worksheet in the published descriptions of the cipher.
+struct VicKeys {
+    /// Keygroup minus the first five date digits
+    c: Vec<u8>,
+    /// Sequenced halves of the phrase
+    e1: Vec<u8>,
+    e2: Vec<u8>,
+    /// C extended to ten digits by chain addition
+    f1: Vec<u8>,
+    /// E1 + F1
+    g: Vec<u8>,
+    /// G looked up through the 1234567890 -> E2 table
+    h: Vec<u8>,
+    /// Sequenced H, the column order for reading the block
+    j: Vec<u8>,
+    /// Lines K to P: five rows of chain addition continuing from H
+    block: Vec<Vec<u8>>,
+    /// Transposition key digits taken from the block
+    q: Vec<u8>,
+    r: Vec<u8>,
+    /// Sequenced P, the checkerboard header
+    s: Vec<u8>,
+}
+
+// VIC Cipher Module
+#[derive(Serialize, Deserialize)]
+pub struct VicCipherModule {
+    /// At least 20 letters; historically the opening of a song
+    phrase: String,
+    /// Date digits; the first five are used
+    date: String,
+    personal_number: u8,
+    /// Five-digit message indicator, sent with the message
+    keygroup: String,
+    mode: Direction,
+}
+
+impl Default for VicCipherModule {
+    fn default() -> Self {
+        Self {
+            phrase: "TWASTHENIGHTBEFORECHRISTMAS".to_string(),
+            date: "741776".to_string(),
+            personal_number: 6,
+            keygroup: "77651".to_string(),
+            mode: Direction::Encode,
+        }
+    }
+}
+
+impl VicCipherModule {
+    fn derive_keys(&self) -> Result<VicKeys, String> {
+        let phrase: Vec<char> = self
+            .phrase
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .map(|c| c.to_ascii_uppercase())
+            .take(20)
+            .collect();
+        if phrase.len() < 20 {
+            return Err("The phrase needs at least 20 letters".to_string());
+        }
+        let date = parse_digits(&self.date, "Date")?;
+        if date.len() < 5 {
+            return Err("The date needs at least 5 digits".to_string());
+        }
+        let keygroup = parse_digits(&self.keygroup, "Keygroup")?;
+        if keygroup.len() != 5 {
+            return Err("The keygroup must be exactly 5 digits".to_string());
+        }
+        // Larger numbers would read past the 50-digit block
+        if !(1..=16).contains(&self.personal_number) {
+            return Err("The personal number must be between 1 and 16".to_string());
+        }
+
+        let c: Vec<u8> = keygroup
+            .iter()
+            .zip(&date)
+            .map(|(&k, &d)| (k + 10 - d) % 10)
+            .collect();
+        let e1 = sequence_digits(&rank(&phrase[..10]));
+        let e2 = sequence_digits(&rank(&phrase[10..]));
+        let f1 = [c.clone(), chain_add(&c, 5)].concat();
+        let g: Vec<u8> = e1.iter().zip(&f1).map(|(&e, &f)| (e + f) % 10).collect();
+        // Find each digit of G in 1234567890 and take the E2 digit below it
+        let h: Vec<u8> = g.iter().map(|&d| e2[(d as usize + 9) % 10]).collect();
+        let j = sequence_digits(&rank(&digit_weights(&h)));
+        let block: Vec<Vec<u8>> = chain_add(&h, 50).chunks(10).map(<[u8]>::to_vec).collect();
+        let p = &block[4];
+
+        // The last two unequal digits of P, plus the personal number, give the key lengths
+        let last = p[9];
+        let before = p[..9]
+            .iter()
+            .rev()
+            .find(|&&d| d != last)
+            .copied()
+            .unwrap_or(last);
+        let first_len = (self.personal_number + before) as usize;
+        let second_len = (self.personal_number + last) as usize;
+
+        // Read the block down its columns in the order given by J
+        let mut columns: Vec<usize> = (0..10).collect();
+        columns.sort_by_key(|&col| (j[col] + 9) % 10);
+        let read: Vec<u8> = columns
+            .iter()
+            .flat_map(|&col| block.iter().map(move |row| row[col]))
+            .collect();
+        let q = read[..first_len].to_vec();
+        let r = read[first_len..first_len + second_len].to_vec();
+        let s = sequence_digits(&rank(&digit_weights(p)));
+
+        Ok(VicKeys {
+            c,
+            e1,
+            e2,
+            f1,
+            g,
+            h,
+            j,
+            block,
+            q,
+            r,
+            s,
+        })
+    }
+
+    fn encode(&self, input: &str, keys: &VicKeys) -> String {
+        let digits: Vec<char> = checkerboard_encode(input, &keys.s).chars().collect();
+        let once = columnar_columns(&digits, &transposition_key(&keys.q)).concat();
+        let once: Vec<char> = once.chars().collect();
+        let twice: Vec<char> = columnar_columns(&once, &transposition_key(&keys.r))
+            .concat()
+            .chars()
+            .collect();
+        twice
+            .chunks(5)
+            .map(|group| group.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn decode(&self, input: &str, keys: &VicKeys) -> String {
+        let digits: Vec<char> = input.chars().filter(|c| c.is_ascii_digit()).collect();
+        let once: Vec<char> = columnar_decrypt(&digits, &transposition_key(&keys.r))
+            .chars()
+            .collect();
+        let plain = columnar_decrypt(&once, &transposition_key(&keys.q));
+        checkerboard_decode(&plain, &keys.s)
+    }
+}
+
+impl Module for VicCipherModule {
+    fn name(&self) -> &str {
+        "VIC Cipher"
+    }
+
+    fn id(&self) -> &str {
+        "vic"
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let keys = self.derive_keys().map_err(ProcessError::BadConfig)?;
+        Ok(match self.mode {
+            Direction::Encode => self.encode(input, &keys),
+            Direction::Decode => self.decode(input, &keys),
+        })
+    }
+
+    fn inverse(&self) -> Option<Inverse> {
+        Some(Inverse::changed(
+            self,
+            serde_json::json!({ "mode": self.mode.inverted() }),
+        ))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.mode, Direction::Encode, "Encode");
+            ui.radio_value(&mut self.mode, Direction::Decode, "Decode");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Phrase:");
+            ui.text_edit_singleline(&mut self.phrase)
+                .on_hover_text("Only the first 20 letters are used");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Date:");
+            ui.text_edit_singleline(&mut self.date)
+                .on_hover_text("Only the first 5 digits are used");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Personal number:");
+            ui.add(egui::DragValue::new(&mut self.personal_number).range(1..=16));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Keygroup:");
+            ui.text_edit_singleline(&mut self.keygroup)
+                .on_hover_text("Five-digit message indicator");
+        });
+        ui.small(
+            "Partial VIC: letters, '.' and digits only; both transpositions are plain \
+             columnar (the second is not disrupted) and the keygroup is not inserted \
+             into the message",
+        );
+    }
+
+    fn inspect_ui(&mut self, ui: &mut egui::Ui, _input: &str, _output: &str) {
+        let keys = match self.derive_keys() {
+            Ok(keys) => keys,
+            Err(_) => return,
+        };
+        ui.collapsing("Key derivation", |ui| {
+            let line = |ui: &mut egui::Ui, name: &str, digits: &[u8]| {
+                ui.monospace(format!("{:<3}{}", name, format_digits(digits)));
+            };
+            line(ui, "C", &keys.c);
+            line(ui, "E1", &keys.e1);
+            line(ui, "E2", &keys.e2);
+            line(ui, "F1", &keys.f1);
+            line(ui, "G", &keys.g);
+            line(ui, "H", &keys.h);
+            line(ui, "J", &keys.j);
+            for (name, row) in ["K", "L", "M", "N", "P"].iter().zip(&keys.block) {
+                line(ui, name, row);
+            }
+            line(ui, "Q", &keys.q);
+            line(ui, "R", &keys.r);
+            line(ui, "S", &keys.s);
+        });
+        ui.collapsing("Checkerboard", |ui| {
+            ui.monospace(format!("   {}", format_digits(&keys.s)));
+            let top: String = CHECKERBOARD_TOP.iter().map(|c| c.unwrap_or(' ')).collect();
+            ui.monospace(format!("   {}", top));
+            for (prefix, row) in row_prefixes(&keys.s).iter().zip(CHECKERBOARD_ROWS) {
+                ui.monospace(format!("{}  {}", prefix, row));
+            }
+        });
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+// VIC helper functions
+fn parse_digits(text: &str, field: &str) -> Result<Vec<u8>, String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or_else(|| format!("{} may only contain digits", field))
+        })
+        .collect()
+}
+
+fn format_digits(digits: &[u8]) -> String {
+    digits.iter().map(|d| char::from(b'0' + d)).collect()
+}
+
+/// Chain addition: each new digit is the sum (mod 10) of the digit `seed.len()`
+/// places back and the one after it. Returns only the `count` new digits.
+fn chain_add(seed: &[u8], count: usize) -> Vec<u8> {
+    let mut digits = seed.to_vec();
+    for i in 0..count {
+        digits.push((digits[i] + digits[i + 1]) % 10);
+    }
+    digits.split_off(seed.len())
+}
+
+/// 0-based rank of each item, ties broken left to right.
+fn rank<T: Ord>(items: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| &items[i]);
+    let mut ranks = vec![0; items.len()];
+    for (position, &i) in order.iter().enumerate() {
+        ranks[i] = position;
+    }
+    ranks
+}
+
+/// Ranks written the VIC way: 1 to 9, then 0 for the tenth.
+fn sequence_digits(ranks: &[usize]) -> Vec<u8> {
+    ranks.iter().map(|&r| ((r + 1) % 10) as u8).collect()
+}
+
+/// When sequencing digits, 0 counts as ten.
+fn digit_weights(digits: &[u8]) -> Vec<u8> {
+    digits
+        .iter()
+        .map(|&d| if d == 0 { 10 } else { d })
+        .collect()
+}
+
+/// A letter key for the columnar helpers that orders columns like the digits.
+fn transposition_key(digits: &[u8]) -> String {
+    rank(&digit_weights(digits))
+        .into_iter()
+        .map(|r| char::from(b'A' + r as u8))
+        .collect()
+}
+
+/// Row digits of the two lower checkerboard rows (the headers over the blanks).
+fn row_prefixes(header: &[u8]) -> Vec<char> {
+    CHECKERBOARD_TOP
+        .iter()
+        .zip(header)
+        .filter(|(c, _)| c.is_none())
+        .map(|(_, &d)| char::from(b'0' + d))
+        .collect()
+}
+
+fn checkerboard_code(c: char, header: &[u8]) -> Option<String> {
+    let digit = |col: usize| char::from(b'0' + header[col]);
+    if let Some(col) = CHECKERBOARD_TOP.iter().position(|&t| t == Some(c)) {
+        return Some(digit(col).to_string());
+    }
+    let prefixes = row_prefixes(header);
+    CHECKERBOARD_ROWS
+        .iter()
+        .zip(prefixes)
+        .find_map(|(row, prefix)| {
+            row.chars()
+                .position(|r| r == c)
+                .map(|col| format!("{}{}", prefix, digit(col)))
+        })
+}
+
+/// Letters and '.' use the board; a run of digits is written between figure
+/// shifts with each digit tripled. Anything else is dropped.
+fn checkerboard_encode(input: &str, header: &[u8]) -> String {
+    let figure = checkerboard_code(FIGURE_SHIFT, header).unwrap_or_default();
+    let mut result = String::new();
+    let mut in_figures = false;
+    for c in input.chars().map(|c| c.to_ascii_uppercase()) {
+        if c.is_ascii_digit() {
+            if !in_figures {
+                result.push_str(&figure);
+                in_figures = true;
+            }
+            result.extend([c; 3]);
+            continue;
+        }
+        let Some(code) = checkerboard_code(c, header).filter(|_| c != FIGURE_SHIFT) else {
+            continue;
+        };
+        if in_figures {
+            result.push_str(&figure);
+            in_figures = false;
+        }
+        result.push_str(&code);
+    }
+    if in_figures {
+        result.push_str(&figure);
+    }
+    result
+}
+
+fn checkerboard_decode(digits: &str, header: &[u8]) -> String {
+    let digits: Vec<char> = digits.chars().collect();
+    let prefixes = row_prefixes(header);
+    let column = |d: char| header.iter().position(|&h| char::from(b'0' + h) == d);
+    let mut result = String::new();
+    let mut in_figures = false;
+    let mut i = 0;
+
+    while i < digits.len() {
+        // In figure mode a tripled digit is a digit; anything else is the closing shift
+        if in_figures {
+            if let [a, b, c, ..] = digits[i..] {
+                if a == b && b == c {
+                    result.push(a);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        let Some(col) = column(digits[i]) else {
+            break;
+        };
+        let c = match CHECKERBOARD_TOP[col] {
+            Some(c) => {
+                i += 1;
+                c
+            }
+            None => {
+                let row = prefixes.iter().position(|&p| p == digits[i]).unwrap_or(0);
+                let Some(col) = digits.get(i + 1).and_then(|&d| column(d)) else {
+                    break;
+                };
+                i += 2;
+                CHECKERBOARD_ROWS[row].chars().nth(col).unwrap_or('?')
+            }
+        };
+        if c == FIGURE_SHIFT {
+            in_figures = !in_figures;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
@@ -1,18 +1,573 @@
-use crate::module::Module;
+use crate::cyberchef;
+use crate::module::{Inverse, Module, ProcessError};
 use crate::modules;
+use crate::modules::transform::ByteFormat;
 use eframe::egui;
+use lazy_static::lazy_static;
+use rand::Rng;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    /// A `${name}` reference to a saved stage output in a module setting
+    static ref VARIABLE_REF: regex::Regex = regex::Regex::new(r"\$\{(\w+)\}").unwrap();
+}
+
+/// Replace `${name}` references in every string of `config` with the saved
+/// outputs in `variables`. Returns whether anything was replaced, or an
+/// error naming a variable no earlier stage saved.
+fn substitute_variables(
+    config: &mut serde_json::Value,
+    variables: &HashMap<String, String>,
+) -> Result<bool, String> {
+    match config {
+        serde_json::Value::String(text) => {
+            if !VARIABLE_REF.is_match(text) {
+                return Ok(false);
+            }
+            if let Some(unknown) = VARIABLE_REF
+                .captures_iter(text)
+                .find(|refs| !variables.contains_key(&refs[1]))
+            {
+                return Err(format!(
+                    "Unknown variable {}: no earlier stage saves its output under that name",
+                    &unknown[0]
+                ));
+            }
+            *text = VARIABLE_REF
+                .replace_all(text, |refs: &regex::Captures| variables[&refs[1]].clone())
+                .into_owned();
+            Ok(true)
+        }
+        serde_json::Value::Array(items) => items.iter_mut().try_fold(false, |changed, item| {
+            Ok(substitute_variables(item, variables)? || changed)
+        }),
+        serde_json::Value::Object(fields) => {
+            fields.values_mut().try_fold(false, |changed, field| {
+                Ok(substitute_variables(field, variables)? || changed)
+            })
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Whether `${name}` is a reference `VARIABLE_REF` picks up whole; a name
+/// with spaces or braces could be saved but never used.
+fn valid_variable_name(name: &str) -> bool {
+    let reference = format!("${{{}}}", name);
+    VARIABLE_REF
+        .find(&reference)
+        .is_some_and(|found| found.as_str() == reference)
+}
+
+/// Names of the variables `config` references as `${name}`.
+fn variable_names(config: &serde_json::Value) -> Vec<String> {
+    match config {
+        serde_json::Value::String(text) => VARIABLE_REF
+            .captures_iter(text)
+            .map(|refs| refs[1].to_string())
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().flat_map(variable_names).collect(),
+        serde_json::Value::Object(fields) => fields.values().flat_map(variable_names).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Add "field: old → new" to `changes` for each setting that differs between
+/// `config` and `inverse`, naming nested settings by their path.
+fn describe_changes(
+    path: &str,
+    config: &serde_json::Value,
+    inverse: &serde_json::Value,
+    changes: &mut Vec<String>,
+) {
+    // Quote anything but a plain word, so "," or "" stays readable
+    let show = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text)
+            if !text.is_empty() && text.chars().all(char::is_alphanumeric) =>
+        {
+            text.clone()
+        }
+        other => other.to_string(),
+    };
+    match (config, inverse) {
+        (serde_json::Value::Object(fields), serde_json::Value::Object(inverse_fields)) => {
+            for (key, inverse_value) in inverse_fields {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let value = fields.get(key).unwrap_or(&serde_json::Value::Null);
+                describe_changes(&path, value, inverse_value, changes);
+            }
+        }
+        _ if config != inverse => {
+            changes.push(format!("{}: {} → {}", path, show(config), show(inverse)))
+        }
+        _ => {}
+    }
+}
+
+/// A module in the pipeline together with its per-stage settings.
+struct Stage {
+    module: Box<dyn Module>,
+    /// How many times `process` is applied in a row.
+    repeat: u32,
+    /// When off, the stage is bypassed and its input passes through unchanged
+    enabled: bool,
+    /// When set, the output box is editable and this text is passed
+    /// downstream instead of the computed output.
+    edited_output: Option<String>,
+    /// Hash of what the last output was computed from, and that output
+    cache: Option<(u64, Result<String, ProcessError>)>,
+    /// Bumped whenever the module's settings change, so the cache key does
+    /// not have to include the whole config
+    revision: u64,
+    /// The settings as of `revision`
+    config: serde_json::Value,
+    /// Variables `config` references, so settings without any are not
+    /// searched for them on every frame
+    references: Vec<String>,
+    /// How long the last run took; a result taken from the cache keeps the
+    /// time of the run that computed it
+    elapsed: Option<Duration>,
+    /// The module's help text is expanded
+    show_help: bool,
+    /// Name this stage's output is saved under, for `${name}` references in
+    /// the settings of later stages; empty to save nothing
+    variable: String,
+    /// A copy of the module with `${name}` references in its settings filled
+    /// in, used instead of `module` while it has any
+    resolved: Option<Box<dyn Module>>,
+    /// Hash of the revision and variable values `resolved` was made from
+    resolved_key: u64,
+    /// Show the output bytes as hex under the text, for modules whose
+    /// output may not be UTF-8
+    show_bytes: bool,
+    /// Like `cache`, for the output bytes shown when `show_bytes` is on
+    bytes_cache: Option<(u64, Result<Vec<u8>, ProcessError>)>,
+}
+
+impl Stage {
+    fn new(module: Box<dyn Module>) -> Self {
+        let config = module.export_config();
+        Self {
+            references: variable_names(&config),
+            config,
+            revision: 0,
+            module,
+            repeat: 1,
+            enabled: true,
+            edited_output: None,
+            cache: None,
+            elapsed: None,
+            show_help: false,
+            variable: String::new(),
+            resolved: None,
+            resolved_key: 0,
+            show_bytes: false,
+            bytes_cache: None,
+        }
+    }
+
+    /// The name this stage's output is saved under, if it is set and valid.
+    fn saved_variable(&self) -> Option<&str> {
+        Some(self.variable.trim()).filter(|name| valid_variable_name(name))
+    }
+
+    /// The module to run: `module`, or its copy with variables filled in.
+    fn active_module(&self) -> &dyn Module {
+        self.resolved.as_deref().unwrap_or(self.module.as_ref())
+    }
+
+    /// Fill `${name}` references in the module's settings from `variables`,
+    /// keeping the previous copy while neither the settings nor the values
+    /// of the variables they use have changed.
+    fn resolve_variables(
+        &mut self,
+        variables: &HashMap<String, String>,
+    ) -> Result<(), ProcessError> {
+        if self.references.is_empty() {
+            self.resolved = None;
+            return Ok(());
+        }
+        let mut hasher = DefaultHasher::new();
+        self.revision.hash(&mut hasher);
+        for name in &self.references {
+            variables.get(name).hash(&mut hasher);
+        }
+        let key = hasher.finish();
+        if self.resolved.is_some() && self.resolved_key == key {
+            return Ok(());
+        }
+        self.resolved = None;
+        let mut config = self.config.clone();
+        substitute_variables(&mut config, variables).map_err(ProcessError::BadConfig)?;
+        let mut module = modules::create_module(self.module.id())
+            .ok_or_else(|| ProcessError::BadConfig("Unknown module".to_string()))?;
+        module
+            .import_config(config)
+            .map_err(ProcessError::BadConfig)?;
+        self.resolved = Some(module);
+        self.resolved_key = key;
+        Ok(())
+    }
+
+    fn process(&self, input: &str) -> Result<String, ProcessError> {
+        let mut text = input.to_string();
+        for _ in 0..self.repeat {
+            text = self.active_module().process(&text)?;
+        }
+        Ok(text)
+    }
+
+    /// Process `input`, or each of its lines separately in column mode.
+    fn run(&self, input: &str, column_mode: bool) -> Result<String, ProcessError> {
+        if column_mode {
+            let cells = input
+                .split('\n')
+                .map(|cell| self.process(cell).and_then(single_line))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(cells.join("\n"))
+        } else {
+            self.process(input)
+        }
+    }
+
+    /// Bump `revision` if the settings differ from the last snapshot.
+    /// Returns whether they did.
+    fn track_config(&mut self) -> bool {
+        let config = self.module.export_config();
+        if config == self.config {
+            return false;
+        }
+        self.references = variable_names(&config);
+        self.config = config;
+        self.revision += 1;
+        true
+    }
+
+    /// The bytes of `run`'s output, taken from the module so that bytes
+    /// which are not UTF-8 survive. Column mode joins the cells with '\n'.
+    fn run_bytes(&self, input: &str, column_mode: bool) -> Result<Vec<u8>, ProcessError> {
+        let cell_bytes = |cell: &str| {
+            let mut text = cell.to_string();
+            for _ in 1..self.repeat {
+                text = self.active_module().process(&text)?;
+            }
+            self.active_module().output_bytes(&text)
+        };
+        if column_mode {
+            let cells = input
+                .split('\n')
+                .map(cell_bytes)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(cells.join(&b'\n'))
+        } else {
+            cell_bytes(input)
+        }
+    }
+
+    /// What a cached output depends on: the input, the run options and the
+    /// module's settings and state.
+    fn cache_key(&self, input: &str, column_mode: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        column_mode.hash(&mut hasher);
+        self.repeat.hash(&mut hasher);
+        self.revision.hash(&mut hasher);
+        self.active_module().state_hash().hash(&mut hasher);
+        self.resolved
+            .as_ref()
+            .map(|_| self.resolved_key)
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Like `run_bytes`, but reuse the previous bytes while the cache key
+    /// is unchanged.
+    fn run_bytes_cached(
+        &mut self,
+        input: &str,
+        column_mode: bool,
+    ) -> Result<Vec<u8>, ProcessError> {
+        let key = self.cache_key(input, column_mode);
+        match &self.bytes_cache {
+            Some((cached_key, bytes)) if *cached_key == key => bytes.clone(),
+            _ => {
+                let bytes = self.run_bytes(input, column_mode);
+                self.bytes_cache = Some((key, bytes.clone()));
+                bytes
+            }
+        }
+    }
+
+    /// Like `run`, but reuse the previous output when neither the input nor
+    /// the module settings have changed since.
+    fn run_cached(&mut self, input: &str, column_mode: bool) -> Result<String, ProcessError> {
+        let key = self.cache_key(input, column_mode);
+
+        match &self.cache {
+            Some((cached_key, output)) if *cached_key == key => output.clone(),
+            _ => {
+                let started = Instant::now();
+                let output = self.run(input, column_mode);
+                self.elapsed = Some(started.elapsed());
+                self.cache = Some((key, output.clone()));
+                output
+            }
+        }
+    }
+}
+
+/// Character set used to turn raw input bytes into text before the first stage,
+/// and the final text back into bytes after the last one.
+#[derive(PartialEq, Clone, Copy)]
+enum Charset {
+    Utf8,
+    Latin1,
+    Windows1252,
+    ShiftJis,
+}
+
+impl Charset {
+    const ALL: [Charset; 4] = [
+        Charset::Utf8,
+        Charset::Latin1,
+        Charset::Windows1252,
+        Charset::ShiftJis,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::Latin1 => "Latin-1 (ISO-8859-1)",
+            Charset::Windows1252 => "Windows-1252",
+            Charset::ShiftJis => "Shift_JIS",
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Charset::Utf8 => String::from_utf8_lossy(bytes).to_string(),
+            // encoding_rs treats ISO-8859-1 as Windows-1252, so map bytes to U+0000..U+00FF directly
+            Charset::Latin1 => bytes.iter().map(|&b| b as char).collect(),
+            Charset::Windows1252 => encoding_rs::WINDOWS_1252
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+            Charset::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+        }
+    }
+
+    /// Characters the charset cannot represent become `?` (Latin-1) or
+    /// numeric character references like `&#12354;` (encoding_rs).
+    fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            Charset::Utf8 => text.as_bytes().to_vec(),
+            Charset::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+                .collect(),
+            Charset::Windows1252 => encoding_rs::WINDOWS_1252.encode(text).0.into_owned(),
+            Charset::ShiftJis => encoding_rs::SHIFT_JIS.encode(text).0.into_owned(),
+        }
+    }
+
+    fn combo(&mut self, ui: &mut egui::Ui, label: &str) {
+        egui::ComboBox::from_label(label)
+            .selected_text(self.label())
+            .show_ui(ui, |ui| {
+                for charset in Charset::ALL {
+                    ui.selectable_value(self, charset, charset.label());
+                }
+            });
+    }
+}
+
+/// Text the input box starts with
+const PANGRAM: &str = "The quick brown fox jumps over the lazy dog.";
+
+const LOREM_IPSUM: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
+    eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis \
+    nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute \
+    irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla \
+    pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia \
+    deserunt mollit anim id est laborum.";
+
+/// Sample inputs offered next to the input box for trying modules out.
+#[derive(PartialEq, Clone, Copy)]
+enum SampleInput {
+    RandomText,
+    RandomBytes,
+    LoremIpsum,
+    Printable,
+    Pangram,
+}
+
+impl SampleInput {
+    const ALL: [SampleInput; 5] = [
+        SampleInput::RandomText,
+        SampleInput::RandomBytes,
+        SampleInput::LoremIpsum,
+        SampleInput::Printable,
+        SampleInput::Pangram,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SampleInput::RandomText => "Random ASCII text",
+            SampleInput::RandomBytes => "Random bytes (hex)",
+            SampleInput::LoremIpsum => "Lorem Ipsum",
+            SampleInput::Printable => "All printable ASCII",
+            SampleInput::Pangram => "Pangram",
+        }
+    }
+
+    fn generate(&self) -> String {
+        let mut rng = rand::rng();
+        match self {
+            SampleInput::RandomText => (0..64)
+                .map(|_| char::from(rng.random_range(0x20u8..0x7f)))
+                .collect(),
+            SampleInput::RandomBytes => {
+                let bytes: Vec<u8> = (0..32).map(|_| rng.random()).collect();
+                ByteFormat::Hex.format(&bytes)
+            }
+            SampleInput::LoremIpsum => LOREM_IPSUM.to_string(),
+            SampleInput::Printable => (0x20u8..0x7f).map(char::from).collect(),
+            SampleInput::Pangram => PANGRAM.to_string(),
+        }
+    }
+}
+
+/// Source-code literals a stage's output bytes can be copied as.
+#[derive(PartialEq, Clone, Copy)]
+enum ByteLiteral {
+    RustArray,
+    RustByteString,
+    C,
+    Python,
+}
+
+impl ByteLiteral {
+    const ALL: [ByteLiteral; 4] = [
+        ByteLiteral::RustArray,
+        ByteLiteral::RustByteString,
+        ByteLiteral::C,
+        ByteLiteral::Python,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ByteLiteral::RustArray => "Rust array [0x48, ...]",
+            ByteLiteral::RustByteString => "Rust byte string b\"...\"",
+            ByteLiteral::C => "C array {0x48, ...}",
+            ByteLiteral::Python => "Python bytes b\"...\"",
+        }
+    }
+
+    fn format(&self, bytes: &[u8]) -> String {
+        let hex_list = || {
+            bytes
+                .iter()
+                .map(|b| format!("0x{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            ByteLiteral::RustArray => format!("[{}]", hex_list()),
+            ByteLiteral::C => format!("unsigned char data[{}] = {{{}}};", bytes.len(), hex_list()),
+            // Both accept \xNN escapes and the same simple escapes
+            ByteLiteral::RustByteString | ByteLiteral::Python => {
+                let mut literal = String::from("b\"");
+                for &b in bytes {
+                    match b {
+                        b'"' => literal.push_str("\\\""),
+                        b'\\' => literal.push_str("\\\\"),
+                        b'\n' => literal.push_str("\\n"),
+                        b'\r' => literal.push_str("\\r"),
+                        b'\t' => literal.push_str("\\t"),
+                        0x20..=0x7e => literal.push(b as char),
+                        _ => literal.push_str(&format!("\\x{:02x}", b)),
+                    }
+                }
+                literal.push('"');
+                literal
+            }
+        }
+    }
+}
+
+/// Where a chain that produced no final output stopped.
+#[derive(Clone, Copy)]
+enum ChainStop {
+    /// The input box did not hold valid hex for the input charset
+    Input,
+    /// The stage at this index returned an error
+    Stage(usize),
+}
+
+impl ChainStop {
+    fn describe(&self) -> String {
+        match self {
+            ChainStop::Input => "the input is not valid hex".to_string(),
+            ChainStop::Stage(idx) => format!("stage {} failed", idx + 1),
+        }
+    }
+}
 
 pub struct Pipeline {
-    modules: Vec<Box<dyn Module>>,
+    stages: Vec<Stage>,
     input_text: String,
+    /// When not UTF-8, the input box holds raw bytes as hex
+    input_charset: Charset,
+    /// When not UTF-8, the final text is re-encoded and shown as hex
+    output_charset: Charset,
+    /// Run the chain on one delimited column of each line, leaving the rest intact
+    column_mode: bool,
+    column_delimiter: String,
+    /// 1-based column index
+    column: usize,
+    /// Run the chain on the selected part of the input only
+    selection_mode: bool,
+    /// Last non-empty selection in the input box, as a char range. egui drops
+    /// the selection when the box loses focus, so it is remembered here.
+    selection: Option<std::ops::Range<usize>>,
+    /// Only rerun a stage when its input or settings change, instead of on
+    /// every frame; "Run" forces a full rerun. On by default so that large
+    /// inputs and slow modules do not reprocess on every repaint.
+    process_on_change: bool,
+    /// Show how long each stage took to process in its header
+    show_timings: bool,
+    /// What the final output should be; checked when not empty
+    expected_output: String,
+    /// The chain's result from the last frame, None when a stage failed
+    final_output: Option<String>,
     dragged_item_idx: Option<usize>,
 }
 
 impl Default for Pipeline {
     fn default() -> Self {
         Self {
-            modules: Vec::new(),
-            input_text: String::from("The quick brown fox jumps over the lazy dog."),
+            stages: Vec::new(),
+            input_text: String::from(PANGRAM),
+            input_charset: Charset::Utf8,
+            output_charset: Charset::Utf8,
+            column_mode: false,
+            column_delimiter: String::from(","),
+            column: 1,
+            selection_mode: false,
+            selection: None,
+            process_on_change: true,
+            show_timings: false,
+            final_output: None,
+            expected_output: String::new(),
             dragged_item_idx: None,
         }
     }
@@ -21,29 +576,329 @@ impl Default for Pipeline {
 impl Pipeline {
     pub fn add_module(&mut self, id: &str) {
         if let Some(module) = modules::create_module(id) {
-            self.modules.push(module);
+            self.stages.push(Stage::new(module));
+        }
+    }
+
+    /// Replace the stages with those translated from a CyberChef recipe,
+    /// returning warnings about operations that could not be imported.
+    pub fn import_cyberchef(&mut self, recipe: &str) -> Result<Vec<String>, String> {
+        let (imported, mut warnings) = cyberchef::parse_recipe(recipe)?;
+        self.stages.clear();
+        self.dragged_item_idx = None;
+        for stage in imported {
+            let Some(mut module) = modules::create_module(stage.id) else {
+                continue;
+            };
+            if let Err(e) = module.import_config(stage.config) {
+                warnings.push(format!("{}: {}", module.name(), e));
+            }
+            self.stages.push(Stage::new(module));
         }
+        Ok(warnings)
+    }
+
+    /// Save the stages as a recipe: each module's id and settings, plus its
+    /// repeat count.
+    pub fn to_json(&self) -> serde_json::Value {
+        let stages: Vec<serde_json::Value> = self
+            .stages
+            .iter()
+            .map(|stage| {
+                serde_json::json!({
+                    "id": stage.module.id(),
+                    "config": stage.module.export_config(),
+                    "repeat": stage.repeat,
+                    "enabled": stage.enabled,
+                    "variable": stage.variable,
+                })
+            })
+            .collect();
+        serde_json::json!({ "stages": stages })
+    }
+
+    /// Replace the stages with those from a recipe saved by `to_json`,
+    /// returning warnings about stages that could not be restored. Unknown
+    /// module ids are skipped.
+    pub fn from_json(&mut self, recipe: &str) -> Result<Vec<String>, String> {
+        let recipe: serde_json::Value =
+            serde_json::from_str(recipe).map_err(|e| format!("Invalid recipe: {}", e))?;
+        let Some(saved) = recipe.get("stages").and_then(|s| s.as_array()) else {
+            return Err("Invalid recipe: missing \"stages\" list".to_string());
+        };
+        let mut warnings = Vec::new();
+        self.stages.clear();
+        self.dragged_item_idx = None;
+        for (idx, saved) in saved.iter().enumerate() {
+            let id = saved.get("id").and_then(|id| id.as_str()).unwrap_or("");
+            let Some(mut module) = modules::create_module(id) else {
+                warnings.push(format!("Step {}: unknown module '{}' skipped", idx + 1, id));
+                continue;
+            };
+            if let Some(config) = saved.get("config") {
+                if let Err(e) = module.import_config(config.clone()) {
+                    warnings.push(format!("Step {} ({}): {}", idx + 1, module.name(), e));
+                }
+            }
+            let mut stage = Stage::new(module);
+            if let Some(repeat) = saved.get("repeat").and_then(|r| r.as_u64()) {
+                stage.repeat = repeat.clamp(1, 1000) as u32;
+            }
+            if let Some(enabled) = saved.get("enabled").and_then(|e| e.as_bool()) {
+                stage.enabled = enabled;
+            }
+            if let Some(variable) = saved.get("variable").and_then(|v| v.as_str()) {
+                if variable.trim().is_empty() || valid_variable_name(variable.trim()) {
+                    stage.variable = variable.to_string();
+                } else {
+                    warnings.push(format!(
+                        "Step {} ({}): variable name '{}' is not usable as ${{name}}; not saved",
+                        idx + 1,
+                        stage.module.name(),
+                        variable
+                    ));
+                }
+            }
+            self.stages.push(stage);
+        }
+        Ok(warnings)
+    }
+
+    /// The chain that would undo this one: the enabled stages last to first,
+    /// each with the settings that undo it. A stage that cannot be undone
+    /// gives an error naming it.
+    pub fn inverse_preview(&self) -> Vec<Result<String, String>> {
+        self.stages
+            .iter()
+            .rev()
+            .filter(|stage| stage.enabled)
+            .map(|stage| {
+                let module = &stage.module;
+                let name = match stage.repeat {
+                    1 => module.name().to_string(),
+                    repeat => format!("{} ×{}", module.name(), repeat),
+                };
+                match module.inverse() {
+                    None => Err(format!("{} cannot be undone", name)),
+                    Some(Inverse::PassThrough) => Ok(format!("{} (passes through)", name)),
+                    Some(Inverse::Itself) => Ok(format!("{} (undoes itself)", name)),
+                    Some(Inverse::Settings(inverse)) => {
+                        let mut changes = Vec::new();
+                        describe_changes("", &module.export_config(), &inverse, &mut changes);
+                        if changes.is_empty() {
+                            Ok(format!("{} (undoes itself)", name))
+                        } else {
+                            Ok(format!("{}: {}", name, changes.join(", ")))
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Replace the input text, e.g. with the clipboard contents
+    pub fn set_input(&mut self, text: String) {
+        self.input_text = text;
+        self.selection = None;
+    }
+
+    pub fn final_output(&self) -> Option<&str> {
+        self.final_output.as_deref()
+    }
+
+    /// Pipeline options shown in the settings window.
+    pub fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.process_on_change, "Process only on change")
+            .on_hover_text(
+                "Rerun a stage only when its input or settings change; saves CPU on heavy chains",
+            );
+        if !self.process_on_change {
+            for stage in &mut self.stages {
+                stage.cache = None;
+            }
+        }
+        ui.checkbox(&mut self.show_timings, "Show stage timings")
+            .on_hover_text("Show how long each stage takes to process, to find slow ones");
     }
 
     pub fn clear(&mut self) {
-        self.modules.clear();
-        self.input_text = String::from("The quick brown fox jumps over the lazy dog.");
+        self.stages.clear();
+        self.input_text = String::from(PANGRAM);
+        self.input_charset = Charset::Utf8;
+        self.output_charset = Charset::Utf8;
+        self.column_mode = false;
+        self.selection_mode = false;
+        self.selection = None;
+        self.expected_output.clear();
         self.dragged_item_idx = None;
     }
 
+    /// Render the pipeline as a numbered, human-readable recipe with each
+    /// stage's settings, for documenting a solution outside the app.
+    pub fn recipe_text(&self) -> String {
+        let mut lines = Vec::new();
+        if self.input_charset != Charset::Utf8 {
+            lines.push(format!(
+                "Input: hex bytes as {}",
+                self.input_charset.label()
+            ));
+        }
+        for (idx, stage) in self.stages.iter().enumerate() {
+            let mut line = format!("{}. {}", idx + 1, stage.module.name());
+            if let serde_json::Value::Object(config) = stage.module.export_config() {
+                let settings: Vec<String> = config
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect();
+                if !settings.is_empty() {
+                    line.push_str(&format!(" ({})", settings.join(", ")));
+                }
+            }
+            if stage.repeat > 1 {
+                line.push_str(&format!(" ×{}", stage.repeat));
+            }
+            if !stage.enabled {
+                line.push_str(" [bypassed]");
+            }
+            if let Some(name) = stage.saved_variable() {
+                line.push_str(&format!(" → ${{{}}}", name));
+            }
+            lines.push(line);
+        }
+        if self.output_charset != Charset::Utf8 {
+            lines.push(format!(
+                "Output: hex bytes as {}",
+                self.output_charset.label()
+            ));
+        }
+        if !self.expected_output.is_empty() {
+            lines.push(format!("Expected output: {}", self.expected_output));
+        }
+        lines.join("\n")
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         // Initial Input
+        let mut current_text = String::new();
+        // Where the chain stopped, if anywhere; nothing runs past that point
+        let mut failed: Option<ChainStop> = None;
         ui.group(|ui| {
-            ui.heading("Input");
-            ui.add(egui::TextEdit::multiline(&mut self.input_text).desired_width(f32::INFINITY));
+            ui.horizontal(|ui| {
+                ui.heading("Input");
+                ui.menu_button("🎲", |ui| {
+                    for sample in SampleInput::ALL {
+                        if ui.button(sample.label()).clicked() {
+                            self.input_text = sample.generate();
+                            self.selection = None;
+                            ui.close_menu();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Replace the input with sample text");
+                if self.process_on_change
+                    && ui
+                        .button("▶ Run")
+                        .on_hover_text("Rerun every stage, ignoring cached outputs")
+                        .clicked()
+                {
+                    for stage in &mut self.stages {
+                        stage.cache = None;
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                self.input_charset.combo(ui, "Input charset");
+                self.output_charset.combo(ui, "Output charset");
+            });
+            let input_box = egui::TextEdit::multiline(&mut self.input_text)
+                .desired_width(f32::INFINITY)
+                .show(ui);
+            if input_box.response.changed() {
+                self.selection = None;
+            }
+            if let Some(range) = input_box.cursor_range {
+                // Only reported while focused; a plain click clears the selection
+                let range = range.as_sorted_char_range();
+                self.selection = (!range.is_empty()).then_some(range);
+            }
+            if self.input_charset == Charset::Utf8 {
+                current_text = self.input_text.clone();
+                ui.small(text_statistics(&self.input_text));
+            } else {
+                ui.small(format!(
+                    "Enter the raw bytes as hex. Decoded as {}:",
+                    self.input_charset.label()
+                ));
+                match ByteFormat::Hex.parse(&self.input_text) {
+                    Ok(bytes) => {
+                        current_text = self.input_charset.decode(&bytes);
+                        ui.label(&current_text);
+                    }
+                    Err(e) => {
+                        error_box(ui, &format!("✗ Input is not valid hex: {}", e));
+                        failed = Some(ChainStop::Input);
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.column_mode, "Column mode")
+                    .on_hover_text("Process only one column of each line, e.g. of CSV data");
+                if self.column_mode {
+                    ui.label("Delimiter:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.column_delimiter).desired_width(40.0),
+                    );
+                    ui.label("Column:");
+                    ui.add(egui::DragValue::new(&mut self.column).range(1..=1000));
+                }
+            });
+            // Selections are made in the typed text, which is hex for other charsets
+            if self.input_charset == Charset::Utf8 {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.selection_mode, "Selection only")
+                        .on_hover_text("Process only the selected input, leaving the rest as is");
+                    if self.selection_mode {
+                        match &self.selection {
+                            Some(range) => ui.small(format!(
+                                "Characters {}-{}: {}",
+                                range.start + 1,
+                                range.end,
+                                preview(&current_text, range)
+                            )),
+                            None => ui.small("Select part of the input to process it alone"),
+                        };
+                    }
+                });
+            }
         });
 
+        // With a selection the stages only see the selected text
+        let whole_text = current_text.clone();
+        let selection = self
+            .selection
+            .clone()
+            .filter(|_| self.selection_mode && self.input_charset == Charset::Utf8)
+            .map(|range| clamp_range(&whole_text, range));
+        if let Some(range) = &selection {
+            current_text = whole_text
+                .chars()
+                .skip(range.start)
+                .take(range.len())
+                .collect();
+        }
+
+        // In column mode the stages see the selected cells, one per line
+        let source_text = current_text.clone();
+        let column_mode = self.column_mode && !self.column_delimiter.is_empty();
+        if column_mode {
+            current_text = extract_column(&source_text, &self.column_delimiter, self.column);
+        }
+
         ui.add_space(8.0);
         ui.separator();
         ui.add_space(8.0);
 
-        let mut current_text = self.input_text.clone();
-
         // Process through modules
         let mut remove_idx = None;
         let mut swap_request = None;
@@ -56,9 +911,26 @@ impl Pipeline {
         let mut next_dragged_idx = self.dragged_item_idx;
         let current_dragged_idx = self.dragged_item_idx;
 
-        let modules_len = self.modules.len();
+        let stages_len = self.stages.len();
+        let process_on_change = self.process_on_change;
+        // Settings only change in reaction to input, so their snapshots are
+        // only compared on frames with clicks, drags or key presses, not on
+        // repaints or plain pointer movement
+        let settings_may_change = ui.input(|i| {
+            i.pointer.any_down()
+                || i.pointer.any_released()
+                || i.events.iter().any(|event| {
+                    !matches!(
+                        event,
+                        egui::Event::PointerMoved(_) | egui::Event::MouseMoved(_)
+                    )
+                })
+        });
+        let show_timings = self.show_timings;
+        // Stage outputs saved under a name so far
+        let mut variables: HashMap<String, String> = HashMap::new();
 
-        for (idx, module) in self.modules.iter_mut().enumerate() {
+        for (idx, stage) in self.stages.iter_mut().enumerate() {
             let is_being_dragged = current_dragged_idx == Some(idx);
 
             ui.push_id(idx, |ui| {
@@ -84,29 +956,184 @@ impl Pipeline {
                             next_dragged_idx = Some(idx);
                         }
 
-                        ui.heading(module.name());
+                        ui.checkbox(&mut stage.enabled, "").on_hover_text(
+                            "Enable this stage; when off, its input passes through unchanged",
+                        );
+                        ui.add_enabled(
+                            stage.enabled,
+                            egui::Label::new(egui::RichText::new(stage.module.name()).heading()),
+                        );
+                        let info = ui.toggle_value(&mut stage.show_help, "ℹ");
+                        match modules::equivalent(stage.module.id()) {
+                            Some(equivalent) => info.on_hover_text(format!(
+                                "{}\n\nClick for help",
+                                equivalent_text(&equivalent)
+                            )),
+                            None => info.on_hover_text("Click for help"),
+                        };
+                        if let Some(elapsed) = stage.elapsed.filter(|_| show_timings) {
+                            ui.small(format!("{:.2} ms", elapsed.as_secs_f64() * 1000.0))
+                                .on_hover_text(
+                                    "Time this stage took to produce its output on the last frame",
+                                );
+                        }
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                             if ui.button("❌").clicked() {
                                 remove_idx = Some(idx);
                             }
+                            ui.add(
+                                egui::DragValue::new(&mut stage.repeat)
+                                    .range(1..=1000)
+                                    .prefix("×"),
+                            )
+                            .on_hover_text("Apply this module N times in a row");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut stage.variable)
+                                    .hint_text("save as")
+                                    .desired_width(70.0),
+                            )
+                            .on_hover_text(
+                                "Save this stage's output under a name; later stages can use it in their settings as ${name}",
+                            );
+                            if !stage.variable.trim().is_empty() && stage.saved_variable().is_none() {
+                                ui.colored_label(egui::Color32::from_rgb(220, 160, 0), "⚠")
+                                    .on_hover_text(
+                                        "Use only letters, digits and _ in the name; the output is not saved until then",
+                                    );
+                            }
                         });
                     });
 
-                    module.ui(ui);
-                    current_text = module.process(&current_text);
+                    if stage.show_help {
+                        ui.label(rust_i18n::t!(format!("help.{}", stage.module.id())));
+                        ui.separator();
+                    }
+                    stage.module.ui(ui);
+                    if settings_may_change {
+                        stage.track_config();
+                    }
+                    if let Some(failed) = failed {
+                        ui.separator();
+                        ui.weak(format!("Not run: {}", failed.describe()));
+                        return;
+                    }
+                    let enabled = stage.enabled;
+                    let output = if !enabled {
+                        stage.elapsed = None;
+                        Ok(current_text.clone())
+                    } else if let Err(e) = stage.resolve_variables(&variables) {
+                        Err(e)
+                    } else if process_on_change {
+                        // Keeps the time of the run that filled the cache
+                        stage.run_cached(&current_text, column_mode)
+                    } else {
+                        let started = Instant::now();
+                        let output = stage.run(&current_text, column_mode);
+                        stage.elapsed = Some(started.elapsed());
+                        output
+                    };
+                    let output = match output {
+                        Ok(output) => output,
+                        Err(e) => {
+                            ui.separator();
+                            error_box(ui, &format!("✗ {}", e));
+                            failed = Some(ChainStop::Stage(idx));
+                            return;
+                        }
+                    };
+                    if enabled {
+                        stage.module.inspect_ui(ui, &current_text, &output);
+                        // e.g. a recovered key; rerun with it on the next frame
+                        if settings_may_change && stage.track_config() {
+                            ui.ctx().request_repaint();
+                        }
+                    }
 
                     ui.separator();
                     ui.horizontal(|ui| {
                         ui.label("Output:");
                         if ui.button("📋").on_hover_text("Copy to clipboard").clicked() {
-                            ui.output_mut(|o| o.copied_text = current_text.clone());
+                            let copied = stage.edited_output.as_ref().unwrap_or(&output).clone();
+                            ui.output_mut(|o| o.copied_text = copied);
+                        }
+                        ui.menu_button("⏷", |ui| {
+                            ui.label("Copy output bytes as:");
+                            for literal in ByteLiteral::ALL {
+                                if ui.button(literal.label()).clicked() {
+                                    // Text decoded from bytes that are not UTF-8 lost them
+                                    // to U+FFFD, so take binary output from the module
+                                    let binary = enabled && stage.active_module().binary_output();
+                                    let bytes = match &stage.edited_output {
+                                        Some(edited) => edited.as_bytes().to_vec(),
+                                        None if binary => stage
+                                            .run_bytes_cached(&current_text, column_mode)
+                                            .unwrap_or_else(|_| output.as_bytes().to_vec()),
+                                        None => output.as_bytes().to_vec(),
+                                    };
+                                    let copied = literal.format(&bytes);
+                                    ui.output_mut(|o| o.copied_text = copied);
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Copy as a byte array literal for source code");
+                        let mut editing = stage.edited_output.is_some();
+                        if ui
+                            .toggle_value(&mut editing, "✏ Edit and branch")
+                            .on_hover_text(
+                                "Edit this output by hand; later stages use the edited text",
+                            )
+                            .changed()
+                        {
+                            stage.edited_output = editing.then(|| output.clone());
+                        }
+                        if enabled && stage.active_module().binary_output() {
+                            ui.toggle_value(&mut stage.show_bytes, "Hex too").on_hover_text(
+                                "Also show the output bytes as hex; the text replaces bytes that are not UTF-8 with �",
+                            );
                         }
                     });
-                    ui.add(
-                        egui::TextEdit::multiline(&mut current_text)
-                            .interactive(false)
-                            .desired_width(f32::INFINITY),
-                    );
+                    current_text = match &mut stage.edited_output {
+                        Some(edited) if enabled => {
+                            ui.add(egui::TextEdit::multiline(edited).desired_width(f32::INFINITY));
+                            if *edited != output {
+                                ui.small("Edited: later stages start from this text");
+                            }
+                            edited.clone()
+                        }
+                        _ => {
+                            let mut output = output;
+                            let mut text_box = egui::TextEdit::multiline(&mut output)
+                                .interactive(false)
+                                .desired_width(f32::INFINITY);
+                            if !enabled {
+                                text_box = text_box.text_color(ui.visuals().weak_text_color());
+                            }
+                            ui.add(text_box);
+                            if !enabled {
+                                ui.small("Bypassed: the input passes through unchanged");
+                            } else if stage.show_bytes && stage.active_module().binary_output() {
+                                if let Ok(bytes) = stage.run_bytes_cached(&current_text, column_mode)
+                                {
+                                    let hex: Vec<String> =
+                                        bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                                    ui.add(
+                                        egui::TextEdit::multiline(&mut hex.join(" ").as_str())
+                                            .font(egui::TextStyle::Monospace)
+                                            .desired_width(f32::INFINITY),
+                                    );
+                                    if std::str::from_utf8(&bytes).is_err() {
+                                        ui.small("Not valid UTF-8: the text above is lossy");
+                                    }
+                                }
+                            }
+                            output
+                        }
+                    };
+                    if let Some(name) = stage.saved_variable() {
+                        variables.insert(name.to_string(), current_text.clone());
+                    }
                 });
 
                 // Swap logic: if dragging and hovering over another item
@@ -124,7 +1151,7 @@ impl Pipeline {
             ui.add_space(8.0);
 
             // Draw arrow between modules
-            if idx < modules_len - 1 {
+            if idx < stages_len - 1 {
                 ui.vertical_centered(|ui| {
                     ui.label("⬇");
                 });
@@ -132,10 +1159,92 @@ impl Pipeline {
             }
         }
 
+        if let Some(failed) = failed {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("✗ The chain stopped: {}", failed.describe()),
+            );
+        }
+
+        if column_mode && failed.is_none() {
+            current_text = replace_column(
+                &source_text,
+                &self.column_delimiter,
+                self.column,
+                &current_text,
+            );
+            ui.group(|ui| {
+                ui.heading("Reassembled output");
+                ui.add(
+                    egui::TextEdit::multiline(&mut current_text.as_str())
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        }
+
+        if let Some(range) = selection.as_ref().filter(|_| failed.is_none()) {
+            current_text = splice_chars(&whole_text, range, &current_text);
+            ui.group(|ui| {
+                ui.heading("Output with selection replaced");
+                ui.add(
+                    egui::TextEdit::multiline(&mut current_text.as_str())
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        }
+
+        if self.output_charset != Charset::Utf8 && failed.is_none() {
+            ui.group(|ui| {
+                ui.heading(format!("Output bytes ({})", self.output_charset.label()));
+                let mut hex = ByteFormat::Hex.format(&self.output_charset.encode(&current_text));
+                ui.add(
+                    egui::TextEdit::multiline(&mut hex)
+                        .interactive(false)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        }
+
+        self.final_output = failed.is_none().then(|| current_text.clone());
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Expected output:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.expected_output)
+                        .hint_text("Pin the final result to check the chain against")
+                        .desired_width(f32::INFINITY),
+                );
+            });
+            if !self.expected_output.is_empty() {
+                if let Some(failed) = failed {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("✗ No final output: {}", failed.describe()),
+                    );
+                } else if current_text == self.expected_output {
+                    ui.colored_label(egui::Color32::GREEN, "✓ Final output matches");
+                } else {
+                    let differs_at = current_text
+                        .chars()
+                        .zip(self.expected_output.chars())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!(
+                            "✗ Final output differs from character {} on",
+                            differs_at + 1
+                        ),
+                    );
+                }
+            }
+        });
+
         self.dragged_item_idx = next_dragged_idx;
 
         if let Some(idx) = remove_idx {
-            self.modules.remove(idx);
+            self.stages.remove(idx);
             // If we removed the dragged item, reset drag state
             if self.dragged_item_idx == Some(idx) {
                 self.dragged_item_idx = None;
@@ -148,9 +1257,425 @@ impl Pipeline {
         }
 
         if let Some((from, to)) = swap_request {
-            self.modules.swap(from, to);
+            self.stages.swap(from, to);
             // Update dragged index to follow the item
             self.dragged_item_idx = Some(to);
         }
     }
 }
+
+/// An error message in a red-bordered box.
+fn error_box(ui: &mut egui::Ui, message: &str) {
+    egui::Frame::none()
+        .fill(ui.visuals().extreme_bg_color)
+        .stroke(egui::Stroke::new(1.0, ui.visuals().error_fg_color))
+        .rounding(4.0)
+        .inner_margin(6.0)
+        .show(ui, |ui| {
+            ui.colored_label(ui.visuals().error_fg_color, message);
+        });
+}
+
+/// Hover text listing a module's names in other tools.
+fn equivalent_text(equivalent: &modules::Equivalent) -> String {
+    let mut lines = Vec::new();
+    if let Some(name) = equivalent.cyberchef {
+        lines.push(format!("CyberChef: {}", name));
+    }
+    if let Some(name) = equivalent.dcode {
+        lines.push(format!("dCode: {}", name));
+    }
+    if let Some(note) = equivalent.note {
+        lines.push(format!("Note: {}", note));
+    }
+    lines.join("\n")
+}
+
+/// Limit a char range to the length of `text`.
+fn clamp_range(text: &str, range: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let len = text.chars().count();
+    range.start.min(len)..range.end.min(len)
+}
+
+/// `text` with the chars in `range` replaced by `replacement`.
+fn splice_chars(text: &str, range: &std::ops::Range<usize>, replacement: &str) -> String {
+    let before: String = text.chars().take(range.start).collect();
+    let after: String = text.chars().skip(range.end).collect();
+    before + replacement + &after
+}
+
+/// The selected text, shortened for display.
+fn preview(text: &str, range: &std::ops::Range<usize>) -> String {
+    let range = clamp_range(text, range.clone());
+    let selected: String = text
+        .chars()
+        .skip(range.start)
+        .take(range.len().min(40))
+        .collect();
+    if range.len() > 40 {
+        format!("\"{}…\"", selected)
+    } else {
+        format!("\"{}\"", selected)
+    }
+}
+
+/// The `column`th (1-based) cell of every line, one per line; lines without
+/// that column give an empty cell.
+fn extract_column(text: &str, delimiter: &str, column: usize) -> String {
+    text.split('\n')
+        .map(|line| line.split(delimiter).nth(column - 1).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Pass a column-mode cell through if it is still one line; any extra line
+/// would shift every later row when the cells are put back.
+fn single_line(cell: String) -> Result<String, ProcessError> {
+    match cell.matches('\n').count() {
+        0 => Ok(cell),
+        breaks => Err(ProcessError::InvalidInput(format!(
+            "Column mode needs one output line per input line, but a cell became {} lines",
+            breaks + 1
+        ))),
+    }
+}
+
+/// Put processed `cells` (one per line) back into the `column`th cell of each
+/// line of `text`. Lines without that column are left as they were.
+fn replace_column(text: &str, delimiter: &str, column: usize, cells: &str) -> String {
+    let mut cells = cells.split('\n');
+    text.split('\n')
+        .map(|line| {
+            let cell = cells.next().unwrap_or_default();
+            let mut fields: Vec<&str> = line.split(delimiter).collect();
+            if let Some(field) = fields.get_mut(column - 1) {
+                *field = cell;
+            }
+            fields.join(delimiter)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Summarize the length of a text the ways that matter for ciphertext:
+/// characters, bytes, words and lines, plus whether it is pure ASCII.
+fn text_statistics(text: &str) -> String {
+    let encoding = if text.is_ascii() { "ASCII" } else { "UTF-8" };
+    format!(
+        "{} chars · {} bytes · {} words · {} lines · {}",
+        text.chars().count(),
+        text.len(),
+        text.split_whitespace().count(),
+        text.lines().count(),
+        encoding
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Appends `suffix` and counts how often `process` runs
+    #[derive(Default, Serialize, Deserialize)]
+    struct CountingModule {
+        suffix: String,
+        #[serde(skip)]
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Module for CountingModule {
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        fn id(&self) -> &str {
+            "counting"
+        }
+
+        fn process(&self, input: &str) -> Result<String, ProcessError> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(format!("{}{}", input, self.suffix))
+        }
+
+        fn ui(&mut self, _ui: &mut egui::Ui) {}
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    fn run_chain(stages: &mut [Stage], input: &str) -> String {
+        stages.iter_mut().fold(input.to_string(), |text, stage| {
+            stage.run_cached(&text, false).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_cached_chain_processes_once_per_module_per_change() {
+        let calls = Rc::new(Cell::new(0));
+        let mut stages: Vec<Stage> = (0..5)
+            .map(|_| {
+                Stage::new(Box::new(CountingModule {
+                    suffix: String::new(),
+                    calls: calls.clone(),
+                }))
+            })
+            .collect();
+        let input = "x".repeat(100_000);
+
+        // Redrawing without changes reuses every output
+        for _ in 0..10 {
+            run_chain(&mut stages, &input);
+        }
+        assert_eq!(calls.get(), 5);
+
+        // New input reruns every stage once
+        let input = "y".repeat(100_000);
+        for _ in 0..10 {
+            run_chain(&mut stages, &input);
+        }
+        assert_eq!(calls.get(), 10);
+
+        // New settings in the third stage rerun it and the two after it
+        stages[2]
+            .module
+            .as_any_mut()
+            .downcast_mut::<CountingModule>()
+            .unwrap()
+            .suffix = "!".to_string();
+        assert!(stages[2].track_config());
+        for _ in 0..10 {
+            assert!(run_chain(&mut stages, &input).ends_with('!'));
+        }
+        assert_eq!(calls.get(), 13);
+
+        // Snapshotting unchanged settings is not a change
+        assert!(!stages[2].track_config());
+        run_chain(&mut stages, &input);
+        assert_eq!(calls.get(), 13);
+    }
+
+    #[test]
+    fn test_repeat_applies_module_n_times() {
+        let mut stage = Stage::new(modules::create_module("rot13").unwrap());
+        assert_eq!(stage.run("Hello", false).unwrap(), "Uryyb");
+        stage.repeat = 2;
+        assert_eq!(stage.run("Hello", false).unwrap(), "Hello");
+        assert_eq!(stage.run("Hello\nWorld", true).unwrap(), "Hello\nWorld");
+    }
+
+    #[test]
+    fn test_latin1_byte_e9_is_e_acute() {
+        assert_eq!(Charset::Latin1.decode(&[0xe9]), "é");
+        assert_eq!(Charset::Latin1.encode("é"), [0xe9]);
+        assert_eq!(Charset::Windows1252.decode(&[0xe9, 0x80]), "é€");
+        assert_eq!(Charset::Windows1252.encode("é€"), [0xe9, 0x80]);
+        assert_eq!(Charset::Latin1.encode("€"), b"?");
+    }
+
+    /// Lay the pipeline out once in a headless frame, as the app does.
+    fn render(pipeline: &mut Pipeline) {
+        let _ = egui::Context::default().run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| pipeline.ui(ui));
+        });
+    }
+
+    #[test]
+    fn test_column_mode_processes_second_column() {
+        let rows = "1,hello,x\n2,world,y\n3";
+        let mut pipeline = Pipeline {
+            stages: vec![Stage::new(modules::create_module("rot13").unwrap())],
+            column_mode: true,
+            column: 2,
+            ..Default::default()
+        };
+        pipeline.set_input(rows.to_string());
+        render(&mut pipeline);
+        assert_eq!(pipeline.final_output(), Some("1,uryyb,x\n2,jbeyq,y\n3"));
+
+        // Wrapped Base64 turns each cell into several lines, which would
+        // shift every later row when the cells are put back
+        let mut base64 = modules::create_module("base64").unwrap();
+        let mut config = base64.export_config();
+        config["wrap"] = 4.into();
+        base64.import_config(config).unwrap();
+        let stage = Stage::new(base64);
+        let cells = extract_column(rows, ",", 2);
+        assert_eq!(stage.run(&cells, false).unwrap().lines().count(), 4);
+        assert!(matches!(
+            stage.run(&cells, true),
+            Err(ProcessError::InvalidInput(_))
+        ));
+        pipeline.stages = vec![stage];
+        render(&mut pipeline);
+        assert_eq!(pipeline.final_output(), None);
+    }
+
+    #[test]
+    fn test_variable_as_vigenere_key() {
+        let vigenere = |key: &str| {
+            let mut module = modules::create_module("vigenere").unwrap();
+            let mut config = module.export_config();
+            config["key"] = key.into();
+            module.import_config(config).unwrap();
+            module
+        };
+        // The first stage's output is saved as `k` and keys the third
+        let mut stages = vec![
+            Stage::new(modules::create_module("rot13").unwrap()),
+            Stage::new(modules::create_module("rot13").unwrap()),
+            Stage::new(vigenere("${k}")),
+        ];
+        stages[0].variable = "k".to_string();
+        let run = |stages: &mut [Stage], input: &str| {
+            let mut variables = HashMap::new();
+            let mut text = input.to_string();
+            for stage in stages.iter_mut() {
+                stage.resolve_variables(&variables)?;
+                text = stage.run_cached(&text, false)?;
+                if let Some(name) = stage.saved_variable() {
+                    variables.insert(name.to_string(), text.clone());
+                }
+            }
+            Ok::<_, ProcessError>(text)
+        };
+
+        let expected = vigenere("LEMON").process("YRZBA").unwrap();
+        assert_eq!(run(&mut stages, "YRZBA").unwrap(), expected);
+        // A new value for the variable re-keys the later stage
+        let expected = vigenere("LIME").process("YVZR").unwrap();
+        assert_eq!(run(&mut stages, "YVZR").unwrap(), expected);
+
+        stages[0].variable.clear();
+        assert!(matches!(
+            run(&mut stages, "YRZBA"),
+            Err(ProcessError::BadConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_variable_names_must_be_referenceable() {
+        for name in ["k", "key_2", " k "] {
+            let stage = Stage {
+                variable: name.to_string(),
+                ..Stage::new(modules::create_module("rot13").unwrap())
+            };
+            assert_eq!(stage.saved_variable(), Some(name.trim()));
+        }
+        for name in ["", "my key", "k}", "${k}"] {
+            let stage = Stage {
+                variable: name.to_string(),
+                ..Stage::new(modules::create_module("rot13").unwrap())
+            };
+            assert_eq!(stage.saved_variable(), None, "{name:?}");
+        }
+
+        let mut pipeline = Pipeline::default();
+        let warnings = pipeline
+            .from_json(r#"{"stages": [{"id": "rot13", "variable": "my key"}]}"#)
+            .unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(pipeline.stages[0].variable, "");
+    }
+
+    #[test]
+    fn test_cache_key_follows_resolved_module_state() {
+        let mut module = modules::create_module("block_cipher").unwrap();
+        module
+            .import_config(serde_json::json!({ "key": "${k}", "random_iv": true }))
+            .unwrap();
+        let mut stage = Stage::new(module);
+        let variables = HashMap::from([("k".to_string(), "0123456789abcdef".to_string())]);
+        stage.resolve_variables(&variables).unwrap();
+
+        // Drawing a random IV changes only the resolved copy of the module
+        let before = stage.cache_key("attack", false);
+        stage.active_module().process("attack").unwrap();
+        assert_ne!(stage.cache_key("attack", false), before);
+    }
+
+    #[test]
+    fn test_out_of_range_recipe_settings_are_errors() {
+        // Values no settings widget can produce, as a hand-edited recipe may have
+        let recipe = r#"{"stages": [
+            {"id": "byteorder", "config": {"word_size": 0}},
+            {"id": "polybius", "config": {"size": 0}},
+            {"id": "polybius", "config": {"size": 7, "mode": "Decode"}},
+            {"id": "bifid", "config": {"size": 0}},
+            {"id": "nihilist", "config": {"size": 65535}},
+            {"id": "a1z26", "config": {"offset": 4294967295}},
+            {"id": "scytale", "config": {"columns": 18446744073709551615}}
+        ]}"#;
+        let mut pipeline = Pipeline::default();
+        assert_eq!(pipeline.from_json(recipe), Ok(Vec::new()));
+        let (scytale, rest) = pipeline.stages.split_last().unwrap();
+        for stage in rest {
+            assert!(
+                matches!(
+                    stage.run("11 23 45", false),
+                    Err(ProcessError::BadConfig(_))
+                ),
+                "{}",
+                stage.module.id()
+            );
+        }
+        // Columns beyond the text length leave it as it is
+        assert_eq!(scytale.run("11 23 45", false).unwrap(), "11 23 45");
+    }
+
+    #[test]
+    fn test_elapsed_kept_on_cache_hits() {
+        let mut stage = Stage::new(modules::create_module("rot13").unwrap());
+        assert_eq!(stage.elapsed, None);
+        stage.run_cached("slow stage", false).unwrap();
+        let first = stage.elapsed.expect("timed on a cache miss");
+        stage.run_cached("slow stage", false).unwrap();
+        assert_eq!(stage.elapsed, Some(first));
+        stage.run_cached("new input", false).unwrap();
+        assert!(stage.elapsed.is_some());
+    }
+
+    #[test]
+    fn test_inverse_preview() {
+        let stage = |id: &str, config: serde_json::Value| {
+            let mut module = modules::create_module(id).unwrap();
+            module.import_config(config).unwrap();
+            Stage::new(module)
+        };
+        let mut pipeline = Pipeline {
+            stages: vec![
+                stage("base64", serde_json::Value::Null),
+                stage("condition", serde_json::Value::Null),
+                stage("rot13", serde_json::Value::Null),
+                stage("gray", serde_json::Value::Null),
+                stage("split_apply", serde_json::Value::Null),
+                stage("caesar", serde_json::json!({ "brute_force": true })),
+                stage(
+                    "split_apply",
+                    serde_json::json!({ "join_with": ";", "inner": { "id": "base32" } }),
+                ),
+            ],
+            ..Default::default()
+        };
+        pipeline.stages[2].repeat = 2;
+        pipeline.stages[3].enabled = false;
+        assert_eq!(
+            pipeline.inverse_preview(),
+            [
+                Ok(r#"Split & Apply: split_on: "," → ";", join_with: ";" → ",", inner.config.mode: Encode → Decode"#.to_string()),
+                Err("Caesar Cipher cannot be undone".to_string()),
+                Err("Split & Apply cannot be undone".to_string()),
+                Ok("ROT13 ×2 (undoes itself)".to_string()),
+                Ok("Condition (passes through)".to_string()),
+                Ok("Base64: mode: Encode → Decode".to_string()),
+            ]
+        );
+    }
+}